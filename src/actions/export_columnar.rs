@@ -0,0 +1,14 @@
+use chrono::Local;
+
+use crate::{datasets::AllDatasets, utils::time};
+
+/// Exports every dataset already on disk as Parquet and Arrow IPC, see `AnyDataset::export_columnar`
+pub fn export_columnar() -> color_eyre::Result<()> {
+    println!("{:?} - Exporting Parquet and Arrow files...", Local::now());
+
+    let datasets = AllDatasets::import()?;
+
+    time("Parquet and Arrow files exported", || {
+        datasets.export_columnar()
+    })
+}
@@ -0,0 +1,12 @@
+use chrono::Local;
+
+use crate::{datasets::AllDatasets, utils::time};
+
+/// Exports every dataset already on disk as CSV, see `AnyDataset::export_csv`
+pub fn export_csv() -> color_eyre::Result<()> {
+    println!("{:?} - Exporting CSVs...", Local::now());
+
+    let datasets = AllDatasets::import()?;
+
+    time("CSVs exported", || datasets.export_csv())
+}
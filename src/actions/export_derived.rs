@@ -0,0 +1,12 @@
+use chrono::Local;
+
+use crate::{datasets::AllDatasets, utils::time};
+
+/// Computes the series of `derived.json` from the datasets on disk, see `AllDatasets::export_derived`
+pub fn export_derived() -> color_eyre::Result<()> {
+    println!("{:?} - Exporting derived series...", Local::now());
+
+    let datasets = AllDatasets::import()?;
+
+    time("Derived series exported", || datasets.export_derived())
+}
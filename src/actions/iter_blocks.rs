@@ -22,19 +22,20 @@ pub fn iter_blocks(bitcoin_db: &BitcoinDB, block_count: usize) -> color_eyre::Re
 
     let mut datasets = AllDatasets::import()?;
 
-    let min_initial_first_unsafe_address_date = datasets
-        .address
-        .get_min_initial_state()
-        .first_unsafe_date
-        .as_ref()
-        .cloned();
-
-    let min_initial_first_unsafe_address_height = datasets
-        .address
-        .get_min_initial_state()
-        .first_unsafe_height
-        .as_ref()
-        .cloned();
+    // The address states are computed for the currencies too
+    let address_datasets = datasets.address_datasets();
+
+    let min_initial_first_unsafe_address_date = address_datasets
+        .iter()
+        .map(|address| address.get_min_initial_state().first_unsafe_date)
+        .min()
+        .flatten();
+
+    let min_initial_first_unsafe_address_height = address_datasets
+        .iter()
+        .map(|address| address.get_min_initial_state().first_unsafe_height)
+        .min()
+        .flatten();
 
     println!("{:?} - Imported datasets", Local::now());
 
@@ -42,7 +43,15 @@ pub fn iter_blocks(bitcoin_db: &BitcoinDB, block_count: usize) -> color_eyre::Re
 
     println!("{:?} - Imported databases", Local::now());
 
-    let mut states = States::import().unwrap_or_default();
+    // States whose layout changed (or which are damaged) can't be imported, they're all reset then
+    // since `find_first_unsafe_height` starts over without a date
+    let mut states = States::import().unwrap_or_else(|report| {
+        println!("Couldn't import the states, starting over: {report}");
+
+        States::default()
+    });
+
+    states.init_currencies(&datasets.currencies.currencies());
 
     println!("{:?} - Imported states", Local::now());
 
@@ -182,6 +191,9 @@ pub fn iter_blocks(bitcoin_db: &BitcoinDB, block_count: usize) -> color_eyre::Re
 
     if export {
         datasets.export()?;
+
+        // Once per run, reading the first and last values of every map isn't free
+        datasets.export_catalog()?;
     }
 
     Ok(())
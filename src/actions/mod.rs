@@ -1,9 +1,19 @@
 mod export_all;
+#[cfg(feature = "columnar")]
+mod export_columnar;
+mod export_csv;
+mod export_derived;
 mod iter_blocks;
 mod min_height;
 mod parse_block;
+mod verify;
 
 pub use export_all::*;
+#[cfg(feature = "columnar")]
+pub use export_columnar::*;
+pub use export_csv::*;
+pub use export_derived::*;
 pub use iter_blocks::*;
 pub use min_height::*;
 pub use parse_block::*;
+pub use verify::*;
@@ -10,16 +10,19 @@ use itertools::Itertools;
 use rayon::prelude::*;
 
 use crate::{
-    bitcoin::BitcoinDB,
+    bitcoin::{unclaimed_reward, BitcoinDB},
     databases::{AddressIndexToEmptyAddressData, AddressToAddressIndex, Databases, TxidToTxIndex},
-    datasets::{AllDatasets, ProcessedBlockData},
+    datasets::{AllDatasets, ProcessedBlockData, ProcessedCurrencyData},
     parse::{
-        Address, AddressData, AddressRealizedData, BlockData, BlockPath, Counter, EmptyAddressData,
-        PartialTxoutData, TxData, TxoutIndex,
+        Address, AddressData, AddressRealizedData, BlockData, BlockPath, CoinJoin, CoinJoinsData,
+        Counter, EmptyAddressData, InscriptionsData, PartialTxoutData, SplitByTxShape, TxData,
+        TxShape, TxoutIndex,
     },
+    price::Currency,
     states::{
         AddressCohortsInputStates, AddressCohortsOutputStates, AddressCohortsRealizedStates,
-        States, UTXOCohortsOneShotStates, UTXOCohortsReceivedStates, UTXOCohortsSentStates,
+        DateDataVec, States, UTXOCohortsDurableStates, UTXOCohortsOneShotStates,
+        UTXOCohortsReceivedStates, UTXOCohortsSentStates,
     },
 };
 
@@ -90,15 +93,18 @@ pub fn parse_block(
         block_index: block_index as u16,
     };
 
-    let block_price = datasets
+    // States work in f64, values are only narrowed to f32 when inserted in the datasets
+    let block_price: f64 = datasets
         .price
         .height_to_close(height, timestamp)
-        .unwrap_or_else(|_| panic!("Expect {height} to have a price"));
+        .unwrap_or_else(|_| panic!("Expect {height} to have a price"))
+        .into();
 
-    let date_price = datasets
+    let date_price: f64 = datasets
         .price
         .date_to_close(date)
-        .unwrap_or_else(|_| panic!("Expect {date} to have a price"));
+        .unwrap_or_else(|_| panic!("Expect {date} to have a price"))
+        .into();
 
     states
         .date_data_vec
@@ -107,6 +113,10 @@ pub fn parse_block(
         .blocks
         .push(BlockData::new(height as u32, block_price, timestamp));
 
+    let pool = datasets.pools.pools.find(block.coinbase().unwrap());
+
+    let currencies = datasets.currencies.currencies();
+
     let mut block_path_to_spent_data: BTreeMap<BlockPath, SpentData> = BTreeMap::new();
     let mut block_path_to_received_data: BTreeMap<BlockPath, ReceivedData> = BTreeMap::new();
     let mut address_index_to_address_realized_data: BTreeMap<u32, AddressRealizedData> =
@@ -122,13 +132,18 @@ pub fn parse_block(
     let mut transaction_count = 0;
     let mut fees = vec![];
     let mut fees_total = 0;
+    let mut tx_shapes: SplitByTxShape<SpentData> = SplitByTxShape::default();
+    let mut coinjoins = CoinJoinsData::default();
+    let mut inscriptions = InscriptionsData::default();
+    let mut unspendable = 0;
+    let mut coinbase_outputs_total = 0;
 
     let (
         (
             TxoutsParsingResults {
                 op_returns: _op_returns,
                 mut partial_txout_data_vec,
-                provably_unspendable: _provably_unspendable,
+                provably_unspendable,
             },
             mut empty_address_index_to_empty_address_data,
         ),
@@ -137,6 +152,7 @@ pub fn parse_block(
         let output_handle = scope.spawn(|| {
             let mut txouts_parsing_results = parse_txouts(
                 &block,
+                height == 0,
                 compute_addresses,
                 &mut states.counters.unknown_addresses,
                 &mut states.counters.empty_addresses,
@@ -190,6 +206,20 @@ pub fn parse_block(
 
         let is_coinbase = tx.is_coinbase();
 
+        let tx_shape = TxShape::from_tx(&tx);
+
+        if is_coinbase {
+            coinbase_outputs_total = tx.output.iter().map(|txout| txout.value.to_sat()).sum();
+        }
+
+        let has_inscription = inscriptions.push_tx(&tx);
+
+        let coinjoin = if is_coinbase {
+            None
+        } else {
+            CoinJoin::detect(tx.input.len(), &tx.output)
+        };
+
         let mut inputs_sum = 0;
         let mut outputs_sum = 0;
 
@@ -222,6 +252,14 @@ pub fn parse_block(
 
                 states.txout_index_to_sats.insert(txout_index, sats);
 
+                if let Some(coinjoin) = coinjoin.as_ref() {
+                    if coinjoin.denomination == sats {
+                        states
+                            .txout_index_to_coinjoin_anonset
+                            .insert(txout_index, coinjoin.anonset as u16);
+                    }
+                }
+
                 if compute_addresses {
                     let address = address.unwrap();
 
@@ -310,10 +348,23 @@ pub fn parse_block(
                     // MUST be before received !
                     let address_realized_data = address_index_to_address_realized_data
                         .entry(address_index)
-                        .or_insert_with(|| AddressRealizedData::default(address_data));
+                        .or_insert_with(|| {
+                            AddressRealizedData::default(address_data).with_initial_realized_caps(
+                                states
+                                    .address_index_to_realized_cap_by_currency
+                                    .get_all(address_index),
+                            )
+                        });
 
                     address_data.receive(sats, block_price);
 
+                    states.address_index_to_realized_cap_by_currency.receive(
+                        address_index,
+                        sats,
+                        &currencies,
+                        |currency| currency.convert(block_price, date),
+                    );
+
                     block_path_to_received_data
                         .entry(block_path)
                         .or_default()
@@ -329,6 +380,11 @@ pub fn parse_block(
 
         if is_coinbase {
             coinbase = non_zero_amount;
+
+            if height == 0 {
+                coinbase = coinbase_outputs_total;
+                unspendable += coinbase_outputs_total;
+            }
         } else {
             outputs_sum += non_zero_amount;
         }
@@ -340,7 +396,18 @@ pub fn parse_block(
         if spendable_outputs != 0 {
             last_block.spendable_outputs += spendable_outputs as u32;
 
-            databases.txid_to_tx_index.insert(&txid, tx_index);
+            // `insert` only knows about the puts since the last export so the database is read too,
+            // only for coinbases since they're the only ones which can share a txid
+            let overwritten_tx_index = if is_coinbase {
+                databases.txid_to_tx_index.safe_get(&txid).cloned()
+            } else {
+                None
+            };
+
+            if databases.txid_to_tx_index.insert(&txid, tx_index).is_some() && !is_coinbase {
+                dbg!(txid, tx_index);
+                panic!("Only coinbases can share a txid");
+            }
 
             states.tx_index_to_tx_data.insert(
                 tx_index,
@@ -349,6 +416,112 @@ pub fn parse_block(
                     spendable_outputs,
                 ),
             );
+
+            // BIP30: the coinbases of 91842 and 91880 have the same txids as the ones of 91812 and 91722
+            // The outputs of the latter can't be spent anymore and become unspendable
+            if let Some(overwritten_tx_index) = overwritten_tx_index {
+                let overwritten_block_path = states
+                    .tx_index_to_tx_data
+                    .remove(&overwritten_tx_index)
+                    .unwrap_or_else(|| {
+                        dbg!(txid, overwritten_tx_index);
+                        panic!("Overwritten tx to be in tx_index_to_tx_data")
+                    })
+                    .block_path;
+
+                let overwritten_block_data = states
+                    .date_data_vec
+                    .get_mut(overwritten_block_path.date_index as usize)
+                    .unwrap()
+                    .blocks
+                    .get_mut(overwritten_block_path.block_index as usize)
+                    .unwrap();
+
+                let overwritten_txout_indexes = states
+                    .txout_index_to_sats
+                    .range(
+                        TxoutIndex::new(overwritten_tx_index, 0)
+                            ..=TxoutIndex::new(overwritten_tx_index, u16::MAX),
+                    )
+                    .map(|(txout_index, _)| *txout_index)
+                    .collect_vec();
+
+                overwritten_txout_indexes
+                    .into_iter()
+                    .for_each(|overwritten_txout_index| {
+                        let sats = states
+                            .txout_index_to_sats
+                            .remove(&overwritten_txout_index)
+                            .unwrap();
+
+                        unspendable += sats;
+
+                        overwritten_block_data.amount -= sats;
+                        overwritten_block_data.spendable_outputs -= 1;
+
+                        states
+                            .txout_index_to_coinjoin_anonset
+                            .remove(&overwritten_txout_index);
+
+                        if compute_addresses {
+                            let address_index = states
+                                .txout_index_to_address_index
+                                .remove(&overwritten_txout_index)
+                                .unwrap();
+
+                            let address_data = states
+                                .address_index_to_address_data
+                                .get_mut(&address_index)
+                                .unwrap();
+
+                            // Tracked so that the address cohorts are updated, but the coins were never
+                            // spent so nothing is sent or realized
+                            address_index_to_address_realized_data
+                                .entry(address_index)
+                                .or_insert_with(|| {
+                                    AddressRealizedData::default(address_data)
+                                        .with_initial_realized_caps(
+                                            states
+                                                .address_index_to_realized_cap_by_currency
+                                                .get_all(address_index),
+                                        )
+                                });
+
+                            states
+                                .address_index_to_realized_cap_by_currency
+                                .remove_unspendable(address_index, address_data.amount, sats);
+
+                            address_data.remove_unspendable(sats);
+
+                            if address_data.is_empty() {
+                                let address_data = states
+                                    .address_index_to_address_data
+                                    .remove(&address_index)
+                                    .unwrap();
+
+                                states
+                                    .address_index_to_realized_cap_by_currency
+                                    .remove(address_index);
+
+                                address_index_at_least_once_removed.insert(address_index);
+
+                                databases.address_index_to_empty_address_data.insert(
+                                    address_index,
+                                    EmptyAddressData::from_non_empty(&address_data),
+                                );
+
+                                address_index_to_removed_address_data
+                                    .insert(address_index, address_data);
+
+                                databases
+                                    .address_index_to_empty_address_data
+                                    .metadata
+                                    .len
+                                    .increment();
+                            }
+                        }
+                    });
+            }
         }
 
         // ---
@@ -399,6 +572,14 @@ pub fn parse_block(
 
                     let input_sats = input_sats.unwrap();
 
+                    if states
+                        .txout_index_to_coinjoin_anonset
+                        .remove(&input_txout_index)
+                        .is_some()
+                    {
+                        coinjoins.spend(input_sats);
+                    }
+
                     let input_tx_data =
                         states.tx_index_to_tx_data.get_mut(&input_tx_index).unwrap();
 
@@ -471,8 +652,24 @@ pub fn parse_block(
                                     .entry(input_address_index)
                                     .or_insert_with(|| {
                                         AddressRealizedData::default(input_address_data)
+                                            .with_initial_realized_caps(
+                                                states
+                                                    .address_index_to_realized_cap_by_currency
+                                                    .get_all(input_address_index),
+                                            )
                                     });
 
+                            // MUST be after `or_insert_with` and before `spend`
+                            input_address_realized_data.send_in_currencies(
+                                states.address_index_to_realized_cap_by_currency.spend(
+                                    input_address_index,
+                                    input_address_data.amount,
+                                    input_sats,
+                                    &currencies,
+                                    |currency| currency.block_price(input_block_data),
+                                ),
+                            );
+
                             // MUST be after `or_insert_with`
                             let address_realized_profit_or_loss =
                                 input_address_data.spend(input_sats, input_block_data.price);
@@ -489,6 +686,10 @@ pub fn parse_block(
                                 .remove(&input_address_index)
                                 .unwrap();
 
+                            states
+                                .address_index_to_realized_cap_by_currency
+                                .remove(input_address_index);
+
                             address_index_at_least_once_removed.insert(input_address_index);
 
                             databases.address_index_to_empty_address_data.insert(
@@ -525,45 +726,49 @@ pub fn parse_block(
 
         sats_sent += inputs_sum;
 
+        if !is_coinbase {
+            tx_shapes.get_mut(tx_shape).spend(inputs_sum);
+        }
+
+        if let Some(coinjoin) = coinjoin.as_ref() {
+            coinjoins.push(coinjoin, inputs_sum);
+        }
+
         let fee = inputs_sum - outputs_sum;
         fees_total += fee;
         fees.push(fee);
 
+        if has_inscription {
+            inscriptions.fees += fee;
+        }
+
         ControlFlow::Continue(())
     });
 
+    unspendable += provably_unspendable;
+
+    // Miners claiming less than the subsidy and the fees
+    unspendable += unclaimed_reward(height, fees_total, coinbase_outputs_total);
+
     let mut utxo_cohorts_sent_states = UTXOCohortsSentStates::default();
     let mut utxo_cohorts_one_shot_states = UTXOCohortsOneShotStates::default();
     let mut utxo_cohorts_received_states = UTXOCohortsReceivedStates::default();
 
+    let mut processed_currencies = BTreeMap::new();
+
     let mut address_cohorts_input_states = None;
     let mut address_cohorts_one_shot_states = None;
     let mut address_cohorts_output_states = None;
     let mut address_cohorts_realized_states = None;
+    let mut address_cohorts_states_by_currency = BTreeMap::new();
 
     thread::scope(|scope| {
         scope.spawn(|| {
-            if let Some(last_date_data) = states.date_data_vec.last() {
-                let last_block_data = last_date_data.blocks.last().unwrap();
-                let previous_last_block_data = states
-                    .date_data_vec
-                    .iter()
-                    .flat_map(|date_data| &date_data.blocks)
-                    .rev()
-                    .nth(1);
-
-                states
-                    .date_data_vec
-                    .iter()
-                    .flat_map(|date_data| &date_data.blocks)
-                    .for_each(|block_data| {
-                        states.utxo_cohorts_durable_states.iterate(
-                            block_data,
-                            last_block_data,
-                            previous_last_block_data,
-                        );
-                    });
-            }
+            iterate_utxo_cohorts_durable_states(
+                &mut states.utxo_cohorts_durable_states,
+                &states.date_data_vec,
+                None,
+            );
 
             utxo_cohorts_one_shot_states =
                 states.utxo_cohorts_durable_states.compute_one_shot_states(
@@ -584,11 +789,65 @@ pub fn parse_block(
         scope.spawn(|| {
             utxo_cohorts_sent_states.compute(
                 &states.date_data_vec,
-                block_path_to_spent_data,
+                &block_path_to_spent_data,
                 block_price,
+                None,
             );
         });
 
+        scope.spawn(|| {
+            processed_currencies = currencies
+                .iter()
+                .map(|currency| {
+                    let utxo_cohorts_durable_states = states
+                        .utxo_cohorts_durable_states_by_currency
+                        .get_mut(&currency.code)
+                        .unwrap();
+
+                    iterate_utxo_cohorts_durable_states(
+                        utxo_cohorts_durable_states,
+                        &states.date_data_vec,
+                        Some(currency),
+                    );
+
+                    let block_price = currency.convert(block_price, date);
+                    let date_price = currency.convert(date_price, date);
+
+                    let utxo_cohorts_one_shot_states = utxo_cohorts_durable_states
+                        .compute_one_shot_states(
+                            block_price,
+                            if is_date_last_block {
+                                Some(date_price)
+                            } else {
+                                None
+                            },
+                        );
+
+                    let mut utxo_cohorts_sent_states = UTXOCohortsSentStates::default();
+
+                    utxo_cohorts_sent_states.compute(
+                        &states.date_data_vec,
+                        &block_path_to_spent_data,
+                        block_price,
+                        Some(currency),
+                    );
+
+                    (
+                        currency.code.to_owned(),
+                        ProcessedCurrencyData {
+                            block_price: block_price as f32,
+                            date_price: date_price as f32,
+                            utxo_cohorts_one_shot_states,
+                            utxo_cohorts_sent_states,
+                            // Computed with the address states
+                            address_cohorts_one_shot_states: None,
+                            address_cohorts_realized_states: None,
+                        },
+                    )
+                })
+                .collect();
+        });
+
         if compute_addresses {
             scope.spawn(|| {
                 address_cohorts_realized_states.replace(AddressCohortsRealizedStates::default());
@@ -645,10 +904,99 @@ pub fn parse_block(
                             },
                         ),
                 );
+
+                address_cohorts_states_by_currency = currencies
+                    .iter()
+                    .map(|currency| {
+                        let address_cohorts_durable_states = states
+                            .address_cohorts_durable_states_by_currency
+                            .get_mut(&currency.code)
+                            .unwrap();
+
+                        let address_index_to_realized_cap = states
+                            .address_index_to_realized_cap_by_currency
+                            .get(&currency.code)
+                            .unwrap();
+
+                        let mut address_cohorts_realized_states =
+                            AddressCohortsRealizedStates::default();
+
+                        address_index_to_address_realized_data.iter().for_each(
+                            |(address_index, address_realized_data)| {
+                                let current_address_data = states
+                                    .address_index_to_address_data
+                                    .get(address_index)
+                                    .unwrap_or_else(|| {
+                                        address_index_to_removed_address_data
+                                            .get(address_index)
+                                            .unwrap()
+                                    });
+
+                                let currency_realized_data = address_realized_data
+                                    .currencies
+                                    .get(&currency.code)
+                                    .cloned()
+                                    .unwrap_or_default();
+
+                                address_cohorts_durable_states.iterate_in_currency(
+                                    address_realized_data,
+                                    current_address_data,
+                                    currency_realized_data.initial_realized_cap,
+                                    address_index_to_realized_cap
+                                        .get(address_index)
+                                        .cloned()
+                                        .unwrap_or_default(),
+                                );
+
+                                address_cohorts_realized_states.iterate_profit_and_loss(
+                                    &address_realized_data.initial_address_data,
+                                    currency_realized_data.profit,
+                                    currency_realized_data.loss,
+                                    &address_realized_data
+                                        .initial_address_data
+                                        .compute_liquidity_classification(),
+                                );
+                            },
+                        );
+
+                        let address_cohorts_one_shot_states = address_cohorts_durable_states
+                            .compute_one_shot_states(
+                                currency.convert(block_price, date),
+                                if is_date_last_block {
+                                    Some(currency.convert(date_price, date))
+                                } else {
+                                    None
+                                },
+                            );
+
+                        (
+                            currency.code.to_owned(),
+                            (
+                                address_cohorts_one_shot_states,
+                                address_cohorts_realized_states,
+                            ),
+                        )
+                    })
+                    .collect();
             });
         }
     });
 
+    address_cohorts_states_by_currency.into_iter().for_each(
+        |(code, (address_cohorts_one_shot_states, address_cohorts_realized_states))| {
+            let processed_currency_data: &mut ProcessedCurrencyData =
+                processed_currencies.get_mut(&code).unwrap();
+
+            processed_currency_data
+                .address_cohorts_one_shot_states
+                .replace(address_cohorts_one_shot_states);
+
+            processed_currency_data
+                .address_cohorts_realized_states
+                .replace(address_cohorts_realized_states);
+        },
+    );
+
     datasets.insert_data(ProcessedBlockData {
         address_cohorts_input_states: &address_cohorts_input_states,
         address_cohorts_one_shot_states: &address_cohorts_one_shot_states,
@@ -656,28 +1004,61 @@ pub fn parse_block(
         address_cohorts_realized_states: &address_cohorts_realized_states,
         address_index_to_address_realized_data: &address_index_to_address_realized_data,
         address_index_to_removed_address_data: &address_index_to_removed_address_data,
-        block_price,
+        block_price: block_price as f32,
         coinbase,
+        coinjoins: &coinjoins,
+        currencies: &processed_currencies,
         databases,
         date,
         date_first_height: first_date_height,
         date_blocks_range: &(first_date_height..=height),
-        date_price,
+        date_price: date_price as f32,
         fees: &fees,
         height,
+        inscriptions: &inscriptions,
         is_date_last_block,
+        pool,
         satblocks_destroyed,
         satdays_destroyed,
         sats_sent,
         states,
         timestamp,
         transaction_count,
+        tx_shapes: &tx_shapes,
+        unspendable,
         utxo_cohorts_one_shot_states: &utxo_cohorts_one_shot_states,
         utxo_cohorts_received_states: &utxo_cohorts_received_states,
         utxo_cohorts_sent_states: &utxo_cohorts_sent_states,
     });
 }
 
+fn iterate_utxo_cohorts_durable_states(
+    utxo_cohorts_durable_states: &mut UTXOCohortsDurableStates,
+    date_data_vec: &DateDataVec,
+    currency: Option<&Currency>,
+) {
+    if let Some(last_date_data) = date_data_vec.last() {
+        let last_block_data = last_date_data.blocks.last().unwrap();
+        let previous_last_block_data = date_data_vec
+            .iter()
+            .flat_map(|date_data| &date_data.blocks)
+            .rev()
+            .nth(1);
+
+        date_data_vec
+            .iter()
+            .flat_map(|date_data| &date_data.blocks)
+            .for_each(|block_data| {
+                utxo_cohorts_durable_states.iterate(
+                    block_data,
+                    last_block_data,
+                    previous_last_block_data,
+                    currency,
+                );
+            });
+    }
+}
+
 pub struct TxoutsParsingResults {
     partial_txout_data_vec: Vec<Option<PartialTxoutData>>,
     provably_unspendable: u64,
@@ -686,6 +1067,7 @@ pub struct TxoutsParsingResults {
 
 fn parse_txouts(
     block: &Block,
+    is_genesis: bool,
     compute_addresses: bool,
     unknown_addresses: &mut Counter,
     empty_addresses: &mut Counter,
@@ -702,6 +1084,11 @@ fn parse_txouts(
             let script = &txout.script_pubkey;
             let value = txout.value.to_sat();
 
+            // The genesis coinbase was never added to the UTXO set
+            if is_genesis {
+                return None;
+            }
+
             // 0 sats outputs are possible and allowed !
             // https://mempool.space/tx/2f2442f68e38b980a6c4cec21e71851b0d8a5847d85208331a27321a9967bbd6
             // https://bitcoin.stackexchange.com/questions/104937/transaction-outputs-with-value-0
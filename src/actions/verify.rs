@@ -0,0 +1,62 @@
+use std::{collections::BTreeMap, ops::RangeInclusive};
+
+use chrono::{Local, NaiveDate};
+use serde::Serialize;
+
+use crate::{
+    databases::Databases,
+    datasets::AllDatasets,
+    io::{Json, OUTPUTS_FOLDER_PATH},
+    states::States,
+    utils::time,
+};
+
+#[derive(Debug, Serialize)]
+struct VerifyReport<'a> {
+    /// By path of the map
+    heights: BTreeMap<&'a str, Vec<RangeInclusive<usize>>>,
+    /// By path of the map, whole years since they're chunked by year
+    dates: BTreeMap<&'a str, Vec<RangeInclusive<NaiveDate>>>,
+    states: Vec<&'static str>,
+    /// By group, relative to its folder
+    databases: BTreeMap<&'static str, Vec<String>>,
+}
+
+/// Checks every chunk, state and database on disk and exports what's damaged to `verify.json` so it can be rebuilt
+pub fn verify() -> color_eyre::Result<()> {
+    println!("{:?} - Verifying...", Local::now());
+
+    let datasets = AllDatasets::import()?;
+
+    let report = time("Verified", || VerifyReport {
+        heights: datasets.find_damaged_heights(),
+        dates: datasets.find_damaged_dates(),
+        states: States::find_damaged(),
+        databases: Databases::find_damaged(),
+    });
+
+    report.heights.iter().for_each(|(path, ranges)| {
+        ranges
+            .iter()
+            .for_each(|range| println!("Damaged {path} from height {range:?}"));
+    });
+
+    report.dates.iter().for_each(|(path, ranges)| {
+        ranges
+            .iter()
+            .for_each(|range| println!("Damaged {path} from date {range:?}"));
+    });
+
+    report
+        .states
+        .iter()
+        .for_each(|name| println!("Damaged state {name}"));
+
+    report.databases.iter().for_each(|(folder, names)| {
+        names
+            .iter()
+            .for_each(|name| println!("Damaged database {folder}/{name}"));
+    });
+
+    Json::export(&format!("{OUTPUTS_FOLDER_PATH}/verify.json"), &report)
+}
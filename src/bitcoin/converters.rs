@@ -11,3 +11,8 @@ pub fn sats_to_btc(sats: u64) -> f32 {
 pub fn btc_to_sats(btc: f32) -> u64 {
     (btc * SATOSHIS_PER_BITCOIN as f32) as u64
 }
+
+#[inline(always)]
+pub fn sats_to_btc_f64(sats: u64) -> f64 {
+    sats as f64 / SATOSHIS_PER_BITCOIN as f64
+}
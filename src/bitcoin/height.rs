@@ -1,5 +1,40 @@
-use super::NUMBER_OF_UNSAFE_BLOCKS;
+use super::{BLOCKS_PER_HAVLING_EPOCH, NUMBER_OF_UNSAFE_BLOCKS, SATOSHIS_PER_BITCOIN};
 
 pub fn check_if_height_safe(height: usize, block_count: usize) -> bool {
     height < block_count - NUMBER_OF_UNSAFE_BLOCKS
 }
+
+/// In sats
+pub fn block_subsidy(height: usize) -> u64 {
+    let halvings = height / BLOCKS_PER_HAVLING_EPOCH;
+
+    if halvings >= 64 {
+        return 0;
+    }
+
+    (50 * SATOSHIS_PER_BITCOIN as u64) >> halvings
+}
+
+/// Sats of the subsidy and fees that the miner of the block didn't claim, they can never be spent
+pub fn unclaimed_reward(height: usize, fees: u64, coinbase_outputs: u64) -> u64 {
+    (block_subsidy(height) + fees).saturating_sub(coinbase_outputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn halves_the_subsidy_every_epoch() {
+        assert_eq!(block_subsidy(0), 5_000_000_000);
+        assert_eq!(block_subsidy(BLOCKS_PER_HAVLING_EPOCH - 1), 5_000_000_000);
+        assert_eq!(block_subsidy(BLOCKS_PER_HAVLING_EPOCH), 2_500_000_000);
+        assert_eq!(block_subsidy(840_000), 312_500_000);
+        assert_eq!(block_subsidy(BLOCKS_PER_HAVLING_EPOCH * 33), 0);
+        assert_eq!(block_subsidy(BLOCKS_PER_HAVLING_EPOCH * 64), 0);
+
+        // Block 124724 claimed 1 sat less than it could
+        assert_eq!(unclaimed_reward(124_724, 1_000_000, 5_000_999_999), 1);
+        assert_eq!(unclaimed_reward(124_724, 1_000_000, 5_001_000_000), 0);
+    }
+}
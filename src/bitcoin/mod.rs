@@ -4,6 +4,7 @@ mod converters;
 mod daemon;
 mod db;
 mod height;
+mod pools;
 
 pub use addresses::*;
 pub use consts::*;
@@ -11,3 +12,4 @@ pub use converters::*;
 pub use daemon::*;
 pub use db::*;
 pub use height::*;
+pub use pools::*;
@@ -0,0 +1,203 @@
+use std::{collections::BTreeMap, path::Path};
+
+use bitcoin::{Address, Network, Transaction};
+use serde::Deserialize;
+
+use crate::io::{Json, IMPORTS_FOLDER_PATH};
+
+const BUNDLED_POOLS: &str = include_str!("pools.json");
+
+#[derive(Debug, Deserialize)]
+pub struct Pool {
+    /// Assigned from the ids already given out, see `Pools::import`
+    #[serde(skip)]
+    pub id: u16,
+    pub name: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub addresses: Vec<String>,
+}
+
+impl Pool {
+    /// Lowercase alphanumeric version of the name, safe to use in a dataset path
+    pub fn slug(&self) -> String {
+        slugify(&self.name)
+    }
+}
+
+fn slugify(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// `0` means unknown
+pub const UNKNOWN_POOL_ID: u16 = 0;
+
+#[derive(Debug)]
+pub struct Pools {
+    pools: Vec<Pool>,
+    /// Every id given out so far by name, removed pools included so that their id is never reused
+    ids: BTreeMap<u16, String>,
+}
+
+impl Pools {
+    /// Reads `imports/pools.json` if present, falls back to the bundled definitions otherwise.
+    ///
+    /// Ids are looked up by slug in the `ids_path` file written by `export_ids` and new pools are
+    /// appended after the last one, so reordering or removing definitions doesn't change history
+    pub fn import(ids_path: &str) -> color_eyre::Result<Self> {
+        let path = Path::new(IMPORTS_FOLDER_PATH).join("pools.json");
+
+        let pools = if path.exists() {
+            Json::import(path.to_str().unwrap())?
+        } else {
+            serde_json::from_str(BUNDLED_POOLS)?
+        };
+
+        let ids = if Path::new(ids_path).exists() {
+            Json::import(ids_path)?
+        } else {
+            BTreeMap::default()
+        };
+
+        Ok(Self::new(pools, ids))
+    }
+
+    fn new(mut pools: Vec<Pool>, mut ids: BTreeMap<u16, String>) -> Self {
+        ids.insert(UNKNOWN_POOL_ID, "Unknown".to_owned());
+
+        pools.iter_mut().for_each(|pool| {
+            let slug = pool.slug();
+
+            pool.id = ids
+                .iter()
+                .find(|(_, name)| slugify(name) == slug)
+                .map(|(id, _)| *id)
+                .unwrap_or_else(|| {
+                    let id = ids.keys().last().unwrap() + 1;
+                    ids.insert(id, pool.name.to_owned());
+                    id
+                });
+        });
+
+        Self { pools, ids }
+    }
+
+    pub fn export_ids(&self, ids_path: &str) -> color_eyre::Result<()> {
+        Json::export(ids_path, &self.ids)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Pool> {
+        self.pools.iter()
+    }
+
+    /// Payout addresses are checked first as they're the most reliable, then the scriptSig tags
+    pub fn find(&self, coinbase_tx: &Transaction) -> u16 {
+        let payout_addresses = coinbase_tx
+            .output
+            .iter()
+            .filter_map(|txout| Address::from_script(&txout.script_pubkey, Network::Bitcoin).ok())
+            .map(|address| address.to_string())
+            .collect::<Vec<_>>();
+
+        self.pools
+            .iter()
+            .find(|pool| {
+                pool.addresses
+                    .iter()
+                    .any(|address| payout_addresses.contains(address))
+            })
+            .or_else(|| {
+                let script_sig = coinbase_tx
+                    .input
+                    .first()
+                    .map(|txin| String::from_utf8_lossy(txin.script_sig.as_bytes()).to_string())
+                    .unwrap_or_default();
+
+                self.pools
+                    .iter()
+                    .find(|pool| pool.tags.iter().any(|tag| script_sig.contains(tag)))
+            })
+            .map_or(UNKNOWN_POOL_ID, |pool| pool.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitcoin::{absolute::LockTime, transaction::Version, Amount, ScriptBuf, TxIn, TxOut};
+
+    use super::*;
+
+    const PAYOUT_ADDRESS: &str = "1KFHE7w8BhaENAswwryaoccDb6qcT6DbYY";
+
+    fn definitions() -> Vec<Pool> {
+        serde_json::from_str(&format!(
+            r#"[
+                {{ "name": "Tagged", "tags": ["/Tagged/"] }},
+                {{ "name": "Paid", "tags": ["/Tagged/"], "addresses": ["{PAYOUT_ADDRESS}"] }}
+            ]"#
+        ))
+        .unwrap()
+    }
+
+    fn coinbase(script_sig: &[u8], address: Option<&str>) -> Transaction {
+        Transaction {
+            version: Version::ONE,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                script_sig: ScriptBuf::from_bytes(script_sig.to_vec()),
+                ..Default::default()
+            }],
+            output: vec![TxOut {
+                value: Amount::from_sat(50),
+                script_pubkey: address.map_or(ScriptBuf::new(), |address| {
+                    Address::from_str(address)
+                        .unwrap()
+                        .assume_checked()
+                        .script_pubkey()
+                }),
+            }],
+        }
+    }
+
+    #[test]
+    fn matches_payout_addresses_before_tags() {
+        let pools = Pools::new(definitions(), BTreeMap::default());
+
+        assert_eq!(pools.find(&coinbase(b"\x03/Tagged/", None)), 1);
+        assert_eq!(
+            pools.find(&coinbase(b"\x03/Tagged/", Some(PAYOUT_ADDRESS))),
+            2
+        );
+        assert_eq!(pools.find(&coinbase(b"\x03/Other/", None)), UNKNOWN_POOL_ID);
+    }
+
+    #[test]
+    fn keeps_ids_when_definitions_change() {
+        let ids = Pools::new(definitions(), BTreeMap::default()).ids;
+
+        let mut definitions = definitions();
+        definitions.reverse();
+        definitions.remove(0);
+        definitions.insert(
+            0,
+            serde_json::from_str(r#"{ "name": "New", "tags": ["/New/"] }"#).unwrap(),
+        );
+
+        let pools = Pools::new(definitions, ids);
+
+        assert_eq!(
+            pools
+                .iter()
+                .map(|pool| (pool.name.as_str(), pool.id))
+                .collect::<Vec<_>>(),
+            [("New", 3), ("Tagged", 1)]
+        );
+        assert_eq!(pools.ids.get(&2).unwrap(), "Paid");
+    }
+}
@@ -1,6 +1,8 @@
-use std::{fs, io};
+use std::{fs, io, path::Path};
 
-use crate::parse::databases_folder_path;
+use crate::{io::Checksum, parse::databases_folder_path};
+
+use super::Metadata;
 
 pub trait AnyDatabaseGroup
 where
@@ -27,4 +29,38 @@ where
     }
 
     fn reset_metadata(&mut self);
+
+    /// Names of the databases of the group, and of its metadata, which can't be trusted
+    fn find_damaged() -> Vec<String> {
+        let mut damaged = Self::find_damaged_databases();
+
+        if Metadata::is_damaged(&Self::full_path()) {
+            damaged.push("metadata.bin".to_owned());
+        }
+
+        damaged
+    }
+
+    fn find_damaged_databases() -> Vec<String>;
+
+    /// Files of the databases in `folder`, relative to the one of the group
+    fn database_names(folder: &str) -> Vec<String> {
+        let path = format!("{}/{folder}", Self::full_path());
+
+        if !Path::new(&path).exists() {
+            return vec![];
+        }
+
+        fs::read_dir(path)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.is_file())
+            .map(|path| path.file_name().unwrap().to_str().unwrap().to_owned())
+            .filter(|name| {
+                name != "metadata.bin"
+                    && !name.ends_with(Checksum::EXTENSION)
+                    && !name.ends_with(Checksum::TEMP_EXTENSION)
+            })
+            .collect()
+    }
 }
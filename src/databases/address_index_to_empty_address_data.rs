@@ -100,4 +100,11 @@ impl AnyDatabaseGroup for AddressIndexToEmptyAddressData {
     fn folder<'a>() -> &'a str {
         "address_index_to_empty_address_data"
     }
+
+    fn find_damaged_databases() -> Vec<String> {
+        Self::database_names("")
+            .into_iter()
+            .filter(|name| Database::is_damaged(Self::folder(), name))
+            .collect()
+    }
 }
@@ -279,4 +279,38 @@ impl AnyDatabaseGroup for AddressToAddressIndex {
     fn folder<'a>() -> &'a str {
         "address_to_address_index"
     }
+
+    fn find_damaged_databases() -> Vec<String> {
+        let by_prefix = [
+            ("p2pk", P2PKDatabase::is_damaged as fn(&str, &str) -> bool),
+            ("p2pkh", P2PKHDatabase::is_damaged),
+            ("p2sh", P2SHDatabase::is_damaged),
+            ("p2wpkh", P2WPKHDatabase::is_damaged),
+            ("p2wsh", P2WSHDatabase::is_damaged),
+            ("p2tr", P2TRDatabase::is_damaged),
+        ]
+        .into_iter()
+        .flat_map(|(kind, is_damaged)| {
+            let folder = format!("{}/{kind}", Self::folder());
+
+            Self::database_names(kind)
+                .into_iter()
+                .filter(move |name| is_damaged(&folder, name))
+                .map(move |name| format!("{kind}/{name}"))
+        });
+
+        let others = [
+            (
+                "unknown",
+                UnknownDatabase::is_damaged as fn(&str, &str) -> bool,
+            ),
+            ("empty", EmptyDatabase::is_damaged),
+            ("multisig", MultisigDatabase::is_damaged),
+        ]
+        .into_iter()
+        .filter(|(name, is_damaged)| is_damaged(Self::folder(), name))
+        .map(|(name, _)| name.to_owned());
+
+        by_prefix.chain(others).collect()
+    }
 }
@@ -3,10 +3,11 @@ use std::{
     fmt::Debug,
     fs, io,
     ops::{Deref, DerefMut},
+    path::Path,
 };
 
 use crate::{
-    io::Binary,
+    io::{Binary, Checksum},
     parse::{Counter, WNaiveDate},
 };
 
@@ -45,6 +46,13 @@ impl Metadata {
     pub fn reset(&mut self) {
         let _ = self.data.reset(&self.path);
     }
+
+    pub fn is_damaged(path: &str) -> bool {
+        let path = MetadataData::full_path(path);
+
+        Path::new(&path).exists()
+            && Checksum::verify(&path).is_damaged(|| Binary::import::<MetadataData>(&path))
+    }
 }
 
 #[derive(Savefile, Default, Debug)]
@@ -4,7 +4,9 @@ mod address_to_address_index;
 mod metadata;
 mod txid_to_tx_index;
 
-use std::thread;
+use std::{collections::BTreeMap, thread};
+
+use rayon::prelude::*;
 
 use _trait::*;
 pub use address_index_to_empty_address_data::*;
@@ -57,6 +59,25 @@ impl Databases {
         Ok(())
     }
 
+    /// Names of the damaged files by group
+    pub fn find_damaged() -> BTreeMap<&'static str, Vec<String>> {
+        [
+            (
+                AddressIndexToEmptyAddressData::folder(),
+                AddressIndexToEmptyAddressData::find_damaged as fn() -> Vec<String>,
+            ),
+            (
+                AddressToAddressIndex::folder(),
+                AddressToAddressIndex::find_damaged,
+            ),
+            (TxidToTxIndex::folder(), TxidToTxIndex::find_damaged),
+        ]
+        .into_par_iter()
+        .map(|(folder, find_damaged)| (folder, find_damaged()))
+        .filter(|(_, damaged)| !damaged.is_empty())
+        .collect()
+    }
+
     pub fn reset(&mut self, include_addresses: bool) {
         if include_addresses {
             let _ = self.address_index_to_empty_address_data.reset();
@@ -40,10 +40,10 @@ impl TxidToTxIndex {
         self.open_db(txid).insert(txid_key, tx_index)
     }
 
-    // pub fn safe_get(&mut self, txid: &Txid) -> Option<&Value> {
-    //     let txid_key = Self::txid_to_key(txid);
-    //     self.open_db(txid).get(&txid_key)
-    // }
+    pub fn safe_get(&mut self, txid: &Txid) -> Option<&Value> {
+        let txid_key = Self::txid_to_key(txid);
+        self.open_db(txid).get(&txid_key)
+    }
 
     /// Doesn't check if the database is open contrary to `safe_get` which does and opens if needed.
     /// Though it makes it easy to use with rayon
@@ -106,4 +106,11 @@ impl AnyDatabaseGroup for TxidToTxIndex {
     fn folder<'a>() -> &'a str {
         "txid_to_tx_index"
     }
+
+    fn find_damaged_databases() -> Vec<String> {
+        Self::database_names("")
+            .into_iter()
+            .filter(|name| Database::is_damaged(Self::folder(), name))
+            .collect()
+    }
 }
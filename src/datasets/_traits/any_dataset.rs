@@ -4,9 +4,12 @@ use rayon::prelude::*;
 
 use crate::parse::{AnyBiMap, AnyDateMap, AnyHeightMap, AnyMap};
 
-use super::MinInitialState;
+use super::{CatalogEntry, DatasetColumns, MinInitialState};
 
 pub trait AnyDataset {
+    /// Snake case name of the dataset, prefix of its merged exports like `{name}_height.csv`
+    fn name(&self) -> &str;
+
     fn get_min_initial_state(&self) -> &MinInitialState;
 
     fn should_insert(&self, height: usize, date: NaiveDate) -> bool {
@@ -129,7 +132,11 @@ pub trait AnyDataset {
     fn export(&self) -> color_eyre::Result<()> {
         self.to_any_map_vec()
             .into_par_iter()
-            .try_for_each(|map| -> color_eyre::Result<()> { map.export() })
+            .try_for_each(|map| -> color_eyre::Result<()> { map.export() })?;
+
+        self.to_any_bi_map_vec()
+            .into_par_iter()
+            .try_for_each(|map| map.export_resampled())
     }
 
     fn post_export(&mut self) {
@@ -147,4 +154,59 @@ pub trait AnyDataset {
                 .for_each(|map| map.post_export())
         });
     }
+
+    /// Cohorts of the map at `path` as `(kind, name)` pairs, like `("liquidity", "illiquid")`
+    fn cohorts(&self, _path: &str) -> Vec<(&'static str, &str)> {
+        vec![]
+    }
+
+    fn to_catalog(&self) -> Vec<CatalogEntry<'_>> {
+        let bis = self.to_any_bi_map_vec().into_iter().flat_map(|bi| {
+            [
+                CatalogEntry::from_height_map(bi.get_height()),
+                CatalogEntry::from_date_map(bi.get_date(), Some(bi.aggregation())),
+            ]
+        });
+
+        let heights = self
+            .to_any_height_map_vec()
+            .into_iter()
+            .map(CatalogEntry::from_height_map);
+
+        let dates = self
+            .to_any_date_map_vec()
+            .into_iter()
+            .map(|map| CatalogEntry::from_date_map(map, None));
+
+        bis.chain(heights)
+            .chain(dates)
+            .map(|mut entry| {
+                entry.cohorts = self.cohorts(entry.path).into_iter().collect();
+                entry
+            })
+            .collect()
+    }
+
+    /// Every map of the dataset, named after their folders relative to the deepest one shared by all
+    /// of them
+    fn columns(&self) -> DatasetColumns<'_> {
+        DatasetColumns::new(
+            self.name(),
+            self.to_any_inserted_height_map_vec(),
+            self.to_any_inserted_date_map_vec(),
+        )
+    }
+
+    /// Writes every map next to its chunks as `height.csv` or `date.csv` and all of them merged in
+    /// `{name}_height.csv` and `{name}_date.csv`, in the deepest folder shared by the maps of the dataset
+    fn export_csv(&self) -> color_eyre::Result<()> {
+        self.columns().export_csv()
+    }
+
+    /// Writes all the maps merged in `{name}_height.parquet`, `{name}_date.parquet` and their `.arrow`
+    /// counterparts, in the deepest folder shared by the maps of the dataset
+    #[cfg(feature = "columnar")]
+    fn export_columnar(&self) -> color_eyre::Result<()> {
+        self.columns().export_columnar()
+    }
 }
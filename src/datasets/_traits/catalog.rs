@@ -0,0 +1,165 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::parse::{Aggregation, AnyDateMap, AnyHeightMap, AnyMap, Granularity, Unit};
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum CatalogIndex {
+    Height(usize),
+    Date(NaiveDate),
+}
+
+/// A `Unit`, or the code of the currency the dollars were converted to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum CatalogUnit {
+    Unit(Unit),
+    Currency(String),
+}
+
+/// Everything consumers need to know about an exported map, `catalog.json` has one per path
+#[derive(Debug, Serialize)]
+pub struct CatalogEntry<'a> {
+    #[serde(skip)]
+    pub path: &'a str,
+    /// Path of the file with only the last value, if any
+    pub last_path: Option<&'a str>,
+    #[serde(rename = "type")]
+    pub t_name: &'a str,
+    pub unit: Option<CatalogUnit>,
+    pub description: &'a str,
+    /// `height` or `date`, followed by the resampled ones for the dates of a `BiMap`
+    pub granularities: Vec<&'static str>,
+    /// Of the resampled granularities
+    pub aggregation: Option<Aggregation>,
+    /// Like `utxo_age`, `address_size`, `address_type` or `liquidity` to the name of the cohort
+    pub cohorts: BTreeMap<&'static str, &'a str>,
+    pub first: Option<CatalogIndex>,
+    pub last: Option<CatalogIndex>,
+    pub version: u32,
+}
+
+impl<'a> CatalogEntry<'a> {
+    pub fn from_height_map(map: &'a (dyn AnyHeightMap + Send + Sync)) -> Self {
+        Self::new(
+            map.as_any_map(),
+            vec!["height"],
+            None,
+            map.first_height().map(CatalogIndex::Height),
+            map.last_height().map(CatalogIndex::Height),
+        )
+    }
+
+    /// With an `aggregation` if the dates are resampled
+    pub fn from_date_map(
+        map: &'a (dyn AnyDateMap + Send + Sync),
+        aggregation: Option<Aggregation>,
+    ) -> Self {
+        let mut granularities = vec!["date"];
+
+        if aggregation.is_some() {
+            granularities.extend(
+                Granularity::ALL
+                    .iter()
+                    .map(|granularity| granularity.name()),
+            );
+        }
+
+        Self::new(
+            map.as_any_map(),
+            granularities,
+            aggregation,
+            map.first_date().map(CatalogIndex::Date),
+            map.last_date().map(CatalogIndex::Date),
+        )
+    }
+
+    fn new(
+        map: &'a (dyn AnyMap + Send + Sync),
+        granularities: Vec<&'static str>,
+        aggregation: Option<Aggregation>,
+        first: Option<CatalogIndex>,
+        last: Option<CatalogIndex>,
+    ) -> Self {
+        let metadata = map.metadata();
+
+        Self {
+            path: map.path(),
+            last_path: map.path_last().as_deref(),
+            t_name: map.t_name(),
+            unit: metadata.unit.map(CatalogUnit::Unit),
+            description: metadata.description,
+            granularities,
+            aggregation,
+            cohorts: BTreeMap::new(),
+            first,
+            last,
+            version: map.version(),
+        }
+    }
+
+    /// For the maps of the datasets converted to the currency with that `code`
+    pub fn set_currency(&mut self, code: &str) {
+        if self.unit == Some(CatalogUnit::Unit(Unit::Dollar)) {
+            self.unit = Some(CatalogUnit::Currency(code.to_uppercase()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::parse::{AnyBiMap, BiMap};
+
+    use super::*;
+
+    #[test]
+    fn describes_both_maps_of_a_bi_map() {
+        let folder = std::env::temp_dir().join(format!("parser/{}/catalog", std::process::id()));
+        let _ = fs::remove_dir_all(&folder);
+        let path = folder.to_str().unwrap();
+
+        let mut map = BiMap::<f32>::new_bin_sum(2, path).with_metadata(Unit::Bitcoin, "Fees");
+
+        (0..10).for_each(|height| {
+            map.height.insert(height, 1.0);
+        });
+
+        let height = CatalogEntry::from_height_map(map.get_height());
+        let date = CatalogEntry::from_date_map(map.get_date(), Some(map.aggregation()));
+
+        assert_eq!(
+            serde_json::to_value(&height).unwrap(),
+            serde_json::json!({
+                "last_path": format!("{path}/last.bin"),
+                "type": "f32",
+                "unit": "BTC",
+                "description": "Fees",
+                "granularities": ["height"],
+                "aggregation": null,
+                "cohorts": {},
+                "first": 0,
+                "last": 9,
+                "version": 2,
+            })
+        );
+
+        assert_eq!(
+            date.granularities,
+            ["date", "week", "month", "quarter", "year"]
+        );
+        assert_eq!(date.aggregation, Some(Aggregation::Sum));
+        assert!(date.first.is_none());
+
+        let mut price = CatalogEntry::from_date_map(map.get_date(), None);
+        price.unit = Some(CatalogUnit::Unit(Unit::Dollar));
+        price.set_currency("eur");
+        assert_eq!(serde_json::to_value(&price.unit).unwrap(), "EUR");
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+}
@@ -0,0 +1,267 @@
+use std::{
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+
+use chrono::NaiveDate;
+use itertools::Itertools;
+use rayon::prelude::*;
+
+#[cfg(feature = "columnar")]
+use crate::io::Columnar;
+use crate::{
+    io::Csv,
+    parse::{AnyDateMap, AnyHeightMap, AnyMapColumn},
+};
+
+/// Every map of a dataset, read from disk one column at a time, see `AnyDataset::columns`
+pub struct DatasetColumns<'a> {
+    /// Deepest folder containing the folders of all the maps
+    pub folder: String,
+    /// Name of the dataset
+    pub name: String,
+    heights: Vec<&'a (dyn AnyHeightMap + Send + Sync)>,
+    dates: Vec<&'a (dyn AnyDateMap + Send + Sync)>,
+}
+
+impl<'a> DatasetColumns<'a> {
+    pub fn new(
+        name: &str,
+        heights: Vec<&'a (dyn AnyHeightMap + Send + Sync)>,
+        dates: Vec<&'a (dyn AnyDateMap + Send + Sync)>,
+    ) -> Self {
+        let folder = Self::shared_folder(
+            heights
+                .iter()
+                .map(|map| map.path())
+                .chain(dates.iter().map(|map| map.path())),
+        );
+
+        Self {
+            folder,
+            name: name.to_owned(),
+            heights,
+            dates,
+        }
+    }
+
+    /// From the first height of any map to the last one
+    fn heights(&self) -> Option<RangeInclusive<usize>> {
+        let first = self
+            .heights
+            .iter()
+            .flat_map(|map| map.first_height())
+            .min()?;
+        let last = self
+            .heights
+            .iter()
+            .flat_map(|map| map.last_height())
+            .max()?;
+
+        Some(first..=last)
+    }
+
+    /// From the first date of any map to the last one
+    fn dates(&self) -> Option<RangeInclusive<NaiveDate>> {
+        let first = self.dates.iter().flat_map(|map| map.first_date()).min()?;
+        let last = self.dates.iter().flat_map(|map| map.last_date()).max()?;
+
+        Some(first..=last)
+    }
+
+    /// Writes every map next to its chunks as `height.csv` or `date.csv` and all of them merged in
+    /// `{name}_height.csv` and `{name}_date.csv`, in the folder of the dataset
+    pub fn export_csv(&self) -> color_eyre::Result<()> {
+        if let Some(heights) = self.heights() {
+            let columns = self
+                .heights
+                .par_iter()
+                .map(|map| map.import_column(heights.clone()))
+                .collect::<color_eyre::Result<Vec<_>>>()?;
+
+            self.export_merged_csv(
+                "height",
+                self.heights.iter().map(|map| map.path()).collect_vec(),
+                heights.collect_vec(),
+                columns,
+            )?;
+        }
+
+        if let Some(dates) = self.dates() {
+            let columns = self
+                .dates
+                .par_iter()
+                .map(|map| map.import_column(dates.clone()))
+                .collect::<color_eyre::Result<Vec<_>>>()?;
+
+            self.export_merged_csv(
+                "date",
+                self.dates.iter().map(|map| map.path()).collect_vec(),
+                dates
+                    .start()
+                    .iter_days()
+                    .take_while(|date| date <= dates.end())
+                    .collect_vec(),
+                columns,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes all the columns merged in `{name}_height` and `{name}_date` tables, as Parquet and
+    /// Arrow IPC files, in the folder of the dataset
+    ///
+    /// Each map is turned into its typed array as soon as it's read
+    #[cfg(feature = "columnar")]
+    pub fn export_columnar(&self) -> color_eyre::Result<()> {
+        use std::sync::Arc;
+
+        use arrow::array::{ArrayRef, Date32Array, UInt64Array};
+
+        if let Some(heights) = self.heights() {
+            let index: ArrayRef = Arc::new(UInt64Array::from(
+                heights.clone().map(|height| height as u64).collect_vec(),
+            ));
+
+            let columns = self
+                .heights
+                .iter()
+                .map(|map| {
+                    Ok((
+                        self.column_name(map.path()),
+                        map.import_column(heights.clone())?.into_array(),
+                    ))
+                })
+                .collect::<color_eyre::Result<Vec<_>>>()?;
+
+            Columnar::export(
+                &format!("{}/{}_height", self.folder, self.name),
+                [("height".to_owned(), index)]
+                    .into_iter()
+                    .chain(columns)
+                    .collect(),
+            )?;
+        }
+
+        if let Some(dates) = self.dates() {
+            let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+
+            let index: ArrayRef = Arc::new(Date32Array::from(
+                dates
+                    .start()
+                    .iter_days()
+                    .take_while(|date| date <= dates.end())
+                    .map(|date| (date - epoch).num_days() as i32)
+                    .collect_vec(),
+            ));
+
+            let columns = self
+                .dates
+                .iter()
+                .map(|map| {
+                    Ok((
+                        self.column_name(map.path()),
+                        map.import_column(dates.clone())?.into_array(),
+                    ))
+                })
+                .collect::<color_eyre::Result<Vec<_>>>()?;
+
+            Columnar::export(
+                &format!("{}/{}_date", self.folder, self.name),
+                [("date".to_owned(), index)]
+                    .into_iter()
+                    .chain(columns)
+                    .collect(),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// `columns` have a value or an empty cell for each of the `keys`
+    fn export_merged_csv<K>(
+        &self,
+        index_name: &str,
+        paths: Vec<&str>,
+        keys: Vec<K>,
+        columns: Vec<Box<dyn AnyMapColumn>>,
+    ) -> color_eyre::Result<()>
+    where
+        K: ToString + Sync,
+    {
+        let names = paths
+            .iter()
+            .map(|path| self.column_name(path))
+            .collect_vec();
+
+        paths
+            .par_iter()
+            .zip(names.par_iter())
+            .zip(columns.par_iter())
+            .try_for_each(|((path, name), column)| -> color_eyre::Result<()> {
+                Csv::export(
+                    &format!("{path}.csv"),
+                    &[index_name.to_owned(), name.to_owned()],
+                    keys.iter().enumerate().filter_map(|(index, key)| {
+                        let cell = column.cell(index);
+
+                        (!cell.is_empty()).then(|| vec![key.to_string(), cell])
+                    }),
+                )
+            })?;
+
+        let header = [index_name.to_owned()]
+            .into_iter()
+            .chain(names)
+            .collect_vec();
+
+        Csv::export(
+            &format!("{}/{}_{index_name}.csv", self.folder, self.name),
+            &header,
+            keys.iter().enumerate().map(|(index, key)| {
+                [key.to_string()]
+                    .into_iter()
+                    .chain(columns.iter().map(|column| column.cell(index)))
+                    .collect_vec()
+            }),
+        )
+    }
+
+    /// Folder of the map relative to the one of the dataset
+    fn column_name(&self, path: &str) -> String {
+        // The last segment of the path is `height` or `date`
+        let map_folder = Path::new(path).parent().unwrap();
+
+        map_folder
+            .strip_prefix(&self.folder)
+            .ok()
+            .and_then(|relative| relative.to_str())
+            .filter(|relative| !relative.is_empty())
+            .or_else(|| map_folder.file_name().and_then(|name| name.to_str()))
+            .unwrap_or_default()
+            .to_owned()
+    }
+
+    fn shared_folder(map_paths: impl Iterator<Item = &'a str>) -> String {
+        map_paths
+            .map(|path| Path::new(path).parent().unwrap().components().collect_vec())
+            .reduce(|shared, components| {
+                shared
+                    .into_iter()
+                    .zip(components)
+                    .take_while(|(a, b)| a == b)
+                    .map(|(a, _)| a)
+                    .collect_vec()
+            })
+            .map(|components| {
+                components
+                    .into_iter()
+                    .collect::<PathBuf>()
+                    .to_str()
+                    .unwrap()
+                    .to_owned()
+            })
+            .unwrap_or_default()
+    }
+}
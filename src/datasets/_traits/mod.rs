@@ -1,9 +1,13 @@
 mod any_dataset;
 mod any_dataset_group;
 mod any_datasets;
+mod catalog;
+mod dataset_columns;
 mod min_initial_state;
 
 pub use any_dataset::*;
 pub use any_dataset_group::*;
 pub use any_datasets::*;
+pub use catalog::*;
+pub use dataset_columns::*;
 pub use min_initial_state::*;
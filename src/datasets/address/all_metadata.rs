@@ -1,6 +1,6 @@
 use crate::{
     datasets::{AnyDataset, MinInitialState, ProcessedBlockData},
-    parse::{AnyBiMap, BiMap},
+    parse::{AnyBiMap, BiMap, Unit},
 };
 
 pub struct AllAddressesMetadataDataset {
@@ -17,8 +17,10 @@ impl AllAddressesMetadataDataset {
         let mut s = Self {
             min_initial_state: MinInitialState::default(),
 
-            total_addresses_created: BiMap::new_bin(1, &f("total_addresses_created")),
-            total_empty_addresses: BiMap::new_bin(1, &f("total_empty_addresses")),
+            total_addresses_created: BiMap::new_bin(1, &f("total_addresses_created"))
+                .with_metadata(Unit::Count, "Addresses created"),
+            total_empty_addresses: BiMap::new_bin(1, &f("total_empty_addresses"))
+                .with_metadata(Unit::Count, "Addresses whose balance went back to zero"),
         };
 
         s.min_initial_state
@@ -59,6 +61,10 @@ impl AllAddressesMetadataDataset {
 }
 
 impl AnyDataset for AllAddressesMetadataDataset {
+    fn name(&self) -> &str {
+        "address_metadata"
+    }
+
     fn get_min_initial_state(&self) -> &MinInitialState {
         &self.min_initial_state
     }
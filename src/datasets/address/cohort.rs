@@ -13,6 +13,7 @@ pub struct CohortDataset {
     min_initial_state: MinInitialState,
 
     split: AddressSplit,
+    name: Option<String>,
 
     metadata: MetadataDataset,
 
@@ -48,6 +49,7 @@ impl CohortDataset {
             min_initial_state: MinInitialState::default(),
 
             split,
+            name: name.map(|name| name.to_owned()),
 
             metadata: MetadataDataset::import(&folder_path)?,
             all: SubDataset::import(&folder_path)?,
@@ -418,6 +420,10 @@ impl CohortDataset {
 }
 
 impl AnyDataset for CohortDataset {
+    fn name(&self) -> &str {
+        "cohort"
+    }
+
     fn to_any_height_map_vec(&self) -> Vec<&(dyn AnyHeightMap + Send + Sync)> {
         self.as_vec()
             .into_iter()
@@ -463,4 +469,32 @@ impl AnyDataset for CohortDataset {
     fn get_min_initial_state(&self) -> &MinInitialState {
         &self.min_initial_state
     }
+
+    fn cohorts(&self, path: &str) -> Vec<(&'static str, &str)> {
+        let split = match self.split {
+            AddressSplit::All => None,
+            AddressSplit::Size(_) => Some("address_size"),
+            AddressSplit::Type(_) => Some("address_type"),
+        };
+
+        let liquidity = [
+            ("illiquid", &self.illiquid),
+            ("liquid", &self.liquid),
+            ("highly_liquid", &self.highly_liquid),
+        ]
+        .into_iter()
+        .find(|(_, sub)| {
+            sub.as_vec()
+                .into_iter()
+                .flat_map(|dataset| dataset.to_any_map_vec())
+                .any(|map| map.path() == path)
+        })
+        .map(|(name, _)| ("liquidity", name));
+
+        split
+            .zip(self.name.as_deref())
+            .into_iter()
+            .chain(liquidity)
+            .collect()
+    }
 }
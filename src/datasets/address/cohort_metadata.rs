@@ -1,6 +1,6 @@
 use crate::{
     datasets::{AnyDataset, MinInitialState, ProcessedBlockData},
-    parse::{AnyBiMap, BiMap},
+    parse::{AnyBiMap, BiMap, Unit},
 };
 
 pub struct MetadataDataset {
@@ -16,7 +16,8 @@ impl MetadataDataset {
         let mut s = Self {
             min_initial_state: MinInitialState::default(),
 
-            address_count: BiMap::new_bin(1, &f("address_count")),
+            address_count: BiMap::new_bin(1, &f("address_count"))
+                .with_metadata(Unit::Count, "Addresses with a balance"),
         };
 
         s.min_initial_state
@@ -44,6 +45,10 @@ impl MetadataDataset {
 }
 
 impl AnyDataset for MetadataDataset {
+    fn name(&self) -> &str {
+        "metadata"
+    }
+
     fn get_min_initial_state(&self) -> &MinInitialState {
         &self.min_initial_state
     }
@@ -1,6 +1,6 @@
 use crate::{
     datasets::AnyDataset,
-    parse::{AnyHeightMap, HeightMap, WNaiveDate},
+    parse::{AnyHeightMap, HeightMap, Unit, WNaiveDate},
     utils::timestamp_to_naive_date,
 };
 
@@ -20,8 +20,9 @@ impl BlockMetadataDataset {
         let mut s = Self {
             min_initial_state: MinInitialState::default(),
 
-            date: HeightMap::new_bin(1, &f("date")),
-            timestamp: HeightMap::new_bin(1, &f("timestamp")),
+            date: HeightMap::new_bin(1, &f("date")).with_metadata(Unit::Date, "Date of the block"),
+            timestamp: HeightMap::new_bin(1, &f("timestamp"))
+                .with_metadata(Unit::Timestamp, "Timestamp of the block"),
         };
 
         s.min_initial_state
@@ -44,6 +45,10 @@ impl BlockMetadataDataset {
 }
 
 impl AnyDataset for BlockMetadataDataset {
+    fn name(&self) -> &str {
+        "block_metadata"
+    }
+
     fn get_min_initial_state(&self) -> &MinInitialState {
         &self.min_initial_state
     }
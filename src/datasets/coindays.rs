@@ -1,7 +1,7 @@
 use crate::{
     bitcoin::sats_to_btc,
     datasets::AnyDataset,
-    parse::{AnyBiMap, BiMap},
+    parse::{AnyBiMap, BiMap, Unit},
 };
 
 use super::{MinInitialState, ProcessedBlockData};
@@ -19,7 +19,10 @@ impl CoindaysDataset {
         let mut s = Self {
             min_initial_state: MinInitialState::default(),
 
-            destroyed: BiMap::new_bin(1, &f("coindays_destroyed")),
+            destroyed: BiMap::new_bin_sum(1, &f("coindays_destroyed")).with_metadata(
+                Unit::Coindays,
+                "Coins spent multiplied by the days they were held for",
+            ),
         };
 
         s.min_initial_state
@@ -51,6 +54,10 @@ impl CoindaysDataset {
 }
 
 impl AnyDataset for CoindaysDataset {
+    fn name(&self) -> &str {
+        "coindays"
+    }
+
     fn to_any_bi_map_vec(&self) -> Vec<&(dyn AnyBiMap + Send + Sync)> {
         vec![&self.destroyed]
     }
@@ -0,0 +1,179 @@
+use crate::{
+    bitcoin::sats_to_btc,
+    datasets::AnyDataset,
+    parse::{AnyBiMap, BiMap, Unit},
+};
+
+use super::{MinInitialState, ProcessedBlockData};
+
+pub struct CoinJoinDataset {
+    min_initial_state: MinInitialState,
+
+    pub count: BiMap<usize>,
+    pub whirlpool_count: BiMap<usize>,
+    pub wasabi_count: BiMap<usize>,
+    pub joinmarket_count: BiMap<usize>,
+    pub volume: BiMap<f32>,
+    pub equal_outputs: BiMap<usize>,
+    pub mean_anonset: BiMap<f32>,
+    pub spent_count: BiMap<usize>,
+    pub spent_volume: BiMap<f32>,
+}
+
+impl CoinJoinDataset {
+    pub fn import(parent_path: &str) -> color_eyre::Result<Self> {
+        let f = |s: &str| format!("{parent_path}/{s}");
+
+        let mut s = Self {
+            min_initial_state: MinInitialState::default(),
+
+            count: BiMap::new_bin_sum(1, &f("coinjoin_count"))
+                .with_metadata(Unit::Count, "Coinjoin transactions"),
+            whirlpool_count: BiMap::new_bin_sum(1, &f("whirlpool_coinjoin_count"))
+                .with_metadata(Unit::Count, "Whirlpool coinjoin transactions"),
+            wasabi_count: BiMap::new_bin_sum(1, &f("wasabi_coinjoin_count"))
+                .with_metadata(Unit::Count, "Wasabi coinjoin transactions"),
+            joinmarket_count: BiMap::new_bin_sum(1, &f("joinmarket_coinjoin_count"))
+                .with_metadata(Unit::Count, "JoinMarket coinjoin transactions"),
+            volume: BiMap::new_bin_sum(1, &f("coinjoin_volume"))
+                .with_metadata(Unit::Bitcoin, "Amount sent by the coinjoin transactions"),
+            equal_outputs: BiMap::new_bin_sum(1, &f("coinjoin_equal_outputs"))
+                .with_metadata(Unit::Count, "Sum of the anonymity sets of the coinjoins"),
+            mean_anonset: BiMap::new_bin(1, &f("coinjoin_mean_anonset"))
+                .with_metadata(Unit::Count, "Mean anonymity set of the coinjoins"),
+            spent_count: BiMap::new_bin_sum(1, &f("coinjoin_spent_count"))
+                .with_metadata(Unit::Count, "Coinjoin outputs spent"),
+            spent_volume: BiMap::new_bin_sum(1, &f("coinjoin_spent_volume"))
+                .with_metadata(Unit::Bitcoin, "Amount of the coinjoin outputs spent"),
+        };
+
+        s.min_initial_state
+            .consume(MinInitialState::compute_from_dataset(&s));
+
+        Ok(s)
+    }
+
+    pub fn insert_data(
+        &mut self,
+        &ProcessedBlockData {
+            coinjoins,
+            date,
+            date_blocks_range,
+            height,
+            is_date_last_block,
+            ..
+        }: &ProcessedBlockData,
+    ) {
+        let count = self.count.height.insert(height, coinjoins.count);
+
+        self.whirlpool_count
+            .height
+            .insert(height, coinjoins.whirlpool_count);
+
+        self.wasabi_count
+            .height
+            .insert(height, coinjoins.wasabi_count);
+
+        self.joinmarket_count
+            .height
+            .insert(height, coinjoins.joinmarket_count);
+
+        self.volume
+            .height
+            .insert(height, sats_to_btc(coinjoins.volume));
+
+        let equal_outputs = self
+            .equal_outputs
+            .height
+            .insert(height, coinjoins.equal_outputs);
+
+        self.mean_anonset
+            .height
+            .insert(height, Self::compute_mean_anonset(equal_outputs, count));
+
+        self.spent_count
+            .height
+            .insert(height, coinjoins.spent_count);
+
+        self.spent_volume
+            .height
+            .insert(height, sats_to_btc(coinjoins.spent_volume));
+
+        if is_date_last_block {
+            self.count.date_insert_sum_range(date, date_blocks_range);
+
+            self.whirlpool_count
+                .date_insert_sum_range(date, date_blocks_range);
+
+            self.wasabi_count
+                .date_insert_sum_range(date, date_blocks_range);
+
+            self.joinmarket_count
+                .date_insert_sum_range(date, date_blocks_range);
+
+            self.volume.date_insert_sum_range(date, date_blocks_range);
+
+            self.equal_outputs
+                .date_insert_sum_range(date, date_blocks_range);
+
+            let count = self.count.date.get(date).unwrap();
+            let equal_outputs = self.equal_outputs.date.get(date).unwrap();
+
+            self.mean_anonset
+                .date
+                .insert(date, Self::compute_mean_anonset(equal_outputs, count));
+
+            self.spent_count
+                .date_insert_sum_range(date, date_blocks_range);
+
+            self.spent_volume
+                .date_insert_sum_range(date, date_blocks_range);
+        }
+    }
+
+    fn compute_mean_anonset(equal_outputs: usize, count: usize) -> f32 {
+        if count == 0 {
+            0.0
+        } else {
+            equal_outputs as f32 / count as f32
+        }
+    }
+}
+
+impl AnyDataset for CoinJoinDataset {
+    fn name(&self) -> &str {
+        "coinjoin"
+    }
+
+    fn get_min_initial_state(&self) -> &MinInitialState {
+        &self.min_initial_state
+    }
+
+    fn to_any_bi_map_vec(&self) -> Vec<&(dyn AnyBiMap + Send + Sync)> {
+        vec![
+            &self.count,
+            &self.whirlpool_count,
+            &self.wasabi_count,
+            &self.joinmarket_count,
+            &self.volume,
+            &self.equal_outputs,
+            &self.mean_anonset,
+            &self.spent_count,
+            &self.spent_volume,
+        ]
+    }
+
+    fn to_any_mut_bi_map_vec(&mut self) -> Vec<&mut dyn AnyBiMap> {
+        vec![
+            &mut self.count,
+            &mut self.whirlpool_count,
+            &mut self.wasabi_count,
+            &mut self.joinmarket_count,
+            &mut self.volume,
+            &mut self.equal_outputs,
+            &mut self.mean_anonset,
+            &mut self.spent_count,
+            &mut self.spent_volume,
+        ]
+    }
+}
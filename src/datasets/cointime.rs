@@ -2,7 +2,7 @@ use crate::{
     bitcoin::{
         sats_to_btc, ONE_DAY_IN_BLOCK_TIME, THREE_MONTHS_IN_BLOCK_TIME, TWO_WEEKS_IN_BLOCK_TIME,
     },
-    parse::{AnyBiMap, BiMap},
+    parse::{AnyBiMap, BiMap, Unit},
     utils::{ONE_DAY_IN_DAYS, ONE_YEAR_IN_DAYS, THREE_MONTHS_IN_DAYS, TWO_WEEK_IN_DAYS},
 };
 
@@ -63,64 +63,146 @@ impl CointimeDataset {
         let mut s = Self {
             min_initial_state: MinInitialState::default(),
 
-            coinblocks_destroyed: BiMap::new_bin(1, &f("coinblocks_destroyed")),
+            coinblocks_destroyed: BiMap::new_bin_sum(1, &f("coinblocks_destroyed")).with_metadata(
+                Unit::Coinblocks,
+                "Coins spent multiplied by the blocks they were held for",
+            ),
             cumulative_coinblocks_destroyed: BiMap::new_bin(
                 1,
                 &f("cumulative_coinblocks_destroyed"),
+            )
+            .with_metadata(
+                Unit::Coinblocks,
+                "Coinblocks destroyed since the genesis block",
+            ),
+            coinblocks_created: BiMap::new_bin_sum(1, &f("coinblocks_created"))
+                .with_metadata(Unit::Coinblocks, "Supply at each block"),
+            cumulative_coinblocks_created: BiMap::new_bin(1, &f("cumulative_coinblocks_created"))
+                .with_metadata(
+                    Unit::Coinblocks,
+                    "Coinblocks created since the genesis block",
+                ),
+            coinblocks_stored: BiMap::new_bin_sum(1, &f("coinblocks_stored")).with_metadata(
+                Unit::Coinblocks,
+                "Coinblocks created minus the ones destroyed",
+            ),
+            cumulative_coinblocks_stored: BiMap::new_bin(1, &f("cumulative_coinblocks_stored"))
+                .with_metadata(
+                    Unit::Coinblocks,
+                    "Coinblocks stored since the genesis block",
+                ),
+            liveliness: BiMap::new_bin(1, &f("liveliness")).with_metadata(
+                Unit::Ratio,
+                "Cumulative coinblocks destroyed divided by the created ones",
+            ),
+            vaultedness: BiMap::new_bin(1, &f("vaultedness"))
+                .with_metadata(Unit::Ratio, "One minus the liveliness"),
+            activity_to_vaultedness_ratio: BiMap::new_bin(1, &f("activity_to_vaultedness_ratio"))
+                .with_metadata(Unit::Ratio, "Liveliness divided by the vaultedness"),
+            concurrent_liveliness: BiMap::new_bin(1, &f("concurrent_liveliness")).with_metadata(
+                Unit::Ratio,
+                "Coinblocks destroyed divided by the created ones",
             ),
-            coinblocks_created: BiMap::new_bin(1, &f("coinblocks_created")),
-            cumulative_coinblocks_created: BiMap::new_bin(1, &f("cumulative_coinblocks_created")),
-            coinblocks_stored: BiMap::new_bin(1, &f("coinblocks_stored")),
-            cumulative_coinblocks_stored: BiMap::new_bin(1, &f("cumulative_coinblocks_stored")),
-            liveliness: BiMap::_new_bin(1, &f("liveliness"), 2),
-            vaultedness: BiMap::new_bin(1, &f("vaultedness")),
-            activity_to_vaultedness_ratio: BiMap::new_bin(1, &f("activity_to_vaultedness_ratio")),
-            concurrent_liveliness: BiMap::_new_bin(1, &f("concurrent_liveliness"), 2),
             concurrent_liveliness_2w_median: BiMap::new_bin(
                 1,
                 &f("concurrent_liveliness_2w_median"),
-            ),
-            liveliness_net_change: BiMap::new_bin(1, &f("liveliness_net_change")),
+            )
+            .with_metadata(Unit::Ratio, "Two weeks median of the concurrent liveliness"),
+            liveliness_net_change: BiMap::new_bin(1, &f("liveliness_net_change"))
+                .with_metadata(Unit::Ratio, "Change of the liveliness over a day"),
             liveliness_net_change_2w_median: BiMap::new_bin(
                 1,
                 &f("liveliness_net_change_2w_median"),
+            )
+            .with_metadata(Unit::Ratio, "Change of the liveliness over two weeks"),
+            vaulted_supply: BiMap::new_bin(1, &f("vaulted_supply"))
+                .with_metadata(Unit::Bitcoin, "Supply multiplied by the vaultedness"),
+            vaulting_rate: BiMap::new_bin(1, &f("vaulting_rate")).with_metadata(
+                Unit::Bitcoin,
+                "Vaulted supply multiplied by the days of a year",
             ),
-            vaulted_supply: BiMap::new_bin(1, &f("vaulted_supply")),
-            vaulting_rate: BiMap::new_bin(1, &f("vaulting_rate")),
-            active_supply: BiMap::_new_bin(1, &f("active_supply"), 2),
-            active_supply_net_change: BiMap::new_bin(1, &f("active_supply_net_change")),
-            active_supply_3m_net_change: BiMap::new_bin(1, &f("active_supply_3m_net_change")),
+            active_supply: BiMap::new_bin(1, &f("active_supply"))
+                .with_metadata(Unit::Bitcoin, "Supply multiplied by the liveliness"),
+            active_supply_net_change: BiMap::new_bin(1, &f("active_supply_net_change"))
+                .with_metadata(Unit::Bitcoin, "Change of the active supply over a day"),
+            active_supply_3m_net_change: BiMap::new_bin(1, &f("active_supply_3m_net_change"))
+                .with_metadata(
+                    Unit::Bitcoin,
+                    "Change of the active supply over three months",
+                ),
             cointime_adjusted_yearly_inflation_rate: BiMap::new_bin(
                 1,
                 &f("cointime_adjusted_yearly_inflation_rate"),
+            )
+            .with_metadata(
+                Unit::Ratio,
+                "Yearly inflation rate multiplied by the activity to vaultedness ratio",
             ),
-            cointime_adjusted_velocity: BiMap::new_bin(1, &f("cointime_adjusted_velocity")),
-            thermo_cap: BiMap::new_bin(1, &f("thermo_cap")),
-            investor_cap: BiMap::new_bin(1, &f("investor_cap")),
+            cointime_adjusted_velocity: BiMap::new_bin(1, &f("cointime_adjusted_velocity"))
+                .with_metadata(
+                    Unit::Ratio,
+                    "Annualized transaction volume divided by the active supply",
+                ),
+            thermo_cap: BiMap::new_bin(1, &f("thermo_cap"))
+                .with_metadata(Unit::Dollar, "Cumulative subsidy in dollars"),
+            investor_cap: BiMap::new_bin(1, &f("investor_cap"))
+                .with_metadata(Unit::Dollar, "Realized cap minus the thermo cap"),
             thermo_cap_to_investor_cap_ratio: BiMap::new_bin(
                 1,
                 &f("thermo_cap_to_investor_cap_ratio"),
-            ),
-            active_price: BiMap::new_bin(1, &f("active_price")),
-            active_cap: BiMap::new_bin(1, &f("active_cap")),
-            vaulted_price: BiMap::new_bin(1, &f("vaulted_price")),
-            vaulted_cap: BiMap::new_bin(1, &f("vaulted_cap")),
-            true_market_mean: BiMap::new_bin(1, &f("true_market_mean")),
-            true_market_deviation: BiMap::new_bin(1, &f("true_market_deviation")),
+            )
+            .with_metadata(Unit::Ratio, "Thermo cap divided by the investor cap"),
+            active_price: BiMap::new_bin(1, &f("active_price"))
+                .with_metadata(Unit::Dollar, "Realized price divided by the liveliness"),
+            active_cap: BiMap::new_bin(1, &f("active_cap"))
+                .with_metadata(Unit::Dollar, "Active supply multiplied by the price"),
+            vaulted_price: BiMap::new_bin(1, &f("vaulted_price"))
+                .with_metadata(Unit::Dollar, "Realized price divided by the vaultedness"),
+            vaulted_cap: BiMap::new_bin(1, &f("vaulted_cap"))
+                .with_metadata(Unit::Dollar, "Vaulted supply multiplied by the price"),
+            true_market_mean: BiMap::new_bin(1, &f("true_market_mean"))
+                .with_metadata(Unit::Dollar, "Investor cap divided by the active supply"),
+            true_market_deviation: BiMap::new_bin(1, &f("true_market_deviation"))
+                .with_metadata(Unit::Ratio, "Active cap divided by the investor cap"),
             true_market_net_unrealized_profit_and_loss: BiMap::new_bin(
                 1,
                 &f("true_market_net_unrealized_profit_and_loss"),
+            )
+            .with_metadata(
+                Unit::Ratio,
+                "Active cap minus the investor cap, relative to the active cap",
+            ),
+            investorness: BiMap::new_bin(1, &f("investorness"))
+                .with_metadata(Unit::Ratio, "Investor cap divided by the realized cap"),
+            producerness: BiMap::new_bin(1, &f("producerness"))
+                .with_metadata(Unit::Ratio, "Thermo cap divided by the realized cap"),
+            cointime_value_created: BiMap::new_bin_sum(1, &f("cointime_value_created"))
+                .with_metadata(Unit::Dollar, "Coinblocks created multiplied by the price"),
+            cointime_value_destroyed: BiMap::new_bin_sum(1, &f("cointime_value_destroyed"))
+                .with_metadata(Unit::Dollar, "Coinblocks destroyed multiplied by the price"),
+            cointime_value_stored: BiMap::new_bin_sum(1, &f("cointime_value_stored"))
+                .with_metadata(Unit::Dollar, "Coinblocks stored multiplied by the price"),
+            total_cointime_value_created: BiMap::new_bin(1, &f("total_cointime_value_created"))
+                .with_metadata(
+                    Unit::Dollar,
+                    "Cointime value created since the genesis block",
+                ),
+            total_cointime_value_destroyed: BiMap::new_bin(1, &f("total_cointime_value_destroyed"))
+                .with_metadata(
+                    Unit::Dollar,
+                    "Cointime value destroyed since the genesis block",
+                ),
+            total_cointime_value_stored: BiMap::new_bin(1, &f("total_cointime_value_stored"))
+                .with_metadata(
+                    Unit::Dollar,
+                    "Cointime value stored since the genesis block",
+                ),
+            cointime_price: BiMap::new_bin(1, &f("cointime_price")).with_metadata(
+                Unit::Dollar,
+                "Total cointime value destroyed divided by the cumulative coinblocks stored",
             ),
-            investorness: BiMap::new_bin(1, &f("investorness")),
-            producerness: BiMap::new_bin(1, &f("producerness")),
-            cointime_value_created: BiMap::new_bin(1, &f("cointime_value_created")),
-            cointime_value_destroyed: BiMap::new_bin(1, &f("cointime_value_destroyed")),
-            cointime_value_stored: BiMap::new_bin(1, &f("cointime_value_stored")),
-            total_cointime_value_created: BiMap::new_bin(1, &f("total_cointime_value_created")),
-            total_cointime_value_destroyed: BiMap::new_bin(1, &f("total_cointime_value_destroyed")),
-            total_cointime_value_stored: BiMap::new_bin(1, &f("total_cointime_value_stored")),
-            cointime_price: BiMap::new_bin(1, &f("cointime_price")),
-            cointime_cap: BiMap::new_bin(1, &f("cointime_cap")),
+            cointime_cap: BiMap::new_bin(1, &f("cointime_cap"))
+                .with_metadata(Unit::Dollar, "Cointime price multiplied by the supply"),
         };
 
         s.min_initial_state
@@ -144,6 +226,7 @@ impl CointimeDataset {
         address_datasets: &AddressDatasets,
         mining_dataset: &MiningDataset,
         transaction_dataset: &TransactionDataset,
+        cumulative_subsidy_value_map: &BiMap<f32>,
     ) {
         let circulating_supply_map = &address_datasets.all.all.supply.total;
         let circulating_supply = circulating_supply_map.height.get(&height).unwrap();
@@ -163,11 +246,7 @@ impl CointimeDataset {
             .get(&height)
             .unwrap();
 
-        let cumulative_subsidy_in_dollars_map = &mining_dataset.cumulative_subsidy_in_dollars;
-        let cumulative_subsidy_in_dollars = cumulative_subsidy_in_dollars_map
-            .height
-            .get(&height)
-            .unwrap();
+        let cumulative_subsidy_value = cumulative_subsidy_value_map.height.get(&height).unwrap();
 
         let coinblocks_destroyed = self
             .coinblocks_destroyed
@@ -283,7 +362,7 @@ impl CointimeDataset {
         let thermo_cap = self
             .thermo_cap
             .height
-            .insert(height, cumulative_subsidy_in_dollars);
+            .insert(height, cumulative_subsidy_value);
 
         let investor_cap = self
             .investor_cap
@@ -386,8 +465,7 @@ impl CointimeDataset {
             let yearly_inflation_rate = yearly_inflation_rate_map.date.get(date).unwrap();
             let annualized_transaction_volume =
                 annualized_transaction_volume_map.date.get(date).unwrap();
-            let cumulative_subsidy_in_dollars =
-                cumulative_subsidy_in_dollars_map.date.get(date).unwrap();
+            let cumulative_subsidy_value = cumulative_subsidy_value_map.date.get(date).unwrap();
 
             self.coinblocks_destroyed
                 .date_insert_sum_range(date, date_blocks_range);
@@ -466,10 +544,7 @@ impl CointimeDataset {
                 .date
                 .insert(date, annualized_transaction_volume / active_supply);
 
-            let thermo_cap = self
-                .thermo_cap
-                .date
-                .insert(date, cumulative_subsidy_in_dollars);
+            let thermo_cap = self.thermo_cap.date.insert(date, cumulative_subsidy_value);
 
             let investor_cap = self
                 .investor_cap
@@ -546,6 +621,10 @@ impl CointimeDataset {
 }
 
 impl AnyDataset for CointimeDataset {
+    fn name(&self) -> &str {
+        "cointime"
+    }
+
     fn to_any_bi_map_vec(&self) -> Vec<&(dyn AnyBiMap + Send + Sync)> {
         vec![
             &self.active_cap,
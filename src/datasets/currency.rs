@@ -0,0 +1,299 @@
+use std::thread;
+
+use itertools::Itertools;
+
+use crate::{
+    parse::{AnyBiMap, BiMap, Unit},
+    price::Currency,
+    states::{
+        AddressCohortsOneShotStates, AddressCohortsRealizedStates, UTXOCohortsOneShotStates,
+        UTXOCohortsSentStates,
+    },
+};
+
+use super::{
+    AddressDatasets, AnyDataset, AnyDatasets, CointimeDataset, MinInitialState, MiningDataset,
+    ProcessedBlockData, TransactionDataset, UTXODatasets, DATASETS_FOLDER_PATH,
+};
+
+/// Prices and the states that depend on them, converted to one currency
+pub struct ProcessedCurrencyData {
+    pub block_price: f32,
+    pub date_price: f32,
+    pub utxo_cohorts_one_shot_states: UTXOCohortsOneShotStates,
+    pub utxo_cohorts_sent_states: UTXOCohortsSentStates,
+    pub address_cohorts_one_shot_states: Option<AddressCohortsOneShotStates>,
+    pub address_cohorts_realized_states: Option<AddressCohortsRealizedStates>,
+}
+
+pub struct CurrencyMarketDataset {
+    min_initial_state: MinInitialState,
+
+    pub price: BiMap<f32>,
+    pub subsidy: BiMap<f32>,
+    pub cumulative_subsidy: BiMap<f32>,
+}
+
+impl CurrencyMarketDataset {
+    pub fn import(parent_path: &str) -> color_eyre::Result<Self> {
+        let f = |s: &str| format!("{parent_path}/{s}");
+
+        let mut s = Self {
+            min_initial_state: MinInitialState::default(),
+
+            price: BiMap::new_bin(1, &f("price")).with_metadata(Unit::Dollar, "Price of a bitcoin"),
+            subsidy: BiMap::new_bin_sum(1, &f("subsidy"))
+                .with_metadata(Unit::Dollar, "Value of the subsidy"),
+            cumulative_subsidy: BiMap::new_bin(1, &f("cumulative_subsidy"))
+                .with_metadata(Unit::Dollar, "Value of the subsidy since the genesis block"),
+        };
+
+        s.min_initial_state
+            .consume(MinInitialState::compute_from_dataset(&s));
+
+        Ok(s)
+    }
+
+    pub fn insert_data(
+        &mut self,
+        &ProcessedBlockData {
+            block_price,
+            date,
+            date_price,
+            height,
+            is_date_last_block,
+            ..
+        }: &ProcessedBlockData,
+        mining: &MiningDataset,
+    ) {
+        self.price.height.insert(height, block_price);
+
+        let subsidy = mining.subsidy.height.get(&height).unwrap();
+
+        self.subsidy.height.insert(height, subsidy * block_price);
+
+        self.cumulative_subsidy
+            .height
+            .insert_cumulative(height, &self.subsidy.height);
+
+        if is_date_last_block {
+            self.price.date.insert(date, date_price);
+
+            let subsidy = mining.subsidy.date.get(date).unwrap();
+
+            self.subsidy.date.insert(date, subsidy * date_price);
+
+            self.cumulative_subsidy
+                .date
+                .insert_cumulative(date, &self.subsidy.date);
+        }
+    }
+}
+
+impl AnyDataset for CurrencyMarketDataset {
+    fn name(&self) -> &str {
+        "market"
+    }
+
+    fn get_min_initial_state(&self) -> &MinInitialState {
+        &self.min_initial_state
+    }
+
+    fn to_any_bi_map_vec(&self) -> Vec<&(dyn AnyBiMap + Send + Sync)> {
+        vec![&self.price, &self.subsidy, &self.cumulative_subsidy]
+    }
+
+    fn to_any_mut_bi_map_vec(&mut self) -> Vec<&mut dyn AnyBiMap> {
+        vec![
+            &mut self.price,
+            &mut self.subsidy,
+            &mut self.cumulative_subsidy,
+        ]
+    }
+}
+
+pub struct CurrencyDatasets {
+    min_initial_state: MinInitialState,
+
+    pub currency: Currency,
+
+    pub address: AddressDatasets,
+    pub cointime: CointimeDataset,
+    pub market: CurrencyMarketDataset,
+    pub utxo: UTXODatasets,
+}
+
+impl CurrencyDatasets {
+    pub fn import(currency: Currency) -> color_eyre::Result<Self> {
+        let path = format!("{DATASETS_FOLDER_PATH}/{}", currency.code);
+
+        let mut s = Self {
+            min_initial_state: MinInitialState::default(),
+
+            address: AddressDatasets::import(&path)?,
+            cointime: CointimeDataset::import(&path)?,
+            market: CurrencyMarketDataset::import(&path)?,
+            utxo: UTXODatasets::import(&path)?,
+
+            currency,
+        };
+
+        s.min_initial_state
+            .consume(MinInitialState::compute_from_datasets(&s));
+
+        Ok(s)
+    }
+
+    /// `processed_block_data` is in dollars, its prices and price dependent states are swapped
+    /// with the ones of the currency
+    pub fn insert_data(
+        &mut self,
+        processed_block_data: &ProcessedBlockData,
+        processed_currency_data: &ProcessedCurrencyData,
+        mining: &MiningDataset,
+        transaction: &TransactionDataset,
+    ) {
+        let ProcessedBlockData { height, date, .. } = *processed_block_data;
+
+        let processed_block_data = ProcessedBlockData {
+            block_price: processed_currency_data.block_price,
+            date_price: processed_currency_data.date_price,
+            utxo_cohorts_one_shot_states: &processed_currency_data.utxo_cohorts_one_shot_states,
+            utxo_cohorts_sent_states: &processed_currency_data.utxo_cohorts_sent_states,
+            address_cohorts_one_shot_states: &processed_currency_data
+                .address_cohorts_one_shot_states,
+            address_cohorts_realized_states: &processed_currency_data
+                .address_cohorts_realized_states,
+            ..*processed_block_data
+        };
+
+        if self.market.should_insert(height, date) {
+            self.market.insert_data(&processed_block_data, mining);
+        }
+
+        self.utxo.insert_data(&processed_block_data);
+
+        self.address.insert_data(&processed_block_data);
+
+        if self.cointime.should_insert(height, date) {
+            self.cointime.insert_data(
+                &processed_block_data,
+                &self.address,
+                mining,
+                transaction,
+                &self.market.cumulative_subsidy,
+            );
+        }
+    }
+}
+
+impl AnyDatasets for CurrencyDatasets {
+    fn get_min_initial_state(&self) -> &MinInitialState {
+        &self.min_initial_state
+    }
+
+    fn to_any_dataset_vec(&self) -> Vec<&(dyn AnyDataset + Send + Sync)> {
+        vec![
+            vec![
+                &self.market as &(dyn AnyDataset + Send + Sync),
+                &self.cointime,
+            ],
+            self.address.to_any_dataset_vec(),
+            self.utxo.to_any_dataset_vec(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect_vec()
+    }
+
+    fn to_mut_any_dataset_vec(&mut self) -> Vec<&mut dyn AnyDataset> {
+        vec![
+            vec![&mut self.market as &mut dyn AnyDataset, &mut self.cointime],
+            self.address.to_mut_any_dataset_vec(),
+            self.utxo.to_mut_any_dataset_vec(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect_vec()
+    }
+}
+
+/// Price denominated datasets of every currency of `imports/fx`, each in a `{code}` folder of the datasets
+pub struct CurrenciesDatasets {
+    min_initial_state: MinInitialState,
+
+    pub by_currency: Vec<CurrencyDatasets>,
+}
+
+impl CurrenciesDatasets {
+    pub fn import() -> color_eyre::Result<Self> {
+        let by_currency = thread::scope(|scope| {
+            Currency::import_all()?
+                .into_iter()
+                .map(|currency| scope.spawn(|| CurrencyDatasets::import(currency)))
+                .collect_vec()
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect::<color_eyre::Result<Vec<_>>>()
+        })?;
+
+        let mut s = Self {
+            min_initial_state: MinInitialState::default(),
+
+            by_currency,
+        };
+
+        s.min_initial_state
+            .consume(MinInitialState::compute_from_datasets(&s));
+
+        Ok(s)
+    }
+
+    pub fn currencies(&self) -> Vec<&Currency> {
+        self.by_currency
+            .iter()
+            .map(|datasets| &datasets.currency)
+            .collect_vec()
+    }
+
+    pub fn insert_data(
+        &mut self,
+        processed_block_data: &ProcessedBlockData,
+        mining: &MiningDataset,
+        transaction: &TransactionDataset,
+    ) {
+        self.by_currency.iter_mut().for_each(|datasets| {
+            let processed_currency_data = processed_block_data
+                .currencies
+                .get(&datasets.currency.code)
+                .unwrap();
+
+            datasets.insert_data(
+                processed_block_data,
+                processed_currency_data,
+                mining,
+                transaction,
+            );
+        });
+    }
+}
+
+impl AnyDatasets for CurrenciesDatasets {
+    fn get_min_initial_state(&self) -> &MinInitialState {
+        &self.min_initial_state
+    }
+
+    fn to_any_dataset_vec(&self) -> Vec<&(dyn AnyDataset + Send + Sync)> {
+        self.by_currency
+            .iter()
+            .flat_map(|datasets| datasets.to_any_dataset_vec())
+            .collect_vec()
+    }
+
+    fn to_mut_any_dataset_vec(&mut self) -> Vec<&mut dyn AnyDataset> {
+        self.by_currency
+            .iter_mut()
+            .flat_map(|datasets| datasets.to_mut_any_dataset_vec())
+            .collect_vec()
+    }
+}
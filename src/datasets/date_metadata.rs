@@ -1,6 +1,6 @@
 use crate::{
     datasets::AnyDataset,
-    parse::{AnyDateMap, DateMap},
+    parse::{AnyDateMap, DateMap, Unit},
 };
 
 use super::{MinInitialState, ProcessedBlockData};
@@ -19,8 +19,10 @@ impl DateMetadataDataset {
         let mut s = Self {
             min_initial_state: MinInitialState::default(),
 
-            first_height: DateMap::new_bin(1, &f("first_height")),
-            last_height: DateMap::new_bin(1, &f("last_height")),
+            first_height: DateMap::new_bin(1, &f("first_height"))
+                .with_metadata(Unit::Height, "First block of the day"),
+            last_height: DateMap::new_bin(1, &f("last_height"))
+                .with_metadata(Unit::Height, "Last block of the day"),
         };
 
         s.min_initial_state
@@ -45,6 +47,10 @@ impl DateMetadataDataset {
 }
 
 impl AnyDataset for DateMetadataDataset {
+    fn name(&self) -> &str {
+        "date_metadata"
+    }
+
     fn to_any_date_map_vec(&self) -> Vec<&(dyn AnyDateMap + Send + Sync)> {
         vec![&self.first_height, &self.last_height]
     }
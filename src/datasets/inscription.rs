@@ -0,0 +1,259 @@
+use std::{collections::BTreeMap, fs};
+
+use chrono::NaiveDate;
+use itertools::Itertools;
+
+use crate::{
+    bitcoin::sats_to_btc,
+    datasets::AnyDataset,
+    parse::{AnyBiMap, AnyDateMap, BiMap, DateMap, SplitByContentTypeGroup, Unit},
+};
+
+use super::{MinInitialState, ProcessedBlockData};
+
+pub struct InscriptionDataset {
+    min_initial_state: MinInitialState,
+
+    pub witness_bytes: BiMap<usize>,
+    pub count: BiMap<usize>,
+    pub bytes: BiMap<usize>,
+    pub transaction_count: BiMap<usize>,
+    pub fees: BiMap<f32>,
+
+    pub count_by_content_type: SplitByContentTypeGroup<BiMap<usize>>,
+
+    /// By MIME type with its `/` replaced by `_`, a new one is only added when it's among the top of a day
+    pub count_by_mime_type: BTreeMap<String, DateMap<usize>>,
+    count_by_mime_type_path: String,
+    /// Counts of the blocks of the current date, always starts empty since parsing resumes at the start of a date
+    date_mime_types: BTreeMap<String, usize>,
+}
+
+/// How many of the most inscribed MIME types of a day get their own map
+const TOP_MIME_TYPES_PER_DAY: usize = 20;
+
+impl InscriptionDataset {
+    pub fn import(parent_path: &str) -> color_eyre::Result<Self> {
+        let f = |s: &str| format!("{parent_path}/{s}");
+
+        let count_by_mime_type_path = f("inscription_count_by_mime_type");
+
+        let count_by_mime_type = fs::read_dir(&count_by_mime_type_path)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .filter(|entry| entry.path().is_dir())
+                    .filter_map(|entry| entry.file_name().to_str().map(str::to_owned))
+                    .map(|name| {
+                        let map = Self::import_mime_type_map(&count_by_mime_type_path, &name);
+                        (name, map)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut s = Self {
+            min_initial_state: MinInitialState::default(),
+
+            witness_bytes: BiMap::new_bin_sum(1, &f("witness_bytes"))
+                .with_metadata(Unit::Bytes, "Size of the witnesses"),
+            count: BiMap::new_bin_sum(1, &f("inscription_count"))
+                .with_metadata(Unit::Count, "Inscriptions"),
+            bytes: BiMap::new_bin_sum(1, &f("inscription_bytes"))
+                .with_metadata(Unit::Bytes, "Size of the inscriptions"),
+            transaction_count: BiMap::new_bin_sum(1, &f("inscription_transaction_count"))
+                .with_metadata(Unit::Count, "Transactions with at least one inscription"),
+            fees: BiMap::new_bin_sum(1, &f("inscription_fees"))
+                .with_metadata(Unit::Bitcoin, "Fees of the transactions with inscriptions"),
+
+            count_by_content_type: SplitByContentTypeGroup {
+                text: BiMap::new_bin_sum(1, &f("text_inscription_count"))
+                    .with_metadata(Unit::Count, "Text inscriptions"),
+                json: BiMap::new_bin_sum(1, &f("json_inscription_count"))
+                    .with_metadata(Unit::Count, "JSON inscriptions"),
+                html: BiMap::new_bin_sum(1, &f("html_inscription_count"))
+                    .with_metadata(Unit::Count, "HTML inscriptions"),
+                image: BiMap::new_bin_sum(1, &f("image_inscription_count"))
+                    .with_metadata(Unit::Count, "Image inscriptions"),
+                audio: BiMap::new_bin_sum(1, &f("audio_inscription_count"))
+                    .with_metadata(Unit::Count, "Audio inscriptions"),
+                video: BiMap::new_bin_sum(1, &f("video_inscription_count"))
+                    .with_metadata(Unit::Count, "Video inscriptions"),
+                other: BiMap::new_bin_sum(1, &f("other_inscription_count"))
+                    .with_metadata(Unit::Count, "Inscriptions of any other content type"),
+            },
+
+            count_by_mime_type,
+            count_by_mime_type_path,
+            date_mime_types: BTreeMap::default(),
+        };
+
+        s.min_initial_state
+            .consume(MinInitialState::compute_from_dataset(&s));
+
+        Ok(s)
+    }
+
+    fn import_mime_type_map(parent_path: &str, name: &str) -> DateMap<usize> {
+        DateMap::new_bin(1, &format!("{parent_path}/{name}")).with_metadata(
+            Unit::Count,
+            "Inscriptions of the MIME type in the path, `/` being replaced by `_`",
+        )
+    }
+
+    fn insert_mime_types(&mut self, date: NaiveDate) {
+        let date_mime_types = std::mem::take(&mut self.date_mime_types);
+
+        date_mime_types
+            .iter()
+            .sorted_by(|(a_name, a_count), (b_name, b_count)| {
+                b_count.cmp(a_count).then(a_name.cmp(b_name))
+            })
+            .take(TOP_MIME_TYPES_PER_DAY)
+            .for_each(|(name, _)| {
+                if !self.count_by_mime_type.contains_key(name) {
+                    let map = Self::import_mime_type_map(&self.count_by_mime_type_path, name);
+                    self.count_by_mime_type.insert(name.to_owned(), map);
+                }
+            });
+
+        self.count_by_mime_type.iter_mut().for_each(|(name, map)| {
+            map.insert(date, date_mime_types.get(name).cloned().unwrap_or_default());
+        });
+    }
+
+    pub fn insert_data(
+        &mut self,
+        &ProcessedBlockData {
+            date,
+            date_blocks_range,
+            height,
+            inscriptions,
+            is_date_last_block,
+            ..
+        }: &ProcessedBlockData,
+    ) {
+        self.witness_bytes
+            .height
+            .insert(height, inscriptions.witness_bytes);
+
+        self.count.height.insert(height, inscriptions.envelopes);
+
+        self.bytes
+            .height
+            .insert(height, inscriptions.envelope_bytes);
+
+        self.transaction_count
+            .height
+            .insert(height, inscriptions.transactions);
+
+        self.fees
+            .height
+            .insert(height, sats_to_btc(inscriptions.fees));
+
+        self.count_by_content_type
+            .as_mut_vec()
+            .into_iter()
+            .zip(inscriptions.content_types.as_vec())
+            .for_each(|(map, count)| {
+                map.height.insert(height, *count);
+            });
+
+        inscriptions
+            .mime_types
+            .iter()
+            .for_each(|(mime_type, count)| {
+                *self
+                    .date_mime_types
+                    .entry(mime_type.replace('/', "_"))
+                    .or_default() += count;
+            });
+
+        if is_date_last_block {
+            self.witness_bytes
+                .date_insert_sum_range(date, date_blocks_range);
+
+            self.count.date_insert_sum_range(date, date_blocks_range);
+
+            self.bytes.date_insert_sum_range(date, date_blocks_range);
+
+            self.transaction_count
+                .date_insert_sum_range(date, date_blocks_range);
+
+            self.fees.date_insert_sum_range(date, date_blocks_range);
+
+            self.count_by_content_type
+                .as_mut_vec()
+                .into_iter()
+                .for_each(|map| map.date_insert_sum_range(date, date_blocks_range));
+
+            self.insert_mime_types(date);
+        }
+    }
+}
+
+impl AnyDataset for InscriptionDataset {
+    fn name(&self) -> &str {
+        "inscription"
+    }
+
+    fn get_min_initial_state(&self) -> &MinInitialState {
+        &self.min_initial_state
+    }
+
+    fn to_any_date_map_vec(&self) -> Vec<&(dyn AnyDateMap + Send + Sync)> {
+        self.count_by_mime_type
+            .values()
+            .map(|map| map as &(dyn AnyDateMap + Send + Sync))
+            .collect_vec()
+    }
+
+    fn to_any_mut_date_map_vec(&mut self) -> Vec<&mut dyn AnyDateMap> {
+        self.count_by_mime_type
+            .values_mut()
+            .map(|map| map as &mut dyn AnyDateMap)
+            .collect_vec()
+    }
+
+    fn to_any_bi_map_vec(&self) -> Vec<&(dyn AnyBiMap + Send + Sync)> {
+        let mut vec: Vec<&(dyn AnyBiMap + Send + Sync)> = vec![
+            &self.witness_bytes,
+            &self.count,
+            &self.bytes,
+            &self.transaction_count,
+            &self.fees,
+        ];
+
+        vec.append(
+            &mut self
+                .count_by_content_type
+                .as_vec()
+                .into_iter()
+                .map(|map| map as &(dyn AnyBiMap + Send + Sync))
+                .collect_vec(),
+        );
+
+        vec
+    }
+
+    fn to_any_mut_bi_map_vec(&mut self) -> Vec<&mut dyn AnyBiMap> {
+        let mut vec: Vec<&mut dyn AnyBiMap> = vec![
+            &mut self.witness_bytes,
+            &mut self.count,
+            &mut self.bytes,
+            &mut self.transaction_count,
+            &mut self.fees,
+        ];
+
+        vec.append(
+            &mut self
+                .count_by_content_type
+                .as_mut_vec()
+                .into_iter()
+                .map(|map| map as &mut dyn AnyBiMap)
+                .collect_vec(),
+        );
+
+        vec
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{
     bitcoin::{sats_to_btc, ONE_YEAR_IN_BLOCK_TIME},
     datasets::AnyDataset,
-    parse::{AnyBiMap, AnyDateMap, BiMap, DateMap},
+    parse::{AnyBiMap, AnyDateMap, BiMap, DateMap, Unit},
     utils::{ONE_MONTH_IN_DAYS, ONE_WEEK_IN_DAYS, ONE_YEAR_IN_DAYS},
 };
 
@@ -32,22 +32,40 @@ impl MiningDataset {
         let mut s = Self {
             min_initial_state: MinInitialState::default(),
 
-            blocks_mined: DateMap::new_bin(1, &f("blocks_mined")),
-            coinbase: BiMap::new_bin(1, &f("coinbase")),
-            fees: BiMap::new_bin(1, &f("fees")),
-
-            subsidy: BiMap::_new_bin(1, &f("subsidy"), 5),
-            subsidy_in_dollars: BiMap::new_bin(1, &f("subsidy_in_dollars")),
-            cumulative_subsidy_in_dollars: BiMap::new_bin(1, &f("cumulative_subsidy_in_dollars")),
-
-            annualized_issuance: BiMap::new_bin(1, &f("annualized_issuance")),
-            yearly_inflation_rate: BiMap::new_bin(1, &f("yearly_inflation_rate")),
-
-            last_subsidy: DateMap::new_bin(1, &f("last_subsidy")),
-            last_subsidy_in_dollars: DateMap::new_bin(1, &f("last_subsidy_in_dollars")),
-
-            blocks_mined_1w_sma: DateMap::new_bin(1, &f("blocks_mined_7d_sma")),
-            blocks_mined_1m_sma: DateMap::new_bin(1, &f("blocks_mined_1m_sma")),
+            blocks_mined: DateMap::new_bin(1, &f("blocks_mined"))
+                .with_metadata(Unit::Count, "Blocks mined"),
+            coinbase: BiMap::new_bin_sum(1, &f("coinbase"))
+                .with_metadata(Unit::Bitcoin, "Subsidy plus fees"),
+            fees: BiMap::new_bin_sum(1, &f("fees")).with_metadata(Unit::Bitcoin, "Fees"),
+
+            subsidy: BiMap::new_bin_sum(1, &f("subsidy"))
+                .with_metadata(Unit::Bitcoin, "Newly issued coins"),
+            subsidy_in_dollars: BiMap::new_bin_sum(1, &f("subsidy_in_dollars"))
+                .with_metadata(Unit::Dollar, "Value of the subsidy"),
+            cumulative_subsidy_in_dollars: BiMap::new_bin(1, &f("cumulative_subsidy_in_dollars"))
+                .with_metadata(Unit::Dollar, "Value of the subsidy since the genesis block"),
+
+            annualized_issuance: BiMap::new_bin(1, &f("annualized_issuance"))
+                .with_metadata(Unit::Bitcoin, "Subsidy of the last year"),
+            yearly_inflation_rate: BiMap::new_bin(1, &f("yearly_inflation_rate"))
+                .with_metadata(Unit::Ratio, "Annualized issuance divided by the supply"),
+
+            last_subsidy: DateMap::new_bin(1, &f("last_subsidy"))
+                .with_metadata(Unit::Bitcoin, "Subsidy of the last block of the day"),
+            last_subsidy_in_dollars: DateMap::new_bin(1, &f("last_subsidy_in_dollars"))
+                .with_metadata(
+                    Unit::Dollar,
+                    "Value of the subsidy of the last block of the day",
+                ),
+
+            blocks_mined_1w_sma: DateMap::new_bin(2, &f("blocks_mined_7d_sma")).with_metadata(
+                Unit::Count,
+                "Seven days simple moving average of the blocks mined",
+            ),
+            blocks_mined_1m_sma: DateMap::new_bin(2, &f("blocks_mined_1m_sma")).with_metadata(
+                Unit::Count,
+                "One month simple moving average of the blocks mined",
+            ),
         };
 
         s.min_initial_state
@@ -163,6 +181,10 @@ impl MiningDataset {
 }
 
 impl AnyDataset for MiningDataset {
+    fn name(&self) -> &str {
+        "mining"
+    }
+
     fn get_min_initial_state(&self) -> &MinInitialState {
         &self.min_initial_state
     }
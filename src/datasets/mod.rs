@@ -8,30 +8,42 @@ mod _traits;
 mod address;
 mod block_metadata;
 mod coindays;
+mod coinjoin;
 mod cointime;
+mod currency;
 mod date_metadata;
+mod inscription;
 mod mining;
+mod pools;
 mod price;
 mod subs;
 mod transaction;
+mod unspendable;
 mod utxo;
 
 pub use _traits::*;
 pub use address::*;
 pub use block_metadata::*;
 pub use coindays::*;
+pub use coinjoin::*;
 pub use cointime::*;
+pub use currency::*;
 pub use date_metadata::*;
+pub use inscription::*;
 pub use mining::*;
+pub use pools::*;
 pub use price::*;
 pub use subs::*;
 pub use transaction::*;
+pub use unspendable::*;
 pub use utxo::*;
 
 use crate::{
+    actions::SpentData,
     databases::Databases,
-    io::Json,
-    parse::{AddressData, AddressRealizedData},
+    derived::{DerivedSeriesConfig, Expression},
+    io::{format_path, Json},
+    parse::{AddressData, AddressRealizedData, CoinJoinsData, InscriptionsData, SplitByTxShape},
     states::{
         AddressCohortsInputStates, AddressCohortsOneShotStates, AddressCohortsOutputStates,
         AddressCohortsRealizedStates, States, UTXOCohortsOneShotStates, UTXOCohortsReceivedStates,
@@ -48,6 +60,8 @@ pub struct ProcessedBlockData<'a> {
     pub address_index_to_removed_address_data: &'a BTreeMap<u32, AddressData>,
     pub block_price: f32,
     pub coinbase: u64,
+    pub coinjoins: &'a CoinJoinsData,
+    pub currencies: &'a BTreeMap<String, ProcessedCurrencyData>,
     pub databases: &'a Databases,
     pub date: NaiveDate,
     pub date_first_height: usize,
@@ -55,13 +69,17 @@ pub struct ProcessedBlockData<'a> {
     pub date_price: f32,
     pub fees: &'a Vec<u64>,
     pub height: usize,
+    pub inscriptions: &'a InscriptionsData,
     pub is_date_last_block: bool,
+    pub pool: u16,
     pub satblocks_destroyed: u64,
     pub satdays_destroyed: u64,
     pub sats_sent: u64,
     pub states: &'a States,
     pub timestamp: u32,
     pub transaction_count: usize,
+    pub tx_shapes: &'a SplitByTxShape<SpentData>,
+    pub unspendable: u64,
     pub utxo_cohorts_one_shot_states: &'a UTXOCohortsOneShotStates,
     pub utxo_cohorts_received_states: &'a UTXOCohortsReceivedStates,
     pub utxo_cohorts_sent_states: &'a UTXOCohortsSentStates,
@@ -71,20 +89,27 @@ pub struct AllDatasets {
     min_initial_state: MinInitialState,
 
     pub address: AddressDatasets,
+    pub currencies: CurrenciesDatasets,
     pub price: PriceDatasets,
     pub utxo: UTXODatasets,
 
     pub block_metadata: BlockMetadataDataset,
     pub cointime: CointimeDataset,
     pub coindays: CoindaysDataset,
+    pub coinjoin: CoinJoinDataset,
     pub date_metadata: DateMetadataDataset,
+    pub inscription: InscriptionDataset,
     pub mining: MiningDataset,
+    pub pools: PoolsDataset,
     pub transaction: TransactionDataset,
+    pub unspendable: UnspendableDataset,
 }
 
+const DATASETS_FOLDER_PATH: &str = "./datasets";
+
 impl AllDatasets {
     pub fn import() -> color_eyre::Result<Self> {
-        let path = "./datasets";
+        let path = DATASETS_FOLDER_PATH;
 
         thread::scope(|scope| {
             let date_metadata_handle = scope.spawn(|| DateMetadataDataset::import(path));
@@ -93,30 +118,48 @@ impl AllDatasets {
 
             let coindays_handle = scope.spawn(|| CoindaysDataset::import(path));
 
+            let coinjoin_handle = scope.spawn(|| CoinJoinDataset::import(path));
+
             let mining_handle = scope.spawn(|| MiningDataset::import(path));
 
+            let pools_handle = scope.spawn(|| PoolsDataset::import(path));
+
             let block_metadata_handle = scope.spawn(|| BlockMetadataDataset::import(path));
 
             let transaction_handle = scope.spawn(|| TransactionDataset::import(path));
 
+            let inscription_handle = scope.spawn(|| InscriptionDataset::import(path));
+
+            let unspendable_handle = scope.spawn(|| UnspendableDataset::import(path));
+
             let address = AddressDatasets::import(path)?;
 
             let utxo = UTXODatasets::import(path)?;
 
             let price = PriceDatasets::import()?;
 
+            let currencies = CurrenciesDatasets::import()?;
+
             let block_metadata = block_metadata_handle.join().unwrap()?;
 
             let cointime = cointime_handle.join().unwrap()?;
 
             let coindays = coindays_handle.join().unwrap()?;
 
+            let coinjoin = coinjoin_handle.join().unwrap()?;
+
             let date_metadata = date_metadata_handle.join().unwrap()?;
 
             let mining = mining_handle.join().unwrap()?;
 
+            let pools = pools_handle.join().unwrap()?;
+
             let transaction = transaction_handle.join().unwrap()?;
 
+            let inscription = inscription_handle.join().unwrap()?;
+
+            let unspendable = unspendable_handle.join().unwrap()?;
+
             let mut s = Self {
                 min_initial_state: MinInitialState::default(),
 
@@ -124,18 +167,21 @@ impl AllDatasets {
                 block_metadata,
                 cointime,
                 coindays,
+                coinjoin,
+                currencies,
                 date_metadata,
+                inscription,
                 price,
                 mining,
+                pools,
                 transaction,
+                unspendable,
                 utxo,
             };
 
             s.min_initial_state
                 .consume(MinInitialState::compute_from_datasets(&s));
 
-            s.export_path_to_type()?;
-
             Ok(s)
         })
     }
@@ -164,34 +210,109 @@ impl AllDatasets {
                 .insert_data(&processed_block_data, &self.address);
         }
 
+        if self.pools.should_insert(height, date) {
+            self.pools.insert_data(&processed_block_data);
+        }
+
         if self.transaction.should_insert(height, date) {
             self.transaction
                 .insert_data(&processed_block_data, &self.address);
         }
 
+        if self.inscription.should_insert(height, date) {
+            self.inscription.insert_data(&processed_block_data);
+        }
+
+        if self.unspendable.should_insert(height, date) {
+            self.unspendable.insert_data(&processed_block_data);
+        }
+
+        if self.coinjoin.should_insert(height, date) {
+            self.coinjoin.insert_data(&processed_block_data);
+        }
+
         if self.cointime.should_insert(height, date) {
             self.cointime.insert_data(
                 &processed_block_data,
                 &self.address,
                 &self.mining,
                 &self.transaction,
+                &self.mining.cumulative_subsidy_in_dollars,
             );
         }
+
+        // After the datasets it reads from
+        self.currencies
+            .insert_data(&processed_block_data, &self.mining, &self.transaction);
     }
 
-    pub fn export_path_to_type(&self) -> color_eyre::Result<()> {
-        let path_to_type: BTreeMap<&str, &str> = self
-            .to_any_dataset_vec()
+    /// The address datasets in dollars and in each currency
+    pub fn address_datasets(&self) -> Vec<&AddressDatasets> {
+        [&self.address]
             .into_iter()
-            .flat_map(|dataset| {
-                dataset
-                    .to_any_map_vec()
-                    .into_iter()
-                    .flat_map(|map| map.exported_path_with_t_name())
-            })
+            .chain(
+                self.currencies
+                    .by_currency
+                    .iter()
+                    .map(|datasets| &datasets.address),
+            )
+            .collect_vec()
+    }
+
+    /// Writes `catalog.json`, the `CatalogEntry` of every map by path, with the code of their
+    /// currency as the unit of the converted maps
+    pub fn export_catalog(&self) -> color_eyre::Result<()> {
+        let mut catalog: BTreeMap<&str, CatalogEntry> = self
+            .to_any_dataset_vec()
+            .into_par_iter()
+            .flat_map(|dataset| dataset.to_catalog())
+            .map(|entry| (entry.path, entry))
             .collect();
 
-        Json::export("./datasets/paths.json", &path_to_type)
+        self.currencies.by_currency.iter().for_each(|datasets| {
+            datasets
+                .to_any_dataset_vec()
+                .into_iter()
+                .flat_map(|dataset| {
+                    dataset
+                        .to_any_inserted_height_map_vec()
+                        .into_iter()
+                        .map(|map| map.path())
+                        .chain(
+                            dataset
+                                .to_any_inserted_date_map_vec()
+                                .into_iter()
+                                .map(|map| map.path()),
+                        )
+                })
+                .for_each(|path| {
+                    if let Some(entry) = catalog.get_mut(path) {
+                        entry.set_currency(&datasets.currency.code);
+                    }
+                });
+        });
+
+        Json::export(&format!("{DATASETS_FOLDER_PATH}/catalog.json"), &catalog)
+    }
+
+    /// Damaged heights by path of the height maps with any, to be rebuilt from the first one
+    pub fn find_damaged_heights(&self) -> BTreeMap<&str, Vec<RangeInclusive<usize>>> {
+        self.to_any_dataset_vec()
+            .into_par_iter()
+            .flat_map(|dataset| dataset.to_any_inserted_height_map_vec())
+            .map(|map| (map.path(), map.find_damaged_heights()))
+            .filter(|(_, damaged)| !damaged.is_empty())
+            .collect()
+    }
+
+    /// Damaged dates by path of the date maps with any, to be rebuilt from the first one
+    pub fn find_damaged_dates(&self) -> BTreeMap<&str, Vec<RangeInclusive<NaiveDate>>> {
+        self.to_any_dataset_vec()
+            .into_par_iter()
+            .flat_map(|dataset| dataset.to_any_inserted_date_map_vec())
+            .map(|map| (map.path(), map.find_damaged_dates()))
+            .filter(|(_, damaged)| !damaged.is_empty())
+            .collect()
     }
 
     pub fn export(&mut self) -> color_eyre::Result<()> {
@@ -209,6 +330,124 @@ impl AllDatasets {
 
         Ok(())
     }
+
+    pub fn export_csv(&self) -> color_eyre::Result<()> {
+        self.to_any_dataset_vec()
+            .into_par_iter()
+            .try_for_each(|dataset| dataset.export_csv())
+    }
+
+    /// Values of the expression by height and by date, `None` when a series it uses has no map for
+    /// that kind of key
+    #[allow(clippy::type_complexity)]
+    pub fn evaluate(
+        &self,
+        expression: &Expression,
+    ) -> color_eyre::Result<(
+        Option<BTreeMap<usize, f64>>,
+        Option<BTreeMap<NaiveDate, f64>>,
+    )> {
+        let path = |name: &str, folder: &str| {
+            format!("{DATASETS_FOLDER_PATH}/{}/{folder}", format_path(name))
+        };
+
+        let heights = expression.evaluate(&mut |name| {
+            let path = path(name, "height");
+
+            self.to_any_dataset_vec()
+                .into_iter()
+                .flat_map(|dataset| dataset.to_any_inserted_height_map_vec())
+                .find(|map| map.path() == path)
+                .map(|map| {
+                    let Some(heights) = map
+                        .first_height()
+                        .zip(map.last_height())
+                        .map(|(first, last)| first..=last)
+                    else {
+                        return Ok(BTreeMap::default());
+                    };
+
+                    let column = map.import_column(heights.clone())?;
+
+                    Ok(heights
+                        .enumerate()
+                        .filter_map(|(index, height)| Some((height, column.to_f64(index)?)))
+                        .collect())
+                })
+                .transpose()
+        })?;
+
+        let dates = expression.evaluate(&mut |name| {
+            let path = path(name, "date");
+
+            self.to_any_dataset_vec()
+                .into_iter()
+                .flat_map(|dataset| dataset.to_any_inserted_date_map_vec())
+                .find(|map| map.path() == path)
+                .map(|map| {
+                    let Some(dates) = map
+                        .first_date()
+                        .zip(map.last_date())
+                        .map(|(first, last)| first..=last)
+                    else {
+                        return Ok(BTreeMap::default());
+                    };
+
+                    let column = map.import_column(dates.clone())?;
+
+                    Ok(dates
+                        .start()
+                        .iter_days()
+                        .take_while(|date| date <= dates.end())
+                        .enumerate()
+                        .filter_map(|(index, date)| Some((date, column.to_f64(index)?)))
+                        .collect())
+                })
+                .transpose()
+        })?;
+
+        Ok((heights, dates))
+    }
+
+    /// Writes the series of `derived.json` in `derived/{name}/height.json` and `date.json`
+    pub fn export_derived(&self) -> color_eyre::Result<()> {
+        DerivedSeriesConfig::import()?
+            .parse()?
+            .into_par_iter()
+            .try_for_each(|(name, expression)| -> color_eyre::Result<()> {
+                let (heights, dates) = self.evaluate(&expression)?;
+
+                if heights.is_none() && dates.is_none() {
+                    println!(
+                        "Skipping {name}, some of {} have neither a height nor a date map",
+                        expression.series().join(", ")
+                    );
+
+                    return Ok(());
+                }
+
+                let folder = format!("{DATASETS_FOLDER_PATH}/derived/{}", format_path(name));
+
+                std::fs::create_dir_all(&folder)?;
+
+                if let Some(heights) = heights {
+                    Json::export(&format!("{folder}/height.json"), &heights)?;
+                }
+
+                if let Some(dates) = dates {
+                    Json::export(&format!("{folder}/date.json"), &dates)?;
+                }
+
+                Ok(())
+            })
+    }
+
+    #[cfg(feature = "columnar")]
+    pub fn export_columnar(&self) -> color_eyre::Result<()> {
+        self.to_any_dataset_vec()
+            .into_par_iter()
+            .try_for_each(|dataset| dataset.export_columnar())
+    }
 }
 
 impl AnyDatasets for AllDatasets {
@@ -219,11 +458,16 @@ impl AnyDatasets for AllDatasets {
     fn to_any_dataset_vec(&self) -> Vec<&(dyn AnyDataset + Send + Sync)> {
         vec![
             self.address.to_any_dataset_vec(),
+            self.currencies.to_any_dataset_vec(),
             self.price.to_any_dataset_vec(),
             self.utxo.to_any_dataset_vec(),
             vec![
                 &self.mining,
+                &self.pools,
                 &self.transaction,
+                &self.coinjoin,
+                &self.inscription,
+                &self.unspendable,
                 &self.block_metadata,
                 &self.date_metadata,
                 &self.cointime,
@@ -238,11 +482,16 @@ impl AnyDatasets for AllDatasets {
     fn to_mut_any_dataset_vec(&mut self) -> Vec<&mut dyn AnyDataset> {
         vec![
             self.address.to_mut_any_dataset_vec(),
+            self.currencies.to_mut_any_dataset_vec(),
             self.price.to_mut_any_dataset_vec(),
             self.utxo.to_mut_any_dataset_vec(),
             vec![
                 &mut self.mining,
+                &mut self.pools,
                 &mut self.transaction,
+                &mut self.coinjoin,
+                &mut self.inscription,
+                &mut self.unspendable,
                 &mut self.block_metadata,
                 &mut self.date_metadata,
                 &mut self.cointime,
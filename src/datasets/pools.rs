@@ -0,0 +1,235 @@
+use itertools::Itertools;
+
+use crate::{
+    bitcoin::{sats_to_btc, Pools, UNKNOWN_POOL_ID},
+    datasets::AnyDataset,
+    parse::{AnyBiMap, AnyDateMap, AnyHeightMap, BiMap, DateMap, HeightMap, Unit},
+    utils::ONE_WEEK_IN_DAYS,
+};
+
+use super::{MinInitialState, ProcessedBlockData};
+
+pub struct PoolDataset {
+    id: u16,
+
+    pub blocks_mined: DateMap<usize>,
+    pub blocks_mined_1w_sum: DateMap<usize>,
+    pub dominance: DateMap<f32>,
+    pub dominance_1w: DateMap<f32>,
+
+    pub fees: BiMap<f32>,
+    pub subsidy: BiMap<f32>,
+}
+
+impl PoolDataset {
+    fn import(parent_path: &str, id: u16, slug: &str) -> Self {
+        let f = |s: &str| format!("{parent_path}/{slug}/{s}");
+
+        Self {
+            id,
+
+            blocks_mined: DateMap::new_bin(1, &f("blocks_mined"))
+                .with_metadata(Unit::Count, "Blocks mined by the pool"),
+            blocks_mined_1w_sum: DateMap::new_bin(1, &f("blocks_mined_1w_sum"))
+                .with_metadata(Unit::Count, "Blocks mined by the pool over a week"),
+            dominance: DateMap::new_bin(1, &f("dominance"))
+                .with_metadata(Unit::Ratio, "Share of the blocks mined by the pool"),
+            dominance_1w: DateMap::new_bin(1, &f("dominance_1w")).with_metadata(
+                Unit::Ratio,
+                "Share of the blocks mined by the pool over a week",
+            ),
+
+            fees: BiMap::new_bin_sum(1, &f("fees"))
+                .with_metadata(Unit::Bitcoin, "Fees collected by the pool"),
+            subsidy: BiMap::new_bin_sum(1, &f("subsidy"))
+                .with_metadata(Unit::Bitcoin, "Subsidy collected by the pool"),
+        }
+    }
+}
+
+pub struct PoolsDataset {
+    min_initial_state: MinInitialState,
+
+    pub pools: Pools,
+
+    pub pool: HeightMap<u16>,
+
+    pub by_pool: Vec<PoolDataset>,
+}
+
+impl PoolsDataset {
+    pub fn import(parent_path: &str) -> color_eyre::Result<Self> {
+        let f = |s: &str| format!("{parent_path}/{s}");
+
+        let ids_path = format!("{}/ids.json", f("pools"));
+
+        let pools = Pools::import(&ids_path)?;
+
+        pools.export_ids(&ids_path)?;
+
+        let mut by_pool = vec![PoolDataset::import(&f("pools"), UNKNOWN_POOL_ID, "unknown")];
+
+        pools.iter().for_each(|pool| {
+            by_pool.push(PoolDataset::import(&f("pools"), pool.id, &pool.slug()));
+        });
+
+        let mut s = Self {
+            min_initial_state: MinInitialState::default(),
+
+            pools,
+
+            pool: HeightMap::new_bin(1, &f("pools/pool")).with_metadata(
+                Unit::Id,
+                "Pool that mined the block, 0 if unknown, see `Pools`",
+            ),
+
+            by_pool,
+        };
+
+        s.min_initial_state
+            .consume(MinInitialState::compute_from_dataset(&s));
+
+        Ok(s)
+    }
+
+    pub fn insert_data(
+        &mut self,
+        &ProcessedBlockData {
+            coinbase,
+            date,
+            date_blocks_range,
+            fees,
+            height,
+            is_date_last_block,
+            pool,
+            ..
+        }: &ProcessedBlockData,
+    ) {
+        self.pool.insert(height, pool);
+
+        let fees = sats_to_btc(fees.iter().sum());
+        let subsidy = sats_to_btc(coinbase) - fees;
+
+        self.by_pool.iter_mut().for_each(|dataset| {
+            if dataset.id == pool {
+                dataset.fees.height.insert(height, fees);
+                dataset.subsidy.height.insert(height, subsidy);
+            } else {
+                dataset.fees.height.insert(height, 0.0);
+                dataset.subsidy.height.insert(height, 0.0);
+            }
+        });
+
+        if is_date_last_block {
+            let date_pools = date_blocks_range
+                .clone()
+                .map(|height| self.pool.get(&height).unwrap())
+                .collect_vec();
+
+            let blocks_mined = date_pools.len();
+
+            self.by_pool.iter_mut().for_each(|dataset| {
+                dataset.fees.date_insert_sum_range(date, date_blocks_range);
+
+                dataset
+                    .subsidy
+                    .date_insert_sum_range(date, date_blocks_range);
+
+                let pool_blocks_mined = dataset.blocks_mined.insert(
+                    date,
+                    date_pools.iter().filter(|id| **id == dataset.id).count(),
+                );
+
+                dataset
+                    .dominance
+                    .insert(date, pool_blocks_mined as f32 / blocks_mined as f32);
+
+                dataset.blocks_mined_1w_sum.insert_last_x_sum(
+                    date,
+                    &dataset.blocks_mined,
+                    ONE_WEEK_IN_DAYS,
+                );
+            });
+
+            // Every block belongs to exactly one pool (unknown included) so the sum is the total
+            let blocks_mined_1w: usize = self
+                .by_pool
+                .iter()
+                .map(|dataset| dataset.blocks_mined_1w_sum.get(date).unwrap())
+                .sum();
+
+            self.by_pool.iter_mut().for_each(|dataset| {
+                let pool_blocks_mined_1w = dataset.blocks_mined_1w_sum.get(date).unwrap();
+
+                dataset
+                    .dominance_1w
+                    .insert(date, pool_blocks_mined_1w as f32 / blocks_mined_1w as f32);
+            });
+        }
+    }
+}
+
+impl AnyDataset for PoolsDataset {
+    fn name(&self) -> &str {
+        "pools"
+    }
+
+    fn get_min_initial_state(&self) -> &MinInitialState {
+        &self.min_initial_state
+    }
+
+    fn to_any_height_map_vec(&self) -> Vec<&(dyn AnyHeightMap + Send + Sync)> {
+        vec![&self.pool]
+    }
+
+    fn to_any_mut_height_map_vec(&mut self) -> Vec<&mut dyn AnyHeightMap> {
+        vec![&mut self.pool]
+    }
+
+    fn to_any_date_map_vec(&self) -> Vec<&(dyn AnyDateMap + Send + Sync)> {
+        self.by_pool
+            .iter()
+            .flat_map(|dataset| {
+                [
+                    &dataset.blocks_mined as &(dyn AnyDateMap + Send + Sync),
+                    &dataset.blocks_mined_1w_sum,
+                    &dataset.dominance,
+                    &dataset.dominance_1w,
+                ]
+            })
+            .collect_vec()
+    }
+
+    fn to_any_mut_date_map_vec(&mut self) -> Vec<&mut dyn AnyDateMap> {
+        self.by_pool
+            .iter_mut()
+            .flat_map(|dataset| {
+                [
+                    &mut dataset.blocks_mined as &mut dyn AnyDateMap,
+                    &mut dataset.blocks_mined_1w_sum,
+                    &mut dataset.dominance,
+                    &mut dataset.dominance_1w,
+                ]
+            })
+            .collect_vec()
+    }
+
+    fn to_any_bi_map_vec(&self) -> Vec<&(dyn AnyBiMap + Send + Sync)> {
+        self.by_pool
+            .iter()
+            .flat_map(|dataset| {
+                [
+                    &dataset.fees as &(dyn AnyBiMap + Send + Sync),
+                    &dataset.subsidy,
+                ]
+            })
+            .collect_vec()
+    }
+
+    fn to_any_mut_bi_map_vec(&mut self) -> Vec<&mut dyn AnyBiMap> {
+        self.by_pool
+            .iter_mut()
+            .flat_map(|dataset| [&mut dataset.fees as &mut dyn AnyBiMap, &mut dataset.subsidy])
+            .collect_vec()
+    }
+}
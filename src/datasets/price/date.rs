@@ -1,33 +1,40 @@
-use std::collections::HashMap;
-
-use chrono::NaiveDate;
+use chrono::{Days, NaiveDate};
 use color_eyre::eyre::Error;
+use rayon::prelude::*;
 
 use crate::{
     datasets::{AnyDataset, MinInitialState},
-    parse::{AnyDateMap, DateMap},
-    price::Kraken,
+    parse::{Aggregation, AnyDateMap, DateMap, Unit},
+    price::{price_source_id, Candle, PriceMethod, PriceSource, PriceSourcesConfig},
 };
 
 pub struct DateDataset {
     min_initial_state: MinInitialState,
 
-    kraken_daily: Option<HashMap<String, f32>>,
+    sources: Vec<Box<dyn PriceSource + Send + Sync>>,
+    /// See `PriceSourcesConfig::max_previous_days`
+    max_previous_days: Option<usize>,
 
+    pub opens: DateMap<f32>,
+    pub highs: DateMap<f32>,
+    pub lows: DateMap<f32>,
     pub closes: DateMap<f32>,
+
+    /// Id of the source of the price, see `quality/ids.json`
+    pub price_source: DateMap<u8>,
+    /// Id of the method that found the price, see `quality/ids.json`
+    pub price_method: DateMap<u8>,
 }
 
 impl DateDataset {
     pub fn import(parent_path: &str) -> color_eyre::Result<Self> {
-        let name = "close";
-
-        let mut s = Self {
-            min_initial_state: MinInitialState::default(),
+        let config = PriceSourcesConfig::import()?;
 
-            kraken_daily: None,
-
-            closes: DateMap::_new_json(1, &format!("{parent_path}/{name}"), usize::MAX, true),
-        };
+        let mut s = Self::new(
+            parent_path,
+            config.daily_sources(),
+            config.max_previous_days(),
+        );
 
         s.min_initial_state
             .consume(MinInitialState::compute_from_dataset(&s));
@@ -35,38 +42,257 @@ impl DateDataset {
         Ok(s)
     }
 
+    pub fn new(
+        parent_path: &str,
+        sources: Vec<Box<dyn PriceSource + Send + Sync>>,
+        max_previous_days: Option<usize>,
+    ) -> Self {
+        let f = |s: &str| DateMap::_new_json(1, &format!("{parent_path}/{s}"), usize::MAX, true);
+
+        Self {
+            min_initial_state: MinInitialState::default(),
+
+            sources,
+            max_previous_days,
+
+            opens: f("open").with_metadata(Unit::Dollar, "Open price of the day"),
+            highs: f("high").with_metadata(Unit::Dollar, "High price of the day"),
+            lows: f("low").with_metadata(Unit::Dollar, "Low price of the day"),
+            closes: f("close").with_metadata(Unit::Dollar, "Close price of the day"),
+
+            price_source: DateMap::_new_json(
+                1,
+                &format!("{parent_path}/quality/source"),
+                usize::MAX,
+                false,
+            ),
+            price_method: DateMap::_new_json(
+                1,
+                &format!("{parent_path}/quality/method"),
+                usize::MAX,
+                false,
+            ),
+        }
+    }
+
     pub fn get(&mut self, date: NaiveDate) -> color_eyre::Result<f32> {
-        if self.closes.is_date_safe(date) {
-            Ok(self.closes.get(date).unwrap().to_owned())
+        Ok(self.get_candle(date)?.close)
+    }
+
+    /// The open, high and low are the close when only the close is known, they're left unset then
+    pub fn get_candle(&mut self, date: NaiveDate) -> color_eyre::Result<Candle> {
+        let (close, candle, source, method) = if self.closes.is_date_safe(date) {
+            let close = self.closes.get(date).unwrap();
+
+            // Only the close was stored
+            let candle = self.stored_candle(date).or_else(|| {
+                self.fetch_candle(date)
+                    .map(|(candle, _)| candle.with_close(close))
+            });
+
+            (
+                close,
+                candle,
+                self.price_source.get(date).unwrap_or_default(),
+                self.price_method
+                    .get(date)
+                    .unwrap_or(PriceMethod::Unknown as u8),
+            )
+        } else if let Some((candle, source)) = self.fetch_candle(date) {
+            (candle.close, Some(candle), source, PriceMethod::Exact as u8)
         } else {
-            let price = self.get_from_daily_kraken(&date.to_string())?;
+            let (close, source) = self.previous_date_close(date).ok_or(Error::msg(format!(
+                "Couldn't find {date} in any price source"
+            )))?;
 
-            self.closes.insert(date, price);
+            println!("price: {date} has no daily candle, using a previous close");
+
+            (close, None, source, PriceMethod::PreviousDate as u8)
+        };
 
-            Ok(price)
+        if let Some(candle) = candle {
+            self.opens.insert(date, candle.open);
+            self.highs.insert(date, candle.high);
+            self.lows.insert(date, candle.low);
         }
+
+        self.closes.insert(date, close);
+
+        self.price_source.insert(date, source);
+        self.price_method.insert(date, method);
+
+        Ok(candle.unwrap_or(Candle::from_close(close)))
     }
 
-    fn get_from_daily_kraken(&mut self, date: &str) -> color_eyre::Result<f32> {
-        if self.kraken_daily.is_none() {
-            self.kraken_daily.replace(Kraken::fetch_daily_prices()?);
-        }
+    /// Candle of the first source that has the date, with the id of the source
+    fn fetch_candle(&mut self, date: NaiveDate) -> Option<(Candle, u8)> {
+        self.sources.iter_mut().find_map(|source| {
+            source
+                .get_daily(date)
+                .ok()
+                .map(|candle| (candle, price_source_id(source.name())))
+        })
+    }
+
+    /// Close and source of the last date priced by another method than `PreviousDate`, within
+    /// `max_previous_days`
+    fn previous_date_close(&self, date: NaiveDate) -> Option<(f32, u8)> {
+        (1..=self.max_previous_days?).find_map(|days| {
+            let previous_date = date.checked_sub_days(Days::new(days as u64))?;
+
+            if self.price_method.get(previous_date) == Some(PriceMethod::PreviousDate as u8) {
+                return None;
+            }
+
+            Some((
+                self.closes.get(previous_date)?,
+                self.price_source.get(previous_date).unwrap_or_default(),
+            ))
+        })
+    }
 
-        self.kraken_daily
-            .as_ref()
-            .unwrap()
-            .get(date)
-            .cloned()
-            .ok_or(Error::msg("Couldn't find date in daily kraken"))
+    fn stored_candle(&self, date: NaiveDate) -> Option<Candle> {
+        Some(Candle {
+            open: self.opens.get(date)?,
+            high: self.highs.get(date)?,
+            low: self.lows.get(date)?,
+            close: self.closes.get(date)?,
+            volume: 0.0,
+        })
     }
 }
 
 impl AnyDataset for DateDataset {
+    fn name(&self) -> &str {
+        "price"
+    }
+
     fn get_min_initial_state(&self) -> &MinInitialState {
         &self.min_initial_state
     }
 
     fn to_any_date_map_vec(&self) -> Vec<&(dyn AnyDateMap + Send + Sync)> {
-        vec![&self.closes]
+        vec![
+            &self.opens,
+            &self.highs,
+            &self.lows,
+            &self.closes,
+            &self.price_source,
+            &self.price_method,
+        ]
+    }
+
+    fn to_any_mut_date_map_vec(&mut self) -> Vec<&mut dyn AnyDateMap> {
+        vec![
+            &mut self.opens,
+            &mut self.highs,
+            &mut self.lows,
+            &mut self.closes,
+            &mut self.price_source,
+            &mut self.price_method,
+        ]
+    }
+
+    fn export(&self) -> color_eyre::Result<()> {
+        self.to_any_map_vec()
+            .into_par_iter()
+            .try_for_each(|map| -> color_eyre::Result<()> { map.export() })?;
+
+        // Candles by week, month, quarter and year
+        self.opens.export_resampled(Aggregation::First)?;
+        self.highs.export_resampled(Aggregation::Max)?;
+        self.lows.export_resampled(Aggregation::Min)?;
+        self.closes.export_resampled(Aggregation::Last)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::{collections::BTreeMap, fs};
+
+    use crate::price::Around;
+
+    use super::*;
+
+    pub struct Daily(pub BTreeMap<NaiveDate, Candle>);
+
+    impl PriceSource for Daily {
+        fn name(&self) -> &str {
+            "local"
+        }
+
+        fn get_1mn(&mut self, _: u32) -> color_eyre::Result<Candle> {
+            Err(Error::msg("No 1mn candles"))
+        }
+
+        fn get_1mn_around(&mut self, _: u32, _: u32) -> color_eyre::Result<Around> {
+            Ok(Around::default())
+        }
+
+        fn get_daily(&mut self, date: NaiveDate) -> color_eyre::Result<Candle> {
+            self.0
+                .get(&date)
+                .cloned()
+                .ok_or(Error::msg("No daily candle"))
+        }
+    }
+
+    #[test]
+    fn leaves_the_ohlc_of_a_previous_close_unset() {
+        let folder = std::env::temp_dir().join(format!("parser/{}/price", std::process::id()));
+
+        let _ = fs::remove_dir_all(&folder);
+
+        let day = NaiveDate::from_ymd_opt(2020, 1, 1).unwrap();
+        let next_day = day.succ_opt().unwrap();
+
+        let candle = Candle {
+            open: 7000.0,
+            high: 7300.0,
+            low: 6900.0,
+            close: 7200.0,
+            volume: 0.0,
+        };
+
+        let mut dataset = DateDataset::new(
+            folder.to_str().unwrap(),
+            vec![Box::new(Daily(BTreeMap::from([(day, candle)])))],
+            Some(1),
+        );
+
+        assert_eq!(dataset.get_candle(day).unwrap(), candle);
+        assert_eq!(dataset.stored_candle(day), Some(candle));
+
+        assert_eq!(dataset.get(next_day).unwrap(), 7200.0);
+        assert_eq!(dataset.closes.get(next_day), Some(7200.0));
+        assert_eq!(dataset.opens.get(next_day), None);
+        assert_eq!(dataset.highs.get(next_day), None);
+        assert_eq!(dataset.lows.get(next_day), None);
+
+        assert_eq!(
+            dataset.price_source.get(day),
+            Some(price_source_id("local"))
+        );
+        assert_eq!(
+            dataset.price_method.get(day),
+            Some(PriceMethod::Exact as u8)
+        );
+        assert_eq!(
+            dataset.price_source.get(next_day),
+            Some(price_source_id("local"))
+        );
+        assert_eq!(
+            dataset.price_method.get(next_day),
+            Some(PriceMethod::PreviousDate as u8)
+        );
+
+        // Not from the previous close which was itself a previous close
+        assert!(dataset.get(next_day.succ_opt().unwrap()).is_err());
+
+        // Unless it's in the gap policy
+        dataset.max_previous_days = None;
+        assert!(dataset.get(day.pred_opt().unwrap()).is_err());
+
+        fs::remove_dir_all(folder).unwrap();
     }
 }
@@ -1,36 +1,122 @@
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, fs};
 
 use chrono::{NaiveDateTime, NaiveTime, TimeZone, Timelike, Utc};
 use color_eyre::eyre::Error;
+use itertools::Itertools;
+use serde::Serialize;
 
 use crate::{
     datasets::{AnyDataset, MinInitialState},
-    parse::{AnyHeightMap, HeightMap},
-    price::{Binance, Kraken},
+    io::Json,
+    parse::{AnyHeightMap, HeightMap, Unit},
+    price::{
+        price_source_id, AggregatedCandle, Around, Candle, PriceAggregation, PriceMethod,
+        PriceSource, PriceSourcesConfig, PRICE_SOURCE_NAMES,
+    },
+    utils::timestamp_to_naive_date,
 };
 
+use super::DateDataset;
+
+#[derive(Serialize)]
+struct QualityIds {
+    sources: BTreeMap<u8, String>,
+    methods: BTreeMap<u8, PriceMethod>,
+}
+
 pub struct HeightDataset {
     min_initial_state: MinInitialState,
 
-    kraken_1mn: Option<BTreeMap<u32, f32>>,
-    binance_1mn: Option<BTreeMap<u32, f32>>,
-    binance_har: Option<BTreeMap<u32, f32>>,
+    sources: Vec<Box<dyn PriceSource + Send + Sync>>,
+    gap_policy: Vec<PriceMethod>,
+    max_gap: u32,
+    max_previous_blocks: usize,
+    aggregation: PriceAggregation,
+    max_deviation: f32,
 
+    pub opens: HeightMap<f32>,
+    pub highs: HeightMap<f32>,
+    pub lows: HeightMap<f32>,
     pub closes: HeightMap<f32>,
+
+    /// Id of the source of the price, see `quality/ids.json`
+    pub price_source: HeightMap<u8>,
+    /// Id of the method that found the price, see `quality/ids.json`
+    pub price_method: HeightMap<u8>,
+    /// Relative difference between the highest and lowest close of the sources, 0 when there was only one
+    pub price_spread: HeightMap<f32>,
+    /// Number of sources that made the price, 0 if unknown or not from a source of the minute
+    pub price_sources_count: HeightMap<u8>,
 }
 
 impl HeightDataset {
     pub fn import(parent_path: &str) -> color_eyre::Result<Self> {
-        let name = "close";
+        let f = |s: &str| HeightMap::_new_json(1, &format!("{parent_path}/{s}"), usize::MAX, false);
+
+        let config = PriceSourcesConfig::import()?;
+
+        let quality_path = format!("{parent_path}/quality");
+
+        fs::create_dir_all(&quality_path)?;
+
+        Json::export(
+            &format!("{quality_path}/ids.json"),
+            &QualityIds {
+                sources: [(0, "unknown")]
+                    .into_iter()
+                    .chain(
+                        PRICE_SOURCE_NAMES
+                            .iter()
+                            .map(|name| (price_source_id(name), *name)),
+                    )
+                    .map(|(id, name)| (id, name.to_owned()))
+                    .collect(),
+                methods: PriceMethod::ALL
+                    .into_iter()
+                    .map(|method| (method as u8, method))
+                    .collect(),
+            },
+        )?;
 
         let mut s = Self {
             min_initial_state: MinInitialState::default(),
 
-            binance_1mn: None,
-            binance_har: None,
-            kraken_1mn: None,
+            sources: config.sources(),
+            gap_policy: config.gap_policy,
+            max_gap: config.max_gap_minutes * 60,
+            max_previous_blocks: config.max_previous_blocks,
+            aggregation: config.aggregation,
+            max_deviation: config.max_deviation,
 
-            closes: HeightMap::_new_json(1, &format!("{parent_path}/{name}"), usize::MAX, false),
+            opens: f("open").with_metadata(Unit::Dollar, "Open price of the block"),
+            highs: f("high").with_metadata(Unit::Dollar, "High price of the block"),
+            lows: f("low").with_metadata(Unit::Dollar, "Low price of the block"),
+            closes: f("close").with_metadata(Unit::Dollar, "Close price of the block"),
+
+            price_source: HeightMap::_new_json(
+                1,
+                &format!("{quality_path}/source"),
+                usize::MAX,
+                false,
+            ),
+            price_method: HeightMap::_new_json(
+                1,
+                &format!("{quality_path}/method"),
+                usize::MAX,
+                false,
+            ),
+            price_spread: HeightMap::_new_json(
+                1,
+                &format!("{quality_path}/spread"),
+                usize::MAX,
+                false,
+            ),
+            price_sources_count: HeightMap::_new_json(
+                1,
+                &format!("{quality_path}/sources_count"),
+                usize::MAX,
+                false,
+            ),
         };
 
         s.min_initial_state
@@ -39,79 +125,365 @@ impl HeightDataset {
         Ok(s)
     }
 
-    pub fn get(&mut self, height: usize, timestamp: u32) -> color_eyre::Result<f32> {
-        if let Some(price) = self.closes.get(&height) {
-            return Ok(price);
-        }
+    pub fn get(
+        &mut self,
+        height: usize,
+        timestamp: u32,
+        date_dataset: &mut DateDataset,
+    ) -> color_eyre::Result<f32> {
+        Ok(self.get_candle(height, timestamp, date_dataset)?.close)
+    }
+
+    /// Candle of the minute of the block
+    pub fn get_candle(
+        &mut self,
+        height: usize,
+        timestamp: u32,
+        date_dataset: &mut DateDataset,
+    ) -> color_eyre::Result<Candle> {
+        let (
+            AggregatedCandle {
+                candle,
+                source,
+                sources_count,
+                spread,
+            },
+            method,
+        ) = if let Some(close) = self.closes.get(&height) {
+            let candle = match (
+                self.opens.get(&height),
+                self.highs.get(&height),
+                self.lows.get(&height),
+            ) {
+                (Some(open), Some(high), Some(low)) => Candle {
+                    open,
+                    high,
+                    low,
+                    close,
+                    volume: 0.0,
+                },
+                // Only the close was stored, looking its candle up again would hit the sources for
+                // every height of an existing install so the candle is left flat
+                _ => Candle::from_close(close),
+            };
+
+            (
+                AggregatedCandle {
+                    candle,
+                    source: self.price_source.get(&height).unwrap_or_default(),
+                    sources_count: self.price_sources_count.get(&height).unwrap_or_default(),
+                    spread: self.price_spread.get(&height).unwrap_or_default(),
+                },
+                self.price_method
+                    .get(&height)
+                    .unwrap_or(PriceMethod::Unknown as u8),
+            )
+        } else {
+            let (aggregated, method) = self.find_candle(height, timestamp, date_dataset)?;
+
+            (aggregated, method as u8)
+        };
+
+        self.opens.insert(height, candle.open);
+        self.highs.insert(height, candle.high);
+        self.lows.insert(height, candle.low);
+        self.closes.insert(height, candle.close);
+
+        self.price_source.insert(height, source);
+        self.price_method.insert(height, method);
+        self.price_spread.insert(height, spread);
+        self.price_sources_count.insert(height, sources_count);
+
+        Ok(candle)
+    }
 
+    fn find_candle(
+        &mut self,
+        height: usize,
+        timestamp: u32,
+        date_dataset: &mut DateDataset,
+    ) -> color_eyre::Result<(AggregatedCandle, PriceMethod)> {
         let date_time = Utc.timestamp_opt(i64::from(timestamp), 0).unwrap();
-        let timestamp = NaiveDateTime::new(
+        let minute = NaiveDateTime::new(
             date_time.date_naive(),
             NaiveTime::from_hms_opt(date_time.hour(), date_time.minute(), 0).unwrap(),
         )
         .and_utc()
         .timestamp() as u32;
 
-        let price = self.get_from_1mn_kraken(timestamp).unwrap_or_else(|_| {
-                self.get_from_1mn_binance(timestamp)
-                    .unwrap_or_else(|_| self.get_from_har_binance(timestamp).unwrap_or_else(|_| {
-                        panic!(
-                            "Can't find price for {height} - {date_time} - {timestamp}, please update binance.har file"
-                        )
-                    }))
-            });
-
-        self.closes.insert(height, price);
+        let get_1mn = |source: &mut Box<dyn PriceSource + Send + Sync>| {
+            source
+                .get_1mn(minute)
+                .ok()
+                .map(|candle| (price_source_id(source.name()), candle))
+        };
 
-        Ok(price)
-    }
+        let candles = if self.aggregation == PriceAggregation::First {
+            self.sources
+                .iter_mut()
+                .find_map(get_1mn)
+                .into_iter()
+                .collect_vec()
+        } else {
+            self.sources.iter_mut().filter_map(get_1mn).collect_vec()
+        };
 
-    fn get_from_1mn_kraken(&mut self, timestamp: u32) -> color_eyre::Result<f32> {
-        if self.kraken_1mn.is_none() {
-            self.kraken_1mn.replace(Kraken::fetch_1mn_prices()?);
+        if let Some(aggregated) = self.aggregation.aggregate(&candles, self.max_deviation) {
+            return Ok((aggregated, PriceMethod::Exact));
         }
 
-        self.kraken_1mn
-            .as_ref()
-            .unwrap()
-            .get(&timestamp)
-            .cloned()
-            .ok_or(Error::msg("Couldn't find timestamp in 1mn kraken"))
+        let found = self.gap_policy.clone().into_iter().find_map(|method| {
+            let (candle, source) = match method {
+                PriceMethod::Nearest => self.find_around(minute, |around| {
+                    [around.before, around.after]
+                        .into_iter()
+                        .flatten()
+                        .min_by_key(|(timestamp, _)| timestamp.abs_diff(minute))
+                        .map(|(_, candle)| candle)
+                })?,
+                PriceMethod::Interpolate => self.find_around(minute, |around| {
+                    let (before_timestamp, before) = around.before?;
+                    let (after_timestamp, after) = around.after?;
+
+                    if before_timestamp == after_timestamp {
+                        return Some(before);
+                    }
+
+                    let ratio = (minute - before_timestamp) as f32
+                        / (after_timestamp - before_timestamp) as f32;
+
+                    Some(Candle::from_close(
+                        before.close + (after.close - before.close) * ratio,
+                    ))
+                })?,
+                PriceMethod::DailyClose => {
+                    let date = timestamp_to_naive_date(timestamp);
+
+                    (
+                        Candle::from_close(date_dataset.get(date).ok()?),
+                        date_dataset.price_source.get(date).unwrap_or_default(),
+                    )
+                }
+                PriceMethod::PreviousBlock => self.previous_block_candle(height)?,
+                PriceMethod::Unknown | PriceMethod::Exact | PriceMethod::PreviousDate => {
+                    return None
+                }
+            };
+
+            Some((candle, source, method))
+        });
+
+        let (candle, source, method) = found.ok_or_else(|| {
+            let sources = self.sources.iter().map(|source| source.name()).join(", ");
+
+            Error::msg(format!(
+                "Can't find price for {height} - {date_time} - {minute} in {sources} with the gap policy {:?}, please add candles to imports/candles",
+                self.gap_policy
+            ))
+        })?;
+
+        println!("price: {height} - {date_time} has no exact candle, using {method:?}");
+
+        let sources_count = match method {
+            PriceMethod::Nearest | PriceMethod::Interpolate => 1,
+            _ => 0,
+        };
+
+        Ok((
+            AggregatedCandle {
+                candle,
+                source,
+                sources_count,
+                spread: 0.0,
+            },
+            method,
+        ))
     }
 
-    fn get_from_1mn_binance(&mut self, timestamp: u32) -> color_eyre::Result<f32> {
-        if self.binance_1mn.is_none() {
-            self.binance_1mn.replace(Binance::fetch_1mn_prices()?);
-        }
+    /// First source with candles around the minute that `pick` accepts
+    fn find_around<F>(&mut self, minute: u32, pick: F) -> Option<(Candle, u8)>
+    where
+        F: Fn(Around) -> Option<Candle>,
+    {
+        let max_gap = self.max_gap;
 
-        self.binance_1mn
-            .as_ref()
-            .unwrap()
-            .get(&timestamp)
-            .cloned()
-            .ok_or(Error::msg("Couldn't find timestamp in 1mn binance"))
+        self.sources.iter_mut().find_map(|source| {
+            source
+                .get_1mn_around(minute, max_gap)
+                .ok()
+                .and_then(&pick)
+                .map(|candle| (candle, price_source_id(source.name())))
+        })
     }
 
-    fn get_from_har_binance(&mut self, timestamp: u32) -> color_eyre::Result<f32> {
-        if self.binance_har.is_none() {
-            self.binance_har.replace(Binance::read_har_file()?);
-        }
+    /// Close and source of the last block priced by another method than `PreviousBlock`, within
+    /// `max_previous_blocks`
+    fn previous_block_candle(&self, height: usize) -> Option<(Candle, u8)> {
+        (1..=self.max_previous_blocks).find_map(|blocks| {
+            let previous_height = height.checked_sub(blocks)?;
 
-        self.binance_har
-            .as_ref()
-            .unwrap()
-            .get(&timestamp)
-            .cloned()
-            .ok_or(Error::msg("Couldn't find timestamp in har binance"))
+            if self.price_method.get(&previous_height) == Some(PriceMethod::PreviousBlock as u8) {
+                return None;
+            }
+
+            Some((
+                Candle::from_close(self.closes.get(&previous_height)?),
+                self.price_source.get(&previous_height).unwrap_or_default(),
+            ))
+        })
     }
 }
 
 impl AnyDataset for HeightDataset {
+    fn name(&self) -> &str {
+        "price"
+    }
+
     fn get_min_initial_state(&self) -> &MinInitialState {
         &self.min_initial_state
     }
 
     fn to_any_height_map_vec(&self) -> Vec<&(dyn AnyHeightMap + Send + Sync)> {
-        vec![&self.closes]
+        vec![
+            &self.opens,
+            &self.highs,
+            &self.lows,
+            &self.closes,
+            &self.price_source,
+            &self.price_method,
+            &self.price_spread,
+            &self.price_sources_count,
+        ]
+    }
+
+    fn to_any_mut_height_map_vec(&mut self) -> Vec<&mut dyn AnyHeightMap> {
+        vec![
+            &mut self.opens,
+            &mut self.highs,
+            &mut self.lows,
+            &mut self.closes,
+            &mut self.price_source,
+            &mut self.price_method,
+            &mut self.price_spread,
+            &mut self.price_sources_count,
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use chrono::NaiveDate;
+
+    use crate::datasets::price::date::tests::Daily;
+
+    use super::*;
+
+    /// Only has the candle of `0` around any minute
+    struct Before(u32, Candle);
+
+    impl PriceSource for Before {
+        fn name(&self) -> &str {
+            "kraken"
+        }
+
+        fn get_1mn(&mut self, _: u32) -> color_eyre::Result<Candle> {
+            Err(Error::msg("No 1mn candles"))
+        }
+
+        fn get_1mn_around(&mut self, _: u32, _: u32) -> color_eyre::Result<Around> {
+            Ok(Around {
+                before: Some((self.0, self.1)),
+                after: None,
+            })
+        }
+
+        fn get_daily(&mut self, _: NaiveDate) -> color_eyre::Result<Candle> {
+            Err(Error::msg("No daily candles"))
+        }
+    }
+
+    #[test]
+    fn tries_only_the_gap_policy_in_order() {
+        let folder = std::env::temp_dir().join(format!("parser/{}/gaps", std::process::id()));
+
+        let _ = fs::remove_dir_all(&folder);
+
+        let f =
+            |s: &str| HeightMap::_new_json(1, folder.join(s).to_str().unwrap(), usize::MAX, false);
+        let g =
+            |s: &str| HeightMap::_new_json(1, folder.join(s).to_str().unwrap(), usize::MAX, false);
+
+        let date = NaiveDate::from_ymd_opt(2020, 1, 2).unwrap();
+        let timestamp = date.and_hms_opt(0, 10, 0).unwrap().and_utc().timestamp() as u32;
+
+        let mut date_dataset = DateDataset::new(
+            folder.to_str().unwrap(),
+            vec![Box::new(Daily(BTreeMap::from([(
+                date,
+                Candle::from_close(7100.0),
+            )])))],
+            None,
+        );
+
+        let mut dataset = HeightDataset {
+            min_initial_state: MinInitialState::default(),
+            sources: vec![Box::new(Before(
+                timestamp - 300,
+                Candle::from_close(7000.0),
+            ))],
+            gap_policy: vec![],
+            max_gap: 3600,
+            max_previous_blocks: 1,
+            aggregation: PriceAggregation::First,
+            max_deviation: 0.02,
+            opens: f("open"),
+            highs: f("high"),
+            lows: f("low"),
+            closes: f("close"),
+            price_source: g("quality/source"),
+            price_method: g("quality/method"),
+            price_spread: f("quality/spread"),
+            price_sources_count: g("quality/sources_count"),
+        };
+
+        dataset.closes.insert(0, 6000.0);
+        dataset.price_source.insert(0, price_source_id("binance"));
+
+        let mut find_at = |height: usize, gap_policy: Vec<PriceMethod>| {
+            dataset.gap_policy = gap_policy;
+
+            dataset
+                .find_candle(height, timestamp, &mut date_dataset)
+                .map(|(aggregated, method)| (aggregated.candle.close, aggregated.source, method))
+        };
+
+        assert_eq!(
+            find_at(1, vec![PriceMethod::Interpolate, PriceMethod::Nearest]).unwrap(),
+            (7000.0, price_source_id("kraken"), PriceMethod::Nearest)
+        );
+
+        assert_eq!(
+            find_at(1, vec![PriceMethod::PreviousBlock, PriceMethod::Nearest]).unwrap(),
+            (
+                6000.0,
+                price_source_id("binance"),
+                PriceMethod::PreviousBlock
+            )
+        );
+
+        assert_eq!(
+            find_at(1, vec![PriceMethod::DailyClose, PriceMethod::PreviousBlock]).unwrap(),
+            (7100.0, price_source_id("local"), PriceMethod::DailyClose)
+        );
+
+        // Not older than `max_previous_blocks`
+        assert!(find_at(2, vec![PriceMethod::PreviousBlock]).is_err());
+
+        // No implicit previous block
+        assert!(find_at(1, vec![PriceMethod::Interpolate]).is_err());
+
+        let _ = fs::remove_dir_all(folder);
     }
 }
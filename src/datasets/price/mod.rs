@@ -5,6 +5,8 @@ use chrono::NaiveDate;
 use date::*;
 use height::*;
 
+use crate::io::PRICE_FOLDER_PATH;
+
 use super::{AnyDataset, AnyDatasets, MinInitialState};
 
 pub struct PriceDatasets {
@@ -16,7 +18,7 @@ pub struct PriceDatasets {
 
 impl PriceDatasets {
     pub fn import() -> color_eyre::Result<Self> {
-        let path = "./price";
+        let path = PRICE_FOLDER_PATH;
 
         let mut s = Self {
             min_initial_state: MinInitialState::default(),
@@ -36,7 +38,7 @@ impl PriceDatasets {
     }
 
     pub fn height_to_close(&mut self, height: usize, timestamp: u32) -> color_eyre::Result<f32> {
-        self.height.get(height, timestamp)
+        self.height.get(height, timestamp, &mut self.date)
     }
 }
 
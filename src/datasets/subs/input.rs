@@ -1,6 +1,6 @@
 use crate::{
     datasets::{AnyDataset, MinInitialState, ProcessedBlockData},
-    parse::{AnyBiMap, BiMap},
+    parse::{AnyBiMap, BiMap, Unit},
     states::InputState,
 };
 
@@ -18,8 +18,10 @@ impl InputSubDataset {
         let mut s = Self {
             min_initial_state: MinInitialState::default(),
 
-            count: BiMap::new_bin(1, &f("input_count")),
-            volume: BiMap::new_bin(1, &f("input_volume")),
+            count: BiMap::new_bin_sum(1, &f("input_count"))
+                .with_metadata(Unit::Count, "Inputs spent"),
+            volume: BiMap::new_bin_sum(1, &f("input_volume"))
+                .with_metadata(Unit::Bitcoin, "Amount sent by the inputs spent"),
         };
 
         s.min_initial_state
@@ -52,6 +54,10 @@ impl InputSubDataset {
 }
 
 impl AnyDataset for InputSubDataset {
+    fn name(&self) -> &str {
+        "input"
+    }
+
     fn get_min_initial_state(&self) -> &MinInitialState {
         &self.min_initial_state
     }
@@ -1,6 +1,6 @@
 use crate::{
     datasets::{AnyDataset, MinInitialState, ProcessedBlockData},
-    parse::{AnyBiMap, BiMap},
+    parse::{AnyBiMap, BiMap, Unit},
     states::OutputState,
 };
 
@@ -18,8 +18,10 @@ impl OutputSubDataset {
         let mut s = Self {
             min_initial_state: MinInitialState::default(),
 
-            count: BiMap::new_bin(1, &f("output_count")),
-            volume: BiMap::new_bin(1, &f("output_volume")),
+            count: BiMap::new_bin_sum(1, &f("output_count"))
+                .with_metadata(Unit::Count, "Outputs created"),
+            volume: BiMap::new_bin_sum(1, &f("output_volume"))
+                .with_metadata(Unit::Bitcoin, "Amount received by the outputs created"),
         };
 
         s.min_initial_state
@@ -52,6 +54,10 @@ impl OutputSubDataset {
 }
 
 impl AnyDataset for OutputSubDataset {
+    fn name(&self) -> &str {
+        "output"
+    }
+
     fn get_min_initial_state(&self) -> &MinInitialState {
         &self.min_initial_state
     }
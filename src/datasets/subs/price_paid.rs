@@ -3,7 +3,7 @@ use itertools::Itertools;
 
 use crate::{
     datasets::{AnyDataset, MinInitialState, ProcessedBlockData},
-    parse::{AnyBiMap, BiMap},
+    parse::{AnyBiMap, BiMap, Unit},
     states::PricePaidState,
 };
 
@@ -41,28 +41,51 @@ impl PricePaidSubDataset {
         let mut s = Self {
             min_initial_state: MinInitialState::default(),
 
-            realized_cap: BiMap::new_bin(1, &f("realized_cap")),
-            realized_price: BiMap::new_bin(1, &f("realized_price")),
+            realized_cap: BiMap::new_bin(1, &f("realized_cap")).with_metadata(
+                Unit::Dollar,
+                "Value of the supply at the price each coin was received at",
+            ),
+            realized_price: BiMap::new_bin(1, &f("realized_price"))
+                .with_metadata(Unit::Dollar, "Realized cap divided by the supply"),
 
-            pp_median: BiMap::new_bin(1, &f("median_price_paid")),
-            pp_95p: BiMap::new_bin(1, &f("95p_price_paid")),
-            pp_90p: BiMap::new_bin(1, &f("90p_price_paid")),
-            pp_85p: BiMap::new_bin(1, &f("85p_price_paid")),
-            pp_80p: BiMap::new_bin(1, &f("80p_price_paid")),
-            pp_75p: BiMap::new_bin(1, &f("75p_price_paid")),
-            pp_70p: BiMap::new_bin(1, &f("70p_price_paid")),
-            pp_65p: BiMap::new_bin(1, &f("65p_price_paid")),
-            pp_60p: BiMap::new_bin(1, &f("60p_price_paid")),
-            pp_55p: BiMap::new_bin(1, &f("55p_price_paid")),
-            pp_45p: BiMap::new_bin(1, &f("45p_price_paid")),
-            pp_40p: BiMap::new_bin(1, &f("40p_price_paid")),
-            pp_35p: BiMap::new_bin(1, &f("35p_price_paid")),
-            pp_30p: BiMap::new_bin(1, &f("30p_price_paid")),
-            pp_25p: BiMap::new_bin(1, &f("25p_price_paid")),
-            pp_20p: BiMap::new_bin(1, &f("20p_price_paid")),
-            pp_15p: BiMap::new_bin(1, &f("15p_price_paid")),
-            pp_10p: BiMap::new_bin(1, &f("10p_price_paid")),
-            pp_05p: BiMap::new_bin(1, &f("05p_price_paid")),
+            pp_median: BiMap::new_bin(1, &f("median_price_paid"))
+                .with_metadata(Unit::Dollar, "Price paid by half of the supply"),
+            pp_95p: BiMap::new_bin(1, &f("95p_price_paid"))
+                .with_metadata(Unit::Dollar, "Price paid by 95% of the supply"),
+            pp_90p: BiMap::new_bin(1, &f("90p_price_paid"))
+                .with_metadata(Unit::Dollar, "Price paid by 90% of the supply"),
+            pp_85p: BiMap::new_bin(1, &f("85p_price_paid"))
+                .with_metadata(Unit::Dollar, "Price paid by 85% of the supply"),
+            pp_80p: BiMap::new_bin(1, &f("80p_price_paid"))
+                .with_metadata(Unit::Dollar, "Price paid by 80% of the supply"),
+            pp_75p: BiMap::new_bin(1, &f("75p_price_paid"))
+                .with_metadata(Unit::Dollar, "Price paid by 75% of the supply"),
+            pp_70p: BiMap::new_bin(1, &f("70p_price_paid"))
+                .with_metadata(Unit::Dollar, "Price paid by 70% of the supply"),
+            pp_65p: BiMap::new_bin(1, &f("65p_price_paid"))
+                .with_metadata(Unit::Dollar, "Price paid by 65% of the supply"),
+            pp_60p: BiMap::new_bin(1, &f("60p_price_paid"))
+                .with_metadata(Unit::Dollar, "Price paid by 60% of the supply"),
+            pp_55p: BiMap::new_bin(1, &f("55p_price_paid"))
+                .with_metadata(Unit::Dollar, "Price paid by 55% of the supply"),
+            pp_45p: BiMap::new_bin(1, &f("45p_price_paid"))
+                .with_metadata(Unit::Dollar, "Price paid by 45% of the supply"),
+            pp_40p: BiMap::new_bin(1, &f("40p_price_paid"))
+                .with_metadata(Unit::Dollar, "Price paid by 40% of the supply"),
+            pp_35p: BiMap::new_bin(1, &f("35p_price_paid"))
+                .with_metadata(Unit::Dollar, "Price paid by 35% of the supply"),
+            pp_30p: BiMap::new_bin(1, &f("30p_price_paid"))
+                .with_metadata(Unit::Dollar, "Price paid by 30% of the supply"),
+            pp_25p: BiMap::new_bin(1, &f("25p_price_paid"))
+                .with_metadata(Unit::Dollar, "Price paid by 25% of the supply"),
+            pp_20p: BiMap::new_bin(1, &f("20p_price_paid"))
+                .with_metadata(Unit::Dollar, "Price paid by 20% of the supply"),
+            pp_15p: BiMap::new_bin(1, &f("15p_price_paid"))
+                .with_metadata(Unit::Dollar, "Price paid by 15% of the supply"),
+            pp_10p: BiMap::new_bin(1, &f("10p_price_paid"))
+                .with_metadata(Unit::Dollar, "Price paid by 10% of the supply"),
+            pp_05p: BiMap::new_bin(1, &f("05p_price_paid"))
+                .with_metadata(Unit::Dollar, "Price paid by 5% of the supply"),
         };
 
         s.min_initial_state
@@ -106,7 +129,10 @@ impl PricePaidSubDataset {
             ..
         } = state;
 
-        let realized_cap = self.realized_cap.height.insert(height, *realized_cap);
+        let realized_cap = self
+            .realized_cap
+            .height
+            .insert(height, *realized_cap as f32);
 
         if is_date_last_block {
             self.realized_cap.date.insert(date, realized_cap);
@@ -132,25 +158,28 @@ impl PricePaidSubDataset {
             return;
         }
 
-        let pp_05p = self.pp_05p.height.insert(height, pp_05p.unwrap());
-        let pp_10p = self.pp_10p.height.insert(height, pp_10p.unwrap());
-        let pp_15p = self.pp_15p.height.insert(height, pp_15p.unwrap());
-        let pp_20p = self.pp_20p.height.insert(height, pp_20p.unwrap());
-        let pp_25p = self.pp_25p.height.insert(height, pp_25p.unwrap());
-        let pp_30p = self.pp_30p.height.insert(height, pp_30p.unwrap());
-        let pp_35p = self.pp_35p.height.insert(height, pp_35p.unwrap());
-        let pp_40p = self.pp_40p.height.insert(height, pp_40p.unwrap());
-        let pp_45p = self.pp_45p.height.insert(height, pp_45p.unwrap());
-        let pp_median = self.pp_median.height.insert(height, pp_median.unwrap());
-        let pp_55p = self.pp_55p.height.insert(height, pp_55p.unwrap());
-        let pp_60p = self.pp_60p.height.insert(height, pp_60p.unwrap());
-        let pp_65p = self.pp_65p.height.insert(height, pp_65p.unwrap());
-        let pp_70p = self.pp_70p.height.insert(height, pp_70p.unwrap());
-        let pp_75p = self.pp_75p.height.insert(height, pp_75p.unwrap());
-        let pp_80p = self.pp_80p.height.insert(height, pp_80p.unwrap());
-        let pp_85p = self.pp_85p.height.insert(height, pp_85p.unwrap());
-        let pp_90p = self.pp_90p.height.insert(height, pp_90p.unwrap());
-        let pp_95p = self.pp_95p.height.insert(height, pp_95p.unwrap());
+        let pp_05p = self.pp_05p.height.insert(height, pp_05p.unwrap() as f32);
+        let pp_10p = self.pp_10p.height.insert(height, pp_10p.unwrap() as f32);
+        let pp_15p = self.pp_15p.height.insert(height, pp_15p.unwrap() as f32);
+        let pp_20p = self.pp_20p.height.insert(height, pp_20p.unwrap() as f32);
+        let pp_25p = self.pp_25p.height.insert(height, pp_25p.unwrap() as f32);
+        let pp_30p = self.pp_30p.height.insert(height, pp_30p.unwrap() as f32);
+        let pp_35p = self.pp_35p.height.insert(height, pp_35p.unwrap() as f32);
+        let pp_40p = self.pp_40p.height.insert(height, pp_40p.unwrap() as f32);
+        let pp_45p = self.pp_45p.height.insert(height, pp_45p.unwrap() as f32);
+        let pp_median = self
+            .pp_median
+            .height
+            .insert(height, pp_median.unwrap() as f32);
+        let pp_55p = self.pp_55p.height.insert(height, pp_55p.unwrap() as f32);
+        let pp_60p = self.pp_60p.height.insert(height, pp_60p.unwrap() as f32);
+        let pp_65p = self.pp_65p.height.insert(height, pp_65p.unwrap() as f32);
+        let pp_70p = self.pp_70p.height.insert(height, pp_70p.unwrap() as f32);
+        let pp_75p = self.pp_75p.height.insert(height, pp_75p.unwrap() as f32);
+        let pp_80p = self.pp_80p.height.insert(height, pp_80p.unwrap() as f32);
+        let pp_85p = self.pp_85p.height.insert(height, pp_85p.unwrap() as f32);
+        let pp_90p = self.pp_90p.height.insert(height, pp_90p.unwrap() as f32);
+        let pp_95p = self.pp_95p.height.insert(height, pp_95p.unwrap() as f32);
 
         if is_date_last_block {
             self.pp_05p.date.insert(date, pp_05p);
@@ -241,6 +270,10 @@ impl PricePaidSubDataset {
 }
 
 impl AnyDataset for PricePaidSubDataset {
+    fn name(&self) -> &str {
+        "price_paid"
+    }
+
     fn get_min_initial_state(&self) -> &MinInitialState {
         &self.min_initial_state
     }
@@ -1,6 +1,6 @@
 use crate::{
     datasets::{AnyDataset, MinInitialState, ProcessedBlockData},
-    parse::{AnyBiMap, BiMap},
+    parse::{AnyBiMap, BiMap, Unit},
     states::RealizedState,
 };
 
@@ -19,8 +19,14 @@ impl RealizedSubDataset {
         let mut s = Self {
             min_initial_state: MinInitialState::default(),
 
-            realized_profit: BiMap::new_bin(1, &f("realized_profit")),
-            realized_loss: BiMap::new_bin(1, &f("realized_loss")),
+            realized_profit: BiMap::new_bin_sum(1, &f("realized_profit")).with_metadata(
+                Unit::Dollar,
+                "Profit of the coins spent above the price they were received at",
+            ),
+            realized_loss: BiMap::new_bin_sum(1, &f("realized_loss")).with_metadata(
+                Unit::Dollar,
+                "Loss of the coins spent below the price they were received at",
+            ),
         };
 
         s.min_initial_state
@@ -42,11 +48,11 @@ impl RealizedSubDataset {
     ) {
         self.realized_profit
             .height
-            .insert(height, height_state.realized_profit);
+            .insert(height, height_state.realized_profit as f32);
 
         self.realized_loss
             .height
-            .insert(height, height_state.realized_loss);
+            .insert(height, height_state.realized_loss as f32);
 
         if is_date_last_block {
             self.realized_profit
@@ -59,6 +65,10 @@ impl RealizedSubDataset {
 }
 
 impl AnyDataset for RealizedSubDataset {
+    fn name(&self) -> &str {
+        "realized"
+    }
+
     fn get_min_initial_state(&self) -> &MinInitialState {
         &self.min_initial_state
     }
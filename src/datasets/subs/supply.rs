@@ -1,7 +1,7 @@
 use crate::{
     bitcoin::sats_to_btc,
     datasets::{AnyDataset, MinInitialState, ProcessedBlockData},
-    parse::{AnyBiMap, BiMap},
+    parse::{AnyBiMap, BiMap, Unit},
     states::SupplyState,
 };
 
@@ -18,7 +18,7 @@ impl SupplySubDataset {
         let mut s = Self {
             min_initial_state: MinInitialState::default(),
 
-            total: BiMap::new_bin(1, &f("supply")),
+            total: BiMap::new_bin(1, &f("supply")).with_metadata(Unit::Bitcoin, "Supply"),
         };
 
         s.min_initial_state
@@ -46,6 +46,10 @@ impl SupplySubDataset {
 }
 
 impl AnyDataset for SupplySubDataset {
+    fn name(&self) -> &str {
+        "supply"
+    }
+
     fn get_min_initial_state(&self) -> &MinInitialState {
         &self.min_initial_state
     }
@@ -1,7 +1,7 @@
 use crate::{
     bitcoin::sats_to_btc,
     datasets::{AnyDataset, MinInitialState, ProcessedBlockData},
-    parse::{AnyBiMap, BiMap},
+    parse::{AnyBiMap, BiMap, Unit},
     states::UnrealizedState,
 };
 
@@ -20,9 +20,16 @@ impl UnrealizedSubDataset {
         let mut s = Self {
             min_initial_state: MinInitialState::default(),
 
-            supply_in_profit: BiMap::new_bin(1, &f("supply_in_profit")),
-            unrealized_profit: BiMap::new_bin(1, &f("unrealized_profit")),
-            unrealized_loss: BiMap::new_bin(1, &f("unrealized_loss")),
+            supply_in_profit: BiMap::new_bin(1, &f("supply_in_profit"))
+                .with_metadata(Unit::Bitcoin, "Supply received below the current price"),
+            unrealized_profit: BiMap::new_bin(1, &f("unrealized_profit")).with_metadata(
+                Unit::Dollar,
+                "Profit of the supply if it was sold at the current price",
+            ),
+            unrealized_loss: BiMap::new_bin(1, &f("unrealized_loss")).with_metadata(
+                Unit::Dollar,
+                "Loss of the supply if it was sold at the current price",
+            ),
         };
 
         s.min_initial_state
@@ -48,11 +55,11 @@ impl UnrealizedSubDataset {
 
         self.unrealized_profit
             .height
-            .insert(height, block_state.unrealized_profit);
+            .insert(height, block_state.unrealized_profit as f32);
 
         self.unrealized_loss
             .height
-            .insert(height, block_state.unrealized_loss);
+            .insert(height, block_state.unrealized_loss as f32);
 
         if is_date_last_block {
             let date_state = date_state.as_ref().unwrap();
@@ -63,16 +70,20 @@ impl UnrealizedSubDataset {
 
             self.unrealized_profit
                 .date
-                .insert(date, date_state.unrealized_profit);
+                .insert(date, date_state.unrealized_profit as f32);
 
             self.unrealized_loss
                 .date
-                .insert(date, date_state.unrealized_loss);
+                .insert(date, date_state.unrealized_loss as f32);
         }
     }
 }
 
 impl AnyDataset for UnrealizedSubDataset {
+    fn name(&self) -> &str {
+        "unrealized"
+    }
+
     fn get_min_initial_state(&self) -> &MinInitialState {
         &self.min_initial_state
     }
@@ -1,6 +1,6 @@
 use crate::{
     datasets::{AnyDataset, MinInitialState, ProcessedBlockData},
-    parse::{AnyBiMap, BiMap},
+    parse::{AnyBiMap, BiMap, Unit},
     states::UTXOState,
 };
 
@@ -17,7 +17,8 @@ impl UTXOSubDataset {
         let mut s = Self {
             min_initial_state: MinInitialState::default(),
 
-            count: BiMap::new_bin(1, &f("utxo_count")),
+            count: BiMap::new_bin(1, &f("utxo_count"))
+                .with_metadata(Unit::Count, "Unspent outputs"),
         };
 
         s.min_initial_state
@@ -45,6 +46,10 @@ impl UTXOSubDataset {
 }
 
 impl AnyDataset for UTXOSubDataset {
+    fn name(&self) -> &str {
+        "utxo"
+    }
+
     fn get_min_initial_state(&self) -> &MinInitialState {
         &self.min_initial_state
     }
@@ -1,7 +1,9 @@
+use itertools::Itertools;
+
 use crate::{
     bitcoin::{sats_to_btc, ONE_YEAR_IN_BLOCK_TIME},
     datasets::ProcessedBlockData,
-    parse::{AnyBiMap, BiMap},
+    parse::{AnyBiMap, BiMap, SplitByTxShape, Unit},
     utils::ONE_YEAR_IN_DAYS,
 };
 
@@ -15,6 +17,9 @@ pub struct TransactionDataset {
 
     pub annualized_volume: BiMap<f32>,
     pub velocity: BiMap<f32>,
+
+    pub count_by_shape: SplitByTxShape<BiMap<usize>>,
+    pub volume_by_shape: SplitByTxShape<BiMap<f32>>,
 }
 
 impl TransactionDataset {
@@ -24,11 +29,51 @@ impl TransactionDataset {
         let mut s = Self {
             min_initial_state: MinInitialState::default(),
 
-            count: BiMap::new_bin(1, &f("transaction_count")),
-            volume: BiMap::_new_bin(1, &f("transaction_volume"), 5),
-
-            annualized_volume: BiMap::new_bin(1, &f("annualized_transaction_volume")),
-            velocity: BiMap::new_bin(1, &f("transaction_velocity")),
+            count: BiMap::new_bin_sum(1, &f("transaction_count"))
+                .with_metadata(Unit::Count, "Transactions"),
+            volume: BiMap::new_bin_sum(1, &f("transaction_volume"))
+                .with_metadata(Unit::Bitcoin, "Amount sent by the transactions"),
+
+            annualized_volume: BiMap::new_bin(1, &f("annualized_transaction_volume"))
+                .with_metadata(Unit::Bitcoin, "Transaction volume of the last year"),
+            velocity: BiMap::new_bin(1, &f("transaction_velocity")).with_metadata(
+                Unit::Ratio,
+                "Annualized transaction volume divided by the supply",
+            ),
+
+            count_by_shape: SplitByTxShape {
+                consolidation: BiMap::new_bin_sum(1, &f("consolidation_transaction_count"))
+                    .with_metadata(
+                        Unit::Count,
+                        "Transactions merging many inputs into a single output",
+                    ),
+                batch: BiMap::new_bin_sum(1, &f("batch_transaction_count")).with_metadata(
+                    Unit::Count,
+                    "Transactions paying out to more outputs than they have inputs",
+                ),
+                self_transfer: BiMap::new_bin_sum(1, &f("self_transfer_transaction_count"))
+                    .with_metadata(Unit::Count, "Transactions with one input and one output"),
+                simple: BiMap::new_bin_sum(1, &f("simple_transaction_count")).with_metadata(
+                    Unit::Count,
+                    "Every other transaction, mostly a payment and its change",
+                ),
+            },
+            volume_by_shape: SplitByTxShape {
+                consolidation: BiMap::new_bin_sum(1, &f("consolidation_transaction_volume"))
+                    .with_metadata(
+                        Unit::Bitcoin,
+                        "Amount sent by the consolidation transactions",
+                    ),
+                batch: BiMap::new_bin_sum(1, &f("batch_transaction_volume"))
+                    .with_metadata(Unit::Bitcoin, "Amount sent by the batch transactions"),
+                self_transfer: BiMap::new_bin_sum(1, &f("self_transfer_transaction_volume"))
+                    .with_metadata(
+                        Unit::Bitcoin,
+                        "Amount sent by the self transfer transactions",
+                    ),
+                simple: BiMap::new_bin_sum(1, &f("simple_transaction_volume"))
+                    .with_metadata(Unit::Bitcoin, "Amount sent by the simple transactions"),
+            },
         };
 
         s.min_initial_state
@@ -46,6 +91,7 @@ impl TransactionDataset {
             transaction_count,
             is_date_last_block,
             date_blocks_range,
+            tx_shapes,
             ..
         }: &ProcessedBlockData,
         address_datasets: &AddressDatasets,
@@ -67,6 +113,16 @@ impl TransactionDataset {
             .height
             .insert(height, annualized_volume / circulating_supply);
 
+        self.count_by_shape
+            .as_mut_vec()
+            .into_iter()
+            .zip(self.volume_by_shape.as_mut_vec())
+            .zip(tx_shapes.as_vec())
+            .for_each(|((count, volume), shape_data)| {
+                count.height.insert(height, shape_data.count as usize);
+                volume.height.insert(height, sats_to_btc(shape_data.volume));
+            });
+
         if is_date_last_block {
             self.count.date_insert_sum_range(date, date_blocks_range);
 
@@ -81,30 +137,78 @@ impl TransactionDataset {
             self.velocity
                 .date
                 .insert(date, annualized_volume / circulating_supply);
+
+            self.count_by_shape
+                .as_mut_vec()
+                .into_iter()
+                .for_each(|count| count.date_insert_sum_range(date, date_blocks_range));
+
+            self.volume_by_shape
+                .as_mut_vec()
+                .into_iter()
+                .for_each(|volume| volume.date_insert_sum_range(date, date_blocks_range));
         }
     }
 }
 
 impl AnyDataset for TransactionDataset {
+    fn name(&self) -> &str {
+        "transaction"
+    }
+
     fn get_min_initial_state(&self) -> &MinInitialState {
         &self.min_initial_state
     }
 
     fn to_any_bi_map_vec(&self) -> Vec<&(dyn AnyBiMap + Send + Sync)> {
-        vec![
+        let mut vec: Vec<&(dyn AnyBiMap + Send + Sync)> = vec![
             &self.count,
             &self.volume,
             &self.annualized_volume,
             &self.velocity,
-        ]
+        ];
+
+        vec.append(
+            &mut self
+                .count_by_shape
+                .as_vec()
+                .into_iter()
+                .map(|map| map as &(dyn AnyBiMap + Send + Sync))
+                .chain(
+                    self.volume_by_shape
+                        .as_vec()
+                        .into_iter()
+                        .map(|map| map as &(dyn AnyBiMap + Send + Sync)),
+                )
+                .collect_vec(),
+        );
+
+        vec
     }
 
     fn to_any_mut_bi_map_vec(&mut self) -> Vec<&mut dyn AnyBiMap> {
-        vec![
+        let mut vec: Vec<&mut dyn AnyBiMap> = vec![
             &mut self.count,
             &mut self.volume,
             &mut self.annualized_volume,
             &mut self.velocity,
-        ]
+        ];
+
+        vec.append(
+            &mut self
+                .count_by_shape
+                .as_mut_vec()
+                .into_iter()
+                .map(|map| map as &mut dyn AnyBiMap)
+                .chain(
+                    self.volume_by_shape
+                        .as_mut_vec()
+                        .into_iter()
+                        .map(|map| map as &mut dyn AnyBiMap),
+                )
+                .collect_vec(),
+        );
+
+        vec
     }
 }
@@ -0,0 +1,85 @@
+use crate::{
+    bitcoin::sats_to_btc,
+    datasets::AnyDataset,
+    parse::{AnyBiMap, BiMap, Unit},
+};
+
+use super::{MinInitialState, ProcessedBlockData};
+
+/// Sats that were created or sent but can never be spent: the genesis coinbase, the coinbases overwritten
+/// by BIP30 duplicates, provably unspendable outputs and unclaimed rewards
+pub struct UnspendableDataset {
+    min_initial_state: MinInitialState,
+
+    pub unspendable: BiMap<f32>,
+    pub unspendable_supply: BiMap<f32>,
+}
+
+impl UnspendableDataset {
+    pub fn import(parent_path: &str) -> color_eyre::Result<Self> {
+        let f = |s: &str| format!("{parent_path}/{s}");
+
+        let mut s = Self {
+            min_initial_state: MinInitialState::default(),
+
+            unspendable: BiMap::new_bin_sum(1, &f("unspendable"))
+                .with_metadata(Unit::Bitcoin, "Amount sent to unspendable outputs"),
+            unspendable_supply: BiMap::new_bin(1, &f("unspendable_supply")).with_metadata(
+                Unit::Bitcoin,
+                "Amount sent to unspendable outputs since the genesis block",
+            ),
+        };
+
+        s.min_initial_state
+            .consume(MinInitialState::compute_from_dataset(&s));
+
+        Ok(s)
+    }
+
+    pub fn insert_data(
+        &mut self,
+        &ProcessedBlockData {
+            date,
+            date_blocks_range,
+            height,
+            is_date_last_block,
+            unspendable,
+            ..
+        }: &ProcessedBlockData,
+    ) {
+        self.unspendable
+            .height
+            .insert(height, sats_to_btc(unspendable));
+
+        self.unspendable_supply
+            .height
+            .insert_cumulative(height, &self.unspendable.height);
+
+        if is_date_last_block {
+            self.unspendable
+                .date_insert_sum_range(date, date_blocks_range);
+
+            self.unspendable_supply
+                .date
+                .insert_cumulative(date, &self.unspendable.date);
+        }
+    }
+}
+
+impl AnyDataset for UnspendableDataset {
+    fn name(&self) -> &str {
+        "unspendable"
+    }
+
+    fn get_min_initial_state(&self) -> &MinInitialState {
+        &self.min_initial_state
+    }
+
+    fn to_any_bi_map_vec(&self) -> Vec<&(dyn AnyBiMap + Send + Sync)> {
+        vec![&self.unspendable, &self.unspendable_supply]
+    }
+
+    fn to_any_mut_bi_map_vec(&mut self) -> Vec<&mut dyn AnyBiMap> {
+        vec![&mut self.unspendable, &mut self.unspendable_supply]
+    }
+}
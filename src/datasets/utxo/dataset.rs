@@ -103,10 +103,18 @@ impl UTXODataset {
 }
 
 impl AnyDataset for UTXODataset {
+    fn name(&self) -> &str {
+        "utxo"
+    }
+
     fn get_min_initial_state(&self) -> &MinInitialState {
         &self.min_initial_state
     }
 
+    fn cohorts(&self, _path: &str) -> Vec<(&'static str, &str)> {
+        vec![("utxo_age", self.id.name())]
+    }
+
     fn to_any_height_map_vec(&self) -> Vec<&(dyn AnyHeightMap + Send + Sync)> {
         self.subs
             .as_vec()
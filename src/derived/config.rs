@@ -0,0 +1,30 @@
+use std::{collections::BTreeMap, path::Path};
+
+use serde::Deserialize;
+
+use crate::io::{Json, IMPORTS_FOLDER_PATH};
+
+use super::Expression;
+
+/// Expressions by name of the series they compute, from `derived.json` in the imports folder
+#[derive(Debug, Default, Deserialize)]
+pub struct DerivedSeriesConfig(BTreeMap<String, String>);
+
+impl DerivedSeriesConfig {
+    pub fn import() -> color_eyre::Result<Self> {
+        let path = Path::new(IMPORTS_FOLDER_PATH).join("derived.json");
+
+        if path.exists() {
+            Json::import(path.to_str().unwrap())
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub fn parse(&self) -> color_eyre::Result<Vec<(&str, Expression)>> {
+        self.0
+            .iter()
+            .map(|(name, expression)| Ok((name.as_str(), Expression::parse(expression)?)))
+            .collect()
+    }
+}
@@ -0,0 +1,455 @@
+use std::{collections::BTreeMap, iter::Peekable, str::Chars};
+
+use color_eyre::eyre::{eyre, ContextCompat};
+use itertools::Itertools;
+
+use crate::utils::ArrayOperations;
+
+/// Formula over stored maps, for example `realized_cap / supply` or `sma(price_close, 200)`
+///
+/// Series are named after their path relative to the datasets folder with `_` instead of `/`.
+/// Operators apply to the keys that every side has, rolling functions drop the keys whose window
+/// isn't complete.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    Number(f64),
+    Series(String),
+    Negate(Box<Expression>),
+    Operation(Box<Expression>, Operator, Box<Expression>),
+    Function(Function, Vec<Expression>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Function {
+    /// `sma(series, size)`
+    SimpleMovingAverage,
+    /// `sum(series, size)`, sum of the last `size` values
+    Sum,
+    /// `net_change(series, offset)`
+    NetChange,
+    /// `median(series, size)`
+    Median,
+    /// `cumulate(series)`
+    Cumulate,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "sma" => Some(Self::SimpleMovingAverage),
+            "sum" => Some(Self::Sum),
+            "net_change" => Some(Self::NetChange),
+            "median" => Some(Self::Median),
+            "cumulate" => Some(Self::Cumulate),
+            _ => None,
+        }
+    }
+
+    fn arity(&self) -> usize {
+        match self {
+            Self::Cumulate => 1,
+            _ => 2,
+        }
+    }
+}
+
+enum Value<K> {
+    Scalar(f64),
+    Series(BTreeMap<K, f64>),
+}
+
+impl Expression {
+    pub fn parse(expression: &str) -> color_eyre::Result<Self> {
+        let mut chars = expression.chars().peekable();
+
+        let parsed = Self::parse_sum(&mut chars)?;
+
+        skip_whitespace(&mut chars);
+
+        if let Some(char) = chars.next() {
+            return Err(eyre!("Unexpected '{char}' in \"{expression}\""));
+        }
+
+        Ok(parsed)
+    }
+
+    /// Names of all the series used, sorted and without duplicates
+    pub fn series(&self) -> Vec<&str> {
+        let mut series = vec![];
+
+        self.collect_series(&mut series);
+
+        series.into_iter().sorted().dedup().collect()
+    }
+
+    /// Evaluates the expression with `resolve` giving the values of a series, `None` if a series
+    /// doesn't exist for this kind of key
+    pub fn evaluate<K>(
+        &self,
+        resolve: &mut impl FnMut(&str) -> color_eyre::Result<Option<BTreeMap<K, f64>>>,
+    ) -> color_eyre::Result<Option<BTreeMap<K, f64>>>
+    where
+        K: Ord + Clone,
+    {
+        Ok(match self._evaluate(resolve)? {
+            Some(Value::Series(series)) => Some(
+                series
+                    .into_iter()
+                    .filter(|(_, value)| value.is_finite())
+                    .collect(),
+            ),
+            _ => None,
+        })
+    }
+
+    fn _evaluate<K>(
+        &self,
+        resolve: &mut impl FnMut(&str) -> color_eyre::Result<Option<BTreeMap<K, f64>>>,
+    ) -> color_eyre::Result<Option<Value<K>>>
+    where
+        K: Ord + Clone,
+    {
+        Ok(match self {
+            Self::Number(number) => Some(Value::Scalar(*number)),
+            Self::Series(name) => resolve(name)?.map(Value::Series),
+            Self::Negate(expression) => expression._evaluate(resolve)?.map(|value| match value {
+                Value::Scalar(scalar) => Value::Scalar(-scalar),
+                Value::Series(series) => Value::Series(
+                    series
+                        .into_iter()
+                        .map(|(key, value)| (key, -value))
+                        .collect(),
+                ),
+            }),
+            Self::Operation(left, operator, right) => {
+                let (Some(left), Some(right)) =
+                    (left._evaluate(resolve)?, right._evaluate(resolve)?)
+                else {
+                    return Ok(None);
+                };
+
+                Some(operate(left, *operator, right))
+            }
+            Self::Function(function, arguments) => {
+                let series = match arguments[0]._evaluate(resolve)? {
+                    Some(Value::Series(series)) => series,
+                    Some(Value::Scalar(_)) => {
+                        return Err(eyre!("{function:?} needs a series as first argument"))
+                    }
+                    None => return Ok(None),
+                };
+
+                let size = match arguments.get(1) {
+                    Some(Self::Number(size)) if *size >= 1.0 && size.fract() == 0.0 => {
+                        *size as usize
+                    }
+                    Some(_) => return Err(eyre!("{function:?} needs a positive integer as size")),
+                    None => 1,
+                };
+
+                if *function == Function::Median && size < 3 {
+                    return Err(eyre!("Median needs a size of at least 3"));
+                }
+
+                Some(Value::Series(apply(*function, series, size)))
+            }
+        })
+    }
+
+    fn collect_series<'a>(&'a self, series: &mut Vec<&'a str>) {
+        match self {
+            Self::Number(_) => {}
+            Self::Series(name) => series.push(name),
+            Self::Negate(expression) => expression.collect_series(series),
+            Self::Operation(left, _, right) => {
+                left.collect_series(series);
+                right.collect_series(series);
+            }
+            Self::Function(_, arguments) => arguments
+                .iter()
+                .for_each(|argument| argument.collect_series(series)),
+        }
+    }
+
+    fn parse_sum(chars: &mut Peekable<Chars>) -> color_eyre::Result<Self> {
+        let mut expression = Self::parse_product(chars)?;
+
+        loop {
+            skip_whitespace(chars);
+
+            let operator = match chars.peek() {
+                Some('+') => Operator::Add,
+                Some('-') => Operator::Subtract,
+                _ => return Ok(expression),
+            };
+
+            chars.next();
+
+            expression = Self::Operation(
+                Box::new(expression),
+                operator,
+                Box::new(Self::parse_product(chars)?),
+            );
+        }
+    }
+
+    fn parse_product(chars: &mut Peekable<Chars>) -> color_eyre::Result<Self> {
+        let mut expression = Self::parse_unary(chars)?;
+
+        loop {
+            skip_whitespace(chars);
+
+            let operator = match chars.peek() {
+                Some('*') => Operator::Multiply,
+                Some('/') => Operator::Divide,
+                _ => return Ok(expression),
+            };
+
+            chars.next();
+
+            expression = Self::Operation(
+                Box::new(expression),
+                operator,
+                Box::new(Self::parse_unary(chars)?),
+            );
+        }
+    }
+
+    fn parse_unary(chars: &mut Peekable<Chars>) -> color_eyre::Result<Self> {
+        skip_whitespace(chars);
+
+        if chars.next_if_eq(&'-').is_some() {
+            return Ok(Self::Negate(Box::new(Self::parse_unary(chars)?)));
+        }
+
+        Self::parse_primary(chars)
+    }
+
+    fn parse_primary(chars: &mut Peekable<Chars>) -> color_eyre::Result<Self> {
+        match chars.peek() {
+            Some('(') => {
+                chars.next();
+
+                let expression = Self::parse_sum(chars)?;
+
+                skip_whitespace(chars);
+
+                chars.next_if_eq(&')').context("Missing ')'")?;
+
+                Ok(expression)
+            }
+            Some(char) if char.is_ascii_digit() || *char == '.' => {
+                let number = take_while(chars, |char| char.is_ascii_digit() || char == '.');
+
+                Ok(Self::Number(number.parse()?))
+            }
+            Some(char) if char.is_alphabetic() || *char == '_' => {
+                let name = take_while(chars, |char| char.is_alphanumeric() || char == '_');
+
+                skip_whitespace(chars);
+
+                if chars.next_if_eq(&'(').is_none() {
+                    return Ok(Self::Series(name));
+                }
+
+                let function =
+                    Function::from_name(&name).context(format!("Unknown function \"{name}\""))?;
+
+                let mut arguments = vec![Self::parse_sum(chars)?];
+
+                loop {
+                    skip_whitespace(chars);
+
+                    match chars.next() {
+                        Some(',') => arguments.push(Self::parse_sum(chars)?),
+                        Some(')') => break,
+                        _ => return Err(eyre!("Missing ')' after the arguments of \"{name}\"")),
+                    }
+                }
+
+                if arguments.len() != function.arity() {
+                    return Err(eyre!(
+                        "\"{name}\" takes {} arguments, {} given",
+                        function.arity(),
+                        arguments.len()
+                    ));
+                }
+
+                Ok(Self::Function(function, arguments))
+            }
+            Some(char) => Err(eyre!("Unexpected '{char}'")),
+            None => Err(eyre!("Unexpected end of expression")),
+        }
+    }
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while chars.next_if(|char| char.is_whitespace()).is_some() {}
+}
+
+fn take_while(chars: &mut Peekable<Chars>, predicate: impl Fn(char) -> bool) -> String {
+    let mut taken = String::new();
+
+    while let Some(char) = chars.next_if(|char| predicate(*char)) {
+        taken.push(char);
+    }
+
+    taken
+}
+
+fn operate<K>(left: Value<K>, operator: Operator, right: Value<K>) -> Value<K>
+where
+    K: Ord + Clone,
+{
+    let scalar = |left: f64, right: f64| match operator {
+        Operator::Add => left + right,
+        Operator::Subtract => left - right,
+        Operator::Multiply => left * right,
+        Operator::Divide => left / right,
+    };
+
+    match (left, right) {
+        (Value::Scalar(left), Value::Scalar(right)) => Value::Scalar(scalar(left, right)),
+        (Value::Series(left), Value::Scalar(right)) => Value::Series(
+            left.into_iter()
+                .map(|(key, left)| (key, scalar(left, right)))
+                .collect(),
+        ),
+        (Value::Scalar(left), Value::Series(right)) => Value::Series(
+            right
+                .into_iter()
+                .map(|(key, right)| (key, scalar(left, right)))
+                .collect(),
+        ),
+        (Value::Series(left), Value::Series(mut right)) => {
+            let (keys, (left, right)): (Vec<_>, (Vec<_>, Vec<_>)) = left
+                .into_iter()
+                .filter_map(|(key, left)| {
+                    let right = right.remove(&key)?;
+                    Some((key, (left, right)))
+                })
+                .unzip();
+
+            let (left, right) = (left.as_slice(), right.as_slice());
+
+            let values = match operator {
+                Operator::Add => left.add(right),
+                Operator::Subtract => left.subtract(right),
+                Operator::Multiply => left.multiply(right),
+                Operator::Divide => left.divide(right),
+            };
+
+            Value::Series(keys.into_iter().zip(values).collect())
+        }
+    }
+}
+
+fn apply<K>(function: Function, series: BTreeMap<K, f64>, size: usize) -> BTreeMap<K, f64>
+where
+    K: Ord + Clone,
+{
+    let (keys, values): (Vec<_>, Vec<_>) = series.into_iter().unzip();
+
+    let values = values.as_slice();
+
+    let (skip, values) = match function {
+        Function::SimpleMovingAverage => (
+            size - 1,
+            values
+                .last_x_sum(size)
+                .into_iter()
+                .map(|sum| sum / size as f64)
+                .collect(),
+        ),
+        Function::Sum => (size - 1, values.last_x_sum(size)),
+        Function::NetChange => (size, values.net_change(size)),
+        Function::Median => (
+            size - 1,
+            values
+                .median(size)
+                .into_iter()
+                .map(|value| value.unwrap_or(f64::NAN))
+                .collect(),
+        ),
+        Function::Cumulate => (0, values.cumulate()),
+    };
+
+    keys.into_iter().zip(values).skip(skip).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(expression: &str) -> Option<BTreeMap<usize, f64>> {
+        let series = BTreeMap::from([
+            (
+                "a".to_owned(),
+                BTreeMap::from([(0, 1.0), (1, 2.0), (2, 3.0), (3, 4.0)]),
+            ),
+            (
+                "b_c".to_owned(),
+                BTreeMap::from([(1, 4.0), (2, 0.0), (3, 8.0)]),
+            ),
+        ]);
+
+        Expression::parse(expression)
+            .unwrap()
+            .evaluate(&mut |name| Ok(series.get(name).cloned()))
+            .unwrap()
+    }
+
+    #[test]
+    fn evaluates_over_the_shared_keys() {
+        assert_eq!(
+            evaluate("-a + b_c / 2 * (1 + 1)"),
+            Some(BTreeMap::from([(1, 2.0), (2, -3.0), (3, 4.0)]))
+        );
+        assert_eq!(
+            evaluate("a / b_c"),
+            Some(BTreeMap::from([(1, 0.5), (3, 0.5)]))
+        );
+        assert_eq!(evaluate("a + missing"), None);
+        assert_eq!(evaluate("2 * 3"), None);
+
+        assert_eq!(
+            Expression::parse("sma(a, 2) - b_c / a").unwrap().series(),
+            vec!["a", "b_c"]
+        );
+        assert!(Expression::parse("sma(a)").is_err());
+        assert!(Expression::parse("a +").is_err());
+        assert!(Expression::parse("unknown(a, 2)").is_err());
+    }
+
+    #[test]
+    fn drops_incomplete_windows() {
+        assert_eq!(
+            evaluate("sma(a, 2)"),
+            Some(BTreeMap::from([(1, 1.5), (2, 2.5), (3, 3.5)]))
+        );
+        assert_eq!(
+            evaluate("sum(a, 3)"),
+            Some(BTreeMap::from([(2, 6.0), (3, 9.0)]))
+        );
+        assert_eq!(
+            evaluate("net_change(a, 1)"),
+            Some(BTreeMap::from([(1, 1.0), (2, 1.0), (3, 1.0)]))
+        );
+        assert_eq!(
+            evaluate("median(a, 3)"),
+            Some(BTreeMap::from([(2, 2.0), (3, 3.0)]))
+        );
+        assert_eq!(
+            evaluate("cumulate(a)"),
+            Some(BTreeMap::from([(0, 1.0), (1, 3.0), (2, 6.0), (3, 10.0)]))
+        );
+    }
+}
@@ -0,0 +1,5 @@
+mod config;
+mod expression;
+
+pub use config::*;
+pub use expression::*;
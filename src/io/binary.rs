@@ -1,7 +1,42 @@
-use savefile::{load_file, save_file, Deserialize, Serialize};
+use std::{fs, io::Read};
+
+use savefile::{load_from_mem, save_to_mem, Deserialize, Serialize};
+
+use super::Checksum;
 
 pub struct Binary;
 
+/// Written before the compressed bytes, followed by the id of the `Compression`
+const COMPRESSED_MAGIC: &[u8; 4] = b"SPCB";
+
+const ZSTD_LEVEL: i32 = 3;
+
+/// Compression of the savefile bytes, recorded in the header of the file so that any binary can be
+/// imported whatever it was exported with
+#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd, Ord, Eq)]
+pub enum Compression {
+    None,
+    #[default]
+    Zstd,
+}
+
+impl Compression {
+    fn to_id(self) -> u8 {
+        match self {
+            Self::None => 0,
+            Self::Zstd => 1,
+        }
+    }
+
+    fn from_id(id: u8) -> color_eyre::Result<Self> {
+        match id {
+            0 => Ok(Self::None),
+            1 => Ok(Self::Zstd),
+            _ => Err(color_eyre::eyre::eyre!("Unknown compression id {id}")),
+        }
+    }
+}
+
 // NOTES:
 // bincode 2.0: it was very consistent in terms of timing until it wasn't at around ~800 000 blocks processed with times between 100s and 3000s, might want to try again later
 // savefile: less consistent maybe even slower but good enough for now (as of height ~350 000)
@@ -12,13 +47,93 @@ impl Binary {
     where
         T: Deserialize,
     {
-        Ok(load_file(path, 0)?)
+        let bytes = fs::read(path)?;
+
+        let Some(compressed) = bytes.strip_prefix(COMPRESSED_MAGIC) else {
+            return Ok(load_from_mem(&bytes, 0)?);
+        };
+
+        let (id, compressed) = compressed
+            .split_first()
+            .ok_or(color_eyre::eyre::eyre!("Missing compression id in {path}"))?;
+
+        match Compression::from_id(*id)? {
+            Compression::None => Ok(load_from_mem(compressed, 0)?),
+            Compression::Zstd => {
+                let mut bytes = vec![];
+
+                zstd::Decoder::new(compressed)?.read_to_end(&mut bytes)?;
+
+                Ok(load_from_mem(&bytes, 0)?)
+            }
+        }
     }
 
+    /// Both exports write a `Checksum` next to the file
     pub fn export<T>(path: &str, value: &T) -> color_eyre::Result<()>
     where
         T: Serialize,
     {
-        Ok(save_file(path, 0, value)?)
+        Checksum::write(path, &save_to_mem(0, value)?)
+    }
+
+    pub fn export_compressed<T>(
+        path: &str,
+        value: &T,
+        compression: Compression,
+    ) -> color_eyre::Result<()>
+    where
+        T: Serialize,
+    {
+        let bytes = save_to_mem(0, value)?;
+
+        let bytes = match compression {
+            Compression::None => bytes,
+            Compression::Zstd => zstd::encode_all(bytes.as_slice(), ZSTD_LEVEL)?,
+        };
+
+        let mut file = Vec::with_capacity(COMPRESSED_MAGIC.len() + 1 + bytes.len());
+
+        file.extend_from_slice(COMPRESSED_MAGIC);
+        file.push(compression.to_id());
+        file.extend(bytes);
+
+        Checksum::write(path, &file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn imports_whatever_the_compression() {
+        let folder =
+            std::env::temp_dir().join(format!("parser/{}/binary_compression", std::process::id()));
+
+        let _ = fs::remove_dir_all(&folder);
+        fs::create_dir_all(&folder).unwrap();
+
+        let values = (0..10_000).map(|i| (i / 100) as f32).collect::<Vec<_>>();
+
+        let raw = folder.join("raw.bin");
+        let raw = raw.to_str().unwrap();
+        Binary::export(raw, &values).unwrap();
+
+        let zstd = folder.join("zstd.bin");
+        let zstd = zstd.to_str().unwrap();
+        Binary::export_compressed(zstd, &values, Compression::Zstd).unwrap();
+
+        let none = folder.join("none.bin");
+        let none = none.to_str().unwrap();
+        Binary::export_compressed(none, &values, Compression::None).unwrap();
+
+        assert!(fs::metadata(zstd).unwrap().len() < fs::metadata(raw).unwrap().len() / 10);
+
+        assert_eq!(Binary::import::<Vec<f32>>(raw).unwrap(), values);
+        assert_eq!(Binary::import::<Vec<f32>>(zstd).unwrap(), values);
+        assert_eq!(Binary::import::<Vec<f32>>(none).unwrap(), values);
+
+        fs::remove_dir_all(folder).unwrap();
     }
 }
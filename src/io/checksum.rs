@@ -0,0 +1,131 @@
+use std::{fs, path::Path};
+
+use serde::Serialize;
+
+pub struct Checksum;
+
+/// State of a file compared to the checksum written next to it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Integrity {
+    Valid,
+    /// Missing, truncated or modified since its checksum was written
+    Damaged,
+    /// Written before checksums were, only a successful import can tell
+    Unverified,
+}
+
+impl Integrity {
+    /// Falls back on `import` for the files without a checksum
+    pub fn is_damaged<T>(self, import: impl FnOnce() -> color_eyre::Result<T>) -> bool {
+        match self {
+            Self::Valid => false,
+            Self::Damaged => true,
+            Self::Unverified => import().is_err(),
+        }
+    }
+}
+
+impl Checksum {
+    pub const EXTENSION: &'static str = "crc32";
+
+    /// Of the files being written, renamed once complete
+    pub const TEMP_EXTENSION: &'static str = "tmp";
+
+    pub fn path(path: &str) -> String {
+        format!("{path}.{}", Self::EXTENSION)
+    }
+
+    /// Writes the bytes and their crc32 in a sidecar file which doesn't have the extension of any serialization
+    ///
+    /// Both are written to temporary files first so an interrupted write never leaves a truncated
+    /// file behind, at worst the file and its checksum don't match and it's reported as damaged
+    pub fn write(path: &str, bytes: &[u8]) -> color_eyre::Result<()> {
+        let checksum_path = Self::path(path);
+
+        let temp_path = Self::temp_path(path);
+        let temp_checksum_path = Self::temp_path(&checksum_path);
+
+        fs::write(&temp_path, bytes)?;
+        fs::write(
+            &temp_checksum_path,
+            format!("{:08x}", crc32fast::hash(bytes)),
+        )?;
+
+        fs::rename(temp_path, path)?;
+
+        Ok(fs::rename(temp_checksum_path, checksum_path)?)
+    }
+
+    fn temp_path(path: &str) -> String {
+        format!("{path}.{}", Self::TEMP_EXTENSION)
+    }
+
+    pub fn verify(path: &str) -> Integrity {
+        let Ok(checksum) = fs::read_to_string(Self::path(path)) else {
+            return Integrity::Unverified;
+        };
+
+        let Ok(bytes) = fs::read(path) else {
+            return Integrity::Damaged;
+        };
+
+        if u32::from_str_radix(checksum.trim(), 16).ok() == Some(crc32fast::hash(&bytes)) {
+            Integrity::Valid
+        } else {
+            Integrity::Damaged
+        }
+    }
+
+    /// Fails on files whose checksum doesn't match, before they're imported
+    pub fn check(path: &str) -> color_eyre::Result<()> {
+        if Self::verify(path) == Integrity::Damaged {
+            return Err(color_eyre::eyre::eyre!("Checksum of {path} doesn't match"));
+        }
+
+        Ok(())
+    }
+
+    /// Moves the file along with its checksum if it has one
+    pub fn rename(from: &str, to: &str) -> color_eyre::Result<()> {
+        fs::rename(from, to)?;
+
+        let from = Self::path(from);
+
+        if Path::new(&from).exists() {
+            fs::rename(from, Self::path(to))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_modified_files() {
+        let folder = std::env::temp_dir().join(format!("parser/{}/checksum", std::process::id()));
+
+        fs::create_dir_all(&folder).unwrap();
+
+        let path = folder.join("chunk.bin");
+        let path = path.to_str().unwrap();
+
+        Checksum::write(path, &[1, 2, 3, 4]).unwrap();
+        assert_eq!(Checksum::verify(path), Integrity::Valid);
+        assert!(!Path::new(&Checksum::temp_path(path)).exists());
+
+        fs::write(path, [1, 2, 7, 4]).unwrap();
+        assert_eq!(Checksum::verify(path), Integrity::Damaged);
+
+        fs::write(path, [1, 2]).unwrap();
+        assert_eq!(Checksum::verify(path), Integrity::Damaged);
+
+        fs::remove_file(Checksum::path(path)).unwrap();
+        assert_eq!(Checksum::verify(path), Integrity::Unverified);
+
+        fs::remove_dir_all(folder).unwrap();
+    }
+}
@@ -0,0 +1,42 @@
+use std::{fs::File, sync::Arc};
+
+use arrow::{
+    array::ArrayRef,
+    datatypes::{Field, Schema},
+    ipc::writer::FileWriter,
+    record_batch::RecordBatch,
+};
+use parquet::arrow::ArrowWriter;
+
+pub struct Columnar;
+
+impl Columnar {
+    /// Writes the columns as a single table in both `{path}.parquet` and `{path}.arrow`
+    pub fn export(path: &str, columns: Vec<(String, ArrayRef)>) -> color_eyre::Result<()> {
+        let schema = Arc::new(Schema::new(
+            columns
+                .iter()
+                .map(|(name, array)| Field::new(name, array.data_type().clone(), true))
+                .collect::<Vec<_>>(),
+        ));
+
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            columns.into_iter().map(|(_, array)| array).collect(),
+        )?;
+
+        let mut writer = ArrowWriter::try_new(
+            File::create(format!("{path}.parquet"))?,
+            schema.clone(),
+            None,
+        )?;
+        writer.write(&batch)?;
+        writer.close()?;
+
+        let mut writer = FileWriter::try_new(File::create(format!("{path}.arrow"))?, &schema)?;
+        writer.write(&batch)?;
+        writer.finish()?;
+
+        Ok(())
+    }
+}
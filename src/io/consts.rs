@@ -1,2 +1,3 @@
 pub const IMPORTS_FOLDER_PATH: &str = "./imports";
 pub const OUTPUTS_FOLDER_PATH: &str = "./target/outputs";
+pub const PRICE_FOLDER_PATH: &str = "./price";
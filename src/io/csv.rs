@@ -0,0 +1,78 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+use itertools::Itertools;
+use serde::Serialize;
+
+pub struct Csv;
+
+impl Csv {
+    pub fn export(
+        path: &str,
+        header: &[String],
+        rows: impl Iterator<Item = Vec<String>>,
+    ) -> color_eyre::Result<()> {
+        let file = File::create(path)?;
+
+        let mut writer = BufWriter::new(file);
+
+        Self::write_row(&mut writer, header)?;
+
+        rows.into_iter()
+            .try_for_each(|row| Self::write_row(&mut writer, &row))?;
+
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Shortest representation that parses back to the same value, empty for missing numbers
+    pub fn to_cell<T>(value: &T) -> String
+    where
+        T: Serialize,
+    {
+        let json = serde_json::to_string(value).unwrap_or_default();
+
+        if json == "null" {
+            String::new()
+        } else if json.starts_with('"') {
+            serde_json::from_str(&json).unwrap_or_default()
+        } else {
+            json
+        }
+    }
+
+    fn write_row(writer: &mut impl Write, cells: &[String]) -> color_eyre::Result<()> {
+        writeln!(
+            writer,
+            "{}",
+            cells.iter().map(|cell| Self::escape(cell)).join(",")
+        )?;
+
+        Ok(())
+    }
+
+    fn escape(cell: &str) -> String {
+        if cell.contains([',', '"', '\n']) {
+            format!("\"{}\"", cell.replace('"', "\"\""))
+        } else {
+            cell.to_owned()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_values_at_full_precision() {
+        assert_eq!(Csv::to_cell(&0.1_f32), "0.1");
+        assert_eq!(Csv::to_cell(&67432.15_f32), "67432.15");
+        assert_eq!(Csv::to_cell(&f32::NAN), "");
+        assert_eq!(Csv::to_cell(&u64::MAX), "18446744073709551615");
+        assert_eq!(Csv::escape("a,\"b\""), "\"a,\"\"b\"\"\"");
+    }
+}
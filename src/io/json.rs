@@ -5,6 +5,8 @@ use std::{
 
 use serde::{de::DeserializeOwned, Serialize};
 
+use super::Checksum;
+
 pub struct Json;
 
 impl Json {
@@ -34,4 +36,12 @@ impl Json {
 
         Ok(())
     }
+
+    /// Same as `export` but with a `Checksum` next to the file
+    pub fn export_with_checksum<T>(path: &str, value: &T) -> color_eyre::Result<()>
+    where
+        T: Serialize,
+    {
+        Checksum::write(path, &serde_json::to_vec_pretty(value)?)
+    }
 }
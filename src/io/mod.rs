@@ -1,11 +1,19 @@
 mod binary;
+mod checksum;
+#[cfg(feature = "columnar")]
+mod columnar;
 mod consts;
+mod csv;
 mod json;
 mod path;
 mod serialization;
 
 pub use binary::*;
+pub use checksum::*;
+#[cfg(feature = "columnar")]
+pub use columnar::*;
 pub use consts::*;
+pub use csv::*;
 pub use json::*;
 pub use path::*;
 pub use serialization::*;
@@ -2,25 +2,26 @@ use std::fmt::Debug;
 
 use serde::{de::DeserializeOwned, Serialize};
 
-use crate::io::{Binary, Json};
+use crate::io::{Binary, Compression, Json};
 
 #[derive(PartialEq, PartialOrd, Ord, Eq)]
 pub enum Serialization {
-    Binary,
+    /// Imports every compression, exports with the given one
+    Binary(Compression),
     Json,
 }
 
 impl Serialization {
     pub fn to_extension(&self) -> &str {
         match self {
-            Self::Binary => "bin",
+            Self::Binary(_) => "bin",
             Self::Json => "json",
         }
     }
 
     pub fn from_extension(extension: &str) -> Self {
         match extension {
-            "bin" => Self::Binary,
+            "bin" => Self::Binary(Compression::default()),
             "json" => Self::Json,
             _ => panic!("Extension \"{extension}\" isn't supported"),
         }
@@ -35,7 +36,7 @@ impl Serialization {
         T: savefile::Deserialize + DeserializeOwned + Debug,
     {
         match self {
-            Serialization::Binary => Binary::import(path),
+            Serialization::Binary(_) => Binary::import(path),
             Serialization::Json => Json::import(path),
         }
     }
@@ -45,8 +46,10 @@ impl Serialization {
         T: savefile::Serialize + Serialize,
     {
         match self {
-            Serialization::Binary => Binary::export(path, value),
-            Serialization::Json => Json::export(path, value),
+            Serialization::Binary(compression) => {
+                Binary::export_compressed(path, value, *compression)
+            }
+            Serialization::Json => Json::export_with_checksum(path, value),
         }
     }
 }
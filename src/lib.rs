@@ -2,6 +2,7 @@ mod actions;
 mod bitcoin;
 mod databases;
 mod datasets;
+mod derived;
 mod io;
 mod parse;
 mod price;
@@ -9,9 +10,12 @@ mod states;
 mod utils;
 
 pub use crate::{
-    actions::iter_blocks,
+    actions::{export_csv, export_derived, iter_blocks, verify},
     bitcoin::{BitcoinDB, BitcoinDaemon},
-    io::{Binary, Json, Serialization},
+    io::{Binary, Compression, Json, Serialization},
     parse::{DateMap, HeightMap, SerializedDateMap, SerializedHeightMap, HEIGHT_MAP_CHUNK_SIZE},
     utils::timestamp_to_naive_date,
 };
+
+#[cfg(feature = "columnar")]
+pub use crate::actions::export_columnar;
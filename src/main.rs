@@ -1,12 +1,29 @@
 use std::path::Path;
 
-use parser::{iter_blocks, BitcoinDB, BitcoinDaemon};
+use parser::{export_csv, export_derived, iter_blocks, verify, BitcoinDB, BitcoinDaemon};
 
 const BITCOIN_DATADIR_RAW_PATH: &str = "/Users/k/Developer/bitcoin";
 
 fn main() -> color_eyre::Result<()> {
     color_eyre::install()?;
 
+    if std::env::args().any(|arg| arg == "--csv") {
+        return export_csv();
+    }
+
+    if std::env::args().any(|arg| arg == "--derived") {
+        return export_derived();
+    }
+
+    if std::env::args().any(|arg| arg == "--verify") {
+        return verify();
+    }
+
+    #[cfg(feature = "columnar")]
+    if std::env::args().any(|arg| arg == "--columnar") {
+        return parser::export_columnar();
+    }
+
     let deamon = BitcoinDaemon::new(BITCOIN_DATADIR_RAW_PATH);
 
     loop {
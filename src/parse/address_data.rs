@@ -1,6 +1,6 @@
 use savefile_derive::Savefile;
 
-use crate::bitcoin::sats_to_btc;
+use crate::utils::{compute_sats_cents, convert_price_to_cents, convert_sats_cents_to_dollars};
 
 use super::{AddressType, EmptyAddressData, LiquidityClassification};
 
@@ -10,7 +10,8 @@ pub struct AddressData {
     pub amount: u64,
     pub sent: u64,
     pub received: u64,
-    pub mean_price_paid: f32,
+    /// In sats times cents, sum of the value of each received output at the price of its block
+    pub realized_cap: u128,
     pub outputs_len: u32,
 }
 
@@ -21,7 +22,7 @@ impl AddressData {
             amount: 0,
             sent: 0,
             received: 0,
-            mean_price_paid: 0.0,
+            realized_cap: 0,
             outputs_len: 0,
         }
     }
@@ -29,53 +30,80 @@ impl AddressData {
     pub fn compute_liquidity_classification(&self) -> LiquidityClassification {
         LiquidityClassification::new(self.sent, self.received)
     }
+
+    pub fn compute_mean_price_paid(realized_cap: u128, amount: u64) -> f64 {
+        if amount == 0 {
+            return 0.0;
+        }
+
+        // Sats times cents divided by sats
+        (realized_cap as f64 / amount as f64) / 100.0
+    }
 }
 
 impl AddressData {
-    pub fn receive(&mut self, sat_amount: u64, price: f32) {
-        let previous_mean_price_paid = self.mean_price_paid;
+    pub fn receive(&mut self, sat_amount: u64, price: f64) {
+        Self::receive_realized_cap(&mut self.realized_cap, sat_amount, price);
 
-        let previous_sat_amount = self.amount;
-        let new_sat_amount = previous_sat_amount + sat_amount;
+        self.amount += sat_amount;
 
-        let btc_amount = sats_to_btc(sat_amount);
-        let priced_btc_value = btc_amount * price;
+        self.received += sat_amount;
 
-        let previous_btc_amount = sats_to_btc(previous_sat_amount);
-        let new_btc_amount = sats_to_btc(new_sat_amount);
+        self.outputs_len += 1;
+    }
 
-        self.mean_price_paid =
-            (previous_mean_price_paid * previous_btc_amount + priced_btc_value) / new_btc_amount;
+    /// Returns the realized profit (or loss if negative) of the spent amount compared to the mean price paid
+    pub fn spend(&mut self, sat_amount: u64, price: f64) -> f64 {
+        let realized_profit_or_loss =
+            Self::spend_realized_cap(&mut self.realized_cap, self.amount, sat_amount, price);
 
-        self.amount = new_sat_amount;
+        self.amount -= sat_amount;
 
-        self.received += sat_amount;
+        self.sent += sat_amount;
 
-        self.outputs_len += 1;
+        self.outputs_len -= 1;
+
+        realized_profit_or_loss
     }
 
-    pub fn spend(&mut self, sat_amount: u64, price: f32) -> f32 {
-        let previous_mean_price_paid = self.mean_price_paid;
+    /// Removes outputs which became unspendable (BIP30) at the mean price paid, without counting them as sent
+    pub fn remove_unspendable(&mut self, sat_amount: u64) {
+        Self::remove_unspendable_realized_cap(&mut self.realized_cap, self.amount, sat_amount);
 
-        let previous_sat_amount = self.amount;
-        let new_sat_amount = previous_sat_amount - sat_amount;
+        self.amount -= sat_amount;
 
-        let btc_value = sats_to_btc(sat_amount);
-        let priced_btc_value = btc_value * price;
+        self.outputs_len -= 1;
+    }
 
-        let previous_btc_amount = sats_to_btc(previous_sat_amount);
-        let new_btc_amount = sats_to_btc(new_sat_amount);
+    pub fn receive_realized_cap(realized_cap: &mut u128, sat_amount: u64, price: f64) {
+        *realized_cap += compute_sats_cents(sat_amount, convert_price_to_cents(price));
+    }
 
-        self.mean_price_paid =
-            ((previous_mean_price_paid * previous_btc_amount) - priced_btc_value) / new_btc_amount;
+    /// Same as `spend` for a realized cap kept aside (in another currency), `amount` being the one
+    /// of the address before spending
+    pub fn spend_realized_cap(
+        realized_cap: &mut u128,
+        amount: u64,
+        sat_amount: u64,
+        price: f64,
+    ) -> f64 {
+        let previous_realized_cap = *realized_cap;
 
-        self.amount = new_sat_amount;
+        let spent_value = compute_sats_cents(sat_amount, convert_price_to_cents(price));
 
-        self.sent += sat_amount;
+        let spent_value_at_mean_price_paid =
+            previous_realized_cap * sat_amount as u128 / amount as u128;
 
-        self.outputs_len -= 1;
+        *realized_cap = previous_realized_cap - spent_value_at_mean_price_paid;
+
+        convert_sats_cents_to_dollars(spent_value)
+            - convert_sats_cents_to_dollars(spent_value_at_mean_price_paid)
+    }
 
-        priced_btc_value - (btc_value * previous_mean_price_paid)
+    /// Same as `remove_unspendable` for a realized cap kept aside, `amount` being the one of the
+    /// address before the removal
+    pub fn remove_unspendable_realized_cap(realized_cap: &mut u128, amount: u64, sat_amount: u64) {
+        *realized_cap -= *realized_cap * sat_amount as u128 / amount as u128;
     }
 
     #[inline(always)]
@@ -89,8 +117,66 @@ impl AddressData {
             amount: 0,
             sent: empty.transfered,
             received: empty.transfered,
-            mean_price_paid: 0.0,
+            realized_cap: 0,
             outputs_len: 0,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn realized_cap_is_exact_after_many_operations() {
+        let mut address_data = AddressData::new(AddressType::P2PKH);
+
+        let prices = [0.05, 13.37, 1_234.56, 67_432.15, 99_999.99];
+
+        (0..10_000).for_each(|i| {
+            address_data.receive(12_345_678 + i, prices[i as usize % prices.len()]);
+        });
+
+        (0..10_000).for_each(|i| {
+            address_data.spend(12_345_678 + i, prices[i as usize % prices.len()]);
+        });
+
+        assert!(address_data.is_empty());
+        assert_eq!(address_data.realized_cap, 0);
+    }
+
+    #[test]
+    fn spending_keeps_the_mean_price_paid() {
+        let mut address_data = AddressData::new(AddressType::P2WPKH);
+
+        address_data.receive(100_000_000, 10_000.0);
+        address_data.receive(100_000_000, 20_000.0);
+
+        let realized_profit = address_data.spend(100_000_000, 30_000.0);
+
+        assert_eq!(realized_profit, 15_000.0);
+        assert_eq!(address_data.realized_cap, 100_000_000 * 1_500_000);
+        assert_eq!(
+            AddressData::compute_mean_price_paid(address_data.realized_cap, address_data.amount),
+            15_000.0
+        );
+    }
+
+    #[test]
+    fn unspendable_outputs_are_removed_without_being_sent() {
+        let mut address_data = AddressData::new(AddressType::P2PK);
+
+        address_data.receive(5_000_000_000, 0.0);
+        address_data.receive(5_000_000_000, 1.0);
+
+        address_data.remove_unspendable(5_000_000_000);
+
+        assert_eq!(address_data.amount, 5_000_000_000);
+        assert_eq!(address_data.sent, 0);
+        assert_eq!(address_data.outputs_len, 1);
+        assert_eq!(
+            AddressData::compute_mean_price_paid(address_data.realized_cap, address_data.amount),
+            0.5
+        );
+    }
+}
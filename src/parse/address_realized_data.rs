@@ -1,14 +1,25 @@
+use std::collections::BTreeMap;
+
 use super::AddressData;
 
 #[derive(Debug)]
 pub struct AddressRealizedData {
     pub received: u64,
     pub sent: u64,
-    pub profit: f32,
-    pub loss: f32,
+    pub profit: f64,
+    pub loss: f64,
     pub utxos_created: u32,
     pub utxos_destroyed: u32,
     pub initial_address_data: AddressData,
+    /// Same as `profit`, `loss` and the initial realized cap but in each currency, by code
+    pub currencies: BTreeMap<String, AddressCurrencyRealizedData>,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AddressCurrencyRealizedData {
+    pub profit: f64,
+    pub loss: f64,
+    pub initial_realized_cap: u128,
 }
 
 impl AddressRealizedData {
@@ -21,22 +32,60 @@ impl AddressRealizedData {
             utxos_created: 0,
             utxos_destroyed: 0,
             initial_address_data: *initial_address_data,
+            currencies: BTreeMap::default(),
         }
     }
 
+    /// Initial realized cap in each currency, by code
+    pub fn with_initial_realized_caps(mut self, realized_caps: BTreeMap<String, u128>) -> Self {
+        self.currencies = realized_caps
+            .into_iter()
+            .map(|(code, initial_realized_cap)| {
+                (
+                    code,
+                    AddressCurrencyRealizedData {
+                        initial_realized_cap,
+                        ..Default::default()
+                    },
+                )
+            })
+            .collect();
+
+        self
+    }
+
     pub fn receive(&mut self, sats: u64) {
         self.received += sats;
         self.utxos_created += 1;
     }
 
-    pub fn send(&mut self, sats: u64, realized_profit_or_loss: f32) {
+    pub fn send(&mut self, sats: u64, realized_profit_or_loss: f64) {
         self.sent += sats;
         self.utxos_destroyed += 1;
 
+        Self::realize(&mut self.profit, &mut self.loss, realized_profit_or_loss);
+    }
+
+    /// Realized profits and losses in each currency of a `send`, by code
+    pub fn send_in_currencies(&mut self, realized_profits_or_losses: Vec<(String, f64)>) {
+        realized_profits_or_losses
+            .into_iter()
+            .for_each(|(code, realized_profit_or_loss)| {
+                let currency = self.currencies.get_mut(&code).unwrap();
+
+                Self::realize(
+                    &mut currency.profit,
+                    &mut currency.loss,
+                    realized_profit_or_loss,
+                );
+            });
+    }
+
+    fn realize(profit: &mut f64, loss: &mut f64, realized_profit_or_loss: f64) {
         if realized_profit_or_loss >= 0.0 {
-            self.profit += realized_profit_or_loss;
+            *profit += realized_profit_or_loss;
         } else {
-            self.loss += realized_profit_or_loss.abs();
+            *loss += realized_profit_or_loss.abs();
         }
     }
 }
@@ -1,18 +1,14 @@
+use super::MapMetadata;
+
 pub trait AnyMap {
     fn path(&self) -> &str;
     fn path_last(&self) -> &Option<String>;
 
     fn t_name(&self) -> &str;
 
-    fn exported_path_with_t_name(&self) -> Vec<(&str, &str)> {
-        let t_name = self.t_name();
+    fn version(&self) -> u32;
 
-        if let Some(path_last) = self.path_last() {
-            vec![(self.path(), t_name), (path_last, t_name)]
-        } else {
-            vec![(self.path(), t_name)]
-        }
-    }
+    fn metadata(&self) -> &MapMetadata;
 
     fn reset(&mut self) -> color_eyre::Result<()>;
 
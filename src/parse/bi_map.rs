@@ -3,7 +3,9 @@ use std::{fmt::Debug, iter::Sum, ops::RangeInclusive};
 use chrono::NaiveDate;
 use serde::{de::DeserializeOwned, Serialize};
 
-use super::{AnyDateMap, AnyHeightMap, AnyMap, DateMap, HeightMap};
+use crate::io::Compression;
+
+use super::{Aggregation, AnyDateMap, AnyHeightMap, AnyMap, DateMap, HeightMap, MapValue, Unit};
 
 pub struct BiMap<T>
 where
@@ -20,6 +22,8 @@ where
 {
     pub height: HeightMap<T>,
     pub date: DateMap<T>,
+    /// Of the resampled dates, see `DateMap::export_resampled`
+    aggregation: Aggregation,
 }
 
 impl<T> BiMap<T>
@@ -39,6 +43,15 @@ where
         Self {
             height: HeightMap::_new_bin(version, path, 1, true),
             date: DateMap::_new_bin(version, path, 1, false),
+            aggregation: Aggregation::default(),
+        }
+    }
+
+    /// For flows, resampled dates are the sum of the days instead of the last one
+    pub fn new_bin_sum(version: u32, path: &str) -> Self {
+        Self {
+            aggregation: Aggregation::Sum,
+            ..Self::new_bin(version, path)
         }
     }
 
@@ -46,6 +59,26 @@ where
         Self {
             height: HeightMap::_new_bin(version, path, height_chunks_in_memory, true),
             date: DateMap::_new_bin(version, path, height_chunks_in_memory, false),
+            aggregation: Aggregation::default(),
+        }
+    }
+
+    /// See `MapMetadata`, shared by both maps
+    pub fn with_metadata(self, unit: Unit, description: &'static str) -> Self {
+        Self {
+            height: self.height.with_metadata(unit, description),
+            date: self.date.with_metadata(unit, description),
+            ..self
+        }
+    }
+
+    /// See `HeightMap::with_compression`, shared by both maps
+    #[allow(unused)]
+    pub fn with_compression(self, compression: Compression) -> Self {
+        Self {
+            height: self.height.with_compression(compression),
+            date: self.date.with_compression(compression),
+            ..self
         }
     }
 
@@ -76,6 +109,10 @@ pub trait AnyBiMap {
     fn get_height(&self) -> &(dyn AnyHeightMap + Send + Sync);
 
     fn get_date(&self) -> &(dyn AnyDateMap + Send + Sync);
+
+    fn export_resampled(&self) -> color_eyre::Result<()>;
+
+    fn aggregation(&self) -> Aggregation;
 }
 
 impl<T> AnyBiMap for BiMap<T>
@@ -90,8 +127,10 @@ where
         + savefile::Serialize
         + savefile::Deserialize
         + savefile::ReprC
+        + PartialOrd
         + Send
-        + Sync,
+        + Sync
+        + MapValue,
 {
     #[inline(always)]
     fn are_date_and_height_safe(&self, date: NaiveDate, height: usize) -> bool {
@@ -113,4 +152,12 @@ where
     fn get_date(&self) -> &(dyn AnyDateMap + Send + Sync) {
         &self.date
     }
+
+    fn export_resampled(&self) -> color_eyre::Result<()> {
+        self.date.export_resampled(self.aggregation)
+    }
+
+    fn aggregation(&self) -> Aggregation {
+        self.aggregation
+    }
 }
@@ -0,0 +1,185 @@
+use std::collections::BTreeMap;
+
+use bitcoin::TxOut;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoinJoinKind {
+    Whirlpool,
+    Wasabi,
+    JoinMarket,
+    Generic,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CoinJoin {
+    pub kind: CoinJoinKind,
+    /// Value shared by the equal outputs
+    pub denomination: u64,
+    /// Number of equal outputs
+    pub anonset: usize,
+}
+
+impl CoinJoin {
+    pub const MIN_ANONSET: usize = 3;
+
+    /// Whirlpool pools, mixes are always 5 inputs to 5 outputs of the pool denomination
+    const WHIRLPOOL_DENOMINATIONS: [u64; 4] = [100_000, 1_000_000, 5_000_000, 50_000_000];
+    const WHIRLPOOL_SIZE: usize = 5;
+
+    /// Wasabi 1 mixed around 0.1 BTC and Wasabi 2 runs big rounds with many inputs
+    const WASABI_1_DENOMINATION_RANGE: (u64, u64) = (9_000_000, 11_000_000);
+    const WASABI_MIN_ANONSET: usize = 10;
+    const WASABI_2_MIN_INPUTS: usize = 50;
+
+    pub fn detect(inputs: usize, outputs: &[TxOut]) -> Option<Self> {
+        let values = outputs
+            .iter()
+            .filter(|txout| !txout.script_pubkey.is_op_return())
+            .map(|txout| txout.value.to_sat())
+            .filter(|value| *value != 0)
+            .collect::<Vec<_>>();
+
+        let (denomination, anonset) = Self::most_common_value(&values)?;
+
+        if anonset < Self::MIN_ANONSET || inputs < anonset {
+            return None;
+        }
+
+        let kind = if inputs == Self::WHIRLPOOL_SIZE
+            && values.len() == Self::WHIRLPOOL_SIZE
+            && anonset == Self::WHIRLPOOL_SIZE
+            && Self::WHIRLPOOL_DENOMINATIONS.contains(&denomination)
+        {
+            CoinJoinKind::Whirlpool
+        } else if anonset >= Self::WASABI_MIN_ANONSET
+            && ((Self::WASABI_1_DENOMINATION_RANGE.0..=Self::WASABI_1_DENOMINATION_RANGE.1)
+                .contains(&denomination)
+                || inputs >= Self::WASABI_2_MIN_INPUTS)
+        {
+            CoinJoinKind::Wasabi
+        } else if values.len() <= 2 * anonset && anonset < Self::WASABI_MIN_ANONSET {
+            // Every taker/maker gets one equal output plus at most one change output
+            CoinJoinKind::JoinMarket
+        } else {
+            CoinJoinKind::Generic
+        };
+
+        Some(Self {
+            kind,
+            denomination,
+            anonset,
+        })
+    }
+
+    /// Biggest value on ties, a bigger denomination is more likely to be the mixed amount
+    fn most_common_value(values: &[u64]) -> Option<(u64, usize)> {
+        let mut value_to_count: BTreeMap<u64, usize> = BTreeMap::new();
+
+        values.iter().for_each(|value| {
+            *value_to_count.entry(*value).or_default() += 1;
+        });
+
+        value_to_count
+            .into_iter()
+            .max_by(|(value_a, count_a), (value_b, count_b)| {
+                count_a.cmp(count_b).then(value_a.cmp(value_b))
+            })
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct CoinJoinsData {
+    pub count: usize,
+    pub whirlpool_count: usize,
+    pub wasabi_count: usize,
+    pub joinmarket_count: usize,
+    pub volume: u64,
+    /// Sum of the anonsets
+    pub equal_outputs: usize,
+    pub spent_count: usize,
+    pub spent_volume: u64,
+}
+
+impl CoinJoinsData {
+    pub fn push(&mut self, coinjoin: &CoinJoin, volume: u64) {
+        self.count += 1;
+        self.volume += volume;
+        self.equal_outputs += coinjoin.anonset;
+
+        match coinjoin.kind {
+            CoinJoinKind::Whirlpool => self.whirlpool_count += 1,
+            CoinJoinKind::Wasabi => self.wasabi_count += 1,
+            CoinJoinKind::JoinMarket => self.joinmarket_count += 1,
+            CoinJoinKind::Generic => {}
+        }
+    }
+
+    pub fn spend(&mut self, sats: u64) {
+        self.spent_count += 1;
+        self.spent_volume += sats;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::{script::PushBytesBuf, Amount, ScriptBuf};
+
+    use super::*;
+
+    fn outputs(values: &[u64]) -> Vec<TxOut> {
+        values
+            .iter()
+            .map(|value| TxOut {
+                value: Amount::from_sat(*value),
+                script_pubkey: ScriptBuf::from_bytes(vec![0; 22]),
+            })
+            .collect()
+    }
+
+    fn kind(inputs: usize, values: &[u64]) -> Option<CoinJoinKind> {
+        CoinJoin::detect(inputs, &outputs(values)).map(|coinjoin| coinjoin.kind)
+    }
+
+    #[test]
+    fn detects_each_pattern() {
+        assert_eq!(kind(5, &[1_000_000; 5]), Some(CoinJoinKind::Whirlpool));
+        // Not a pool denomination
+        assert_eq!(kind(5, &[1_200_000; 5]), Some(CoinJoinKind::JoinMarket));
+
+        assert_eq!(kind(12, &[10_000_000; 12]), Some(CoinJoinKind::Wasabi));
+        assert_eq!(
+            kind(60, &[[5_000; 10].as_slice(), &[1_234; 40]].concat()),
+            Some(CoinJoinKind::Wasabi)
+        );
+
+        // Equal outputs plus one change per participant
+        assert_eq!(
+            kind(4, &[200_000, 200_000, 200_000, 31_000, 52_000, 7_000]),
+            Some(CoinJoinKind::JoinMarket)
+        );
+
+        assert_eq!(
+            kind(4, &[[300; 3].as_slice(), &[1, 2, 4, 5, 6, 7, 8]].concat()),
+            Some(CoinJoinKind::Generic)
+        );
+
+        // Too few equal outputs, or fewer inputs than equal outputs
+        assert_eq!(kind(2, &[50_000, 50_000, 10_000]), None);
+        assert_eq!(kind(2, &[50_000; 3]), None);
+    }
+
+    #[test]
+    fn counts_the_equal_outputs_as_anonset() {
+        let mut outputs = outputs(&[100, 100, 100, 200, 200, 200, 0]);
+        outputs.push(TxOut {
+            value: Amount::from_sat(100),
+            script_pubkey: ScriptBuf::new_op_return(PushBytesBuf::new()),
+        });
+
+        let coinjoin = CoinJoin::detect(6, &outputs).unwrap();
+
+        // Ties go to the biggest value, zero values and OP_RETURNs are ignored
+        assert_eq!(coinjoin.denomination, 200);
+        assert_eq!(coinjoin.anonset, 3);
+    }
+}
@@ -2,6 +2,7 @@ use std::{
     collections::{BTreeMap, BTreeSet},
     fmt::Debug,
     fs,
+    panic::{self, AssertUnwindSafe},
 };
 
 use derive_deref::{Deref, DerefMut};
@@ -15,7 +16,7 @@ use sanakirja::{
     direct_repr, Commit, Env, Error, MutTxn, RootDb, Storable, UnsizedStorable,
 };
 
-use crate::io::OUTPUTS_FOLDER_PATH;
+use crate::io::{Checksum, OUTPUTS_FOLDER_PATH};
 
 #[allow(unused)]
 pub type SizedDatabase<Key, Value> = Database<Key, Key, Value, page::Page<Key, Value>>;
@@ -150,6 +151,50 @@ where
         self.txn.commit()
     }
 
+    /// Walks the whole tree of a copy of an exported database without creating it if missing.
+    ///
+    /// sanakirja has no read-only environment, opening a file resizes it, so the database itself is
+    /// only read to be copied and a file whose length isn't a multiple of `PAGE_SIZE` is damaged.
+    /// Pages aren't checksummed without the `crc32` feature of sanakirja so only a truncated file or a
+    /// broken tree is caught, and `catch_unwind` only catches panics: a page pointing outside of the
+    /// file can still crash the process.
+    pub fn is_damaged(folder: &str, file: &str) -> bool {
+        let path = format!("{}/{file}", databases_folder_path(folder));
+
+        let Ok(metadata) = fs::metadata(&path) else {
+            return false;
+        };
+
+        if metadata.len() == 0 || metadata.len() % PAGE_SIZE != 0 {
+            return true;
+        }
+
+        // Filtered out of the names of the databases if left behind
+        let copy_path = format!("{path}.{}", Checksum::TEMP_EXTENSION);
+
+        if fs::copy(&path, &copy_path).is_err() {
+            let _ = fs::remove_file(&copy_path);
+            return true;
+        }
+
+        let is_damaged = panic::catch_unwind(AssertUnwindSafe(|| -> color_eyre::Result<()> {
+            let env = unsafe { Env::new_nolock(&copy_path, 0, 1)? };
+
+            let txn = Env::txn_begin(&env)?;
+
+            if let Some(db) = txn.root_db::<KeyDB, Value, Page>(ROOT_DB) {
+                btree::iter(&txn, &db, None)?.try_for_each(|entry| entry.map(|_| ()))?;
+            }
+
+            Ok(())
+        }))
+        .map_or(true, |result| result.is_err());
+
+        let _ = fs::remove_file(&copy_path);
+
+        is_damaged
+    }
+
     fn db_get(&self, key: &KeyTree) -> Option<&Value> {
         let k = (self.key_tree_to_key_db)(key);
 
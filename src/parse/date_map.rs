@@ -1,35 +1,76 @@
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
     fmt::Debug,
     fs,
     iter::Sum,
     mem,
-    ops::{Add, AddAssign, Div, Mul, Sub, SubAssign},
+    num::NonZeroUsize,
+    ops::{Add, AddAssign, Bound, Div, Mul, RangeBounds, RangeInclusive, Sub, SubAssign},
     path::{Path, PathBuf},
     str::FromStr,
+    sync::Mutex,
 };
 
 use chrono::{Datelike, Days, NaiveDate};
 use itertools::Itertools;
+use lru::LruCache;
 use ordered_float::{FloatCore, OrderedFloat};
 use savefile_derive::Savefile;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use crate::{
-    io::{format_path, Serialization},
-    utils::ToF32,
+    io::{format_path, Checksum, Compression, Serialization},
+    utils::{RollingPercentile, ToF32},
 };
 
-use super::{AnyMap, WNaiveDate};
+use super::{
+    date_index, Aggregation, AnyMap, AnyMapColumn, Granularity, MapColumn, MapMetadata, MapValue,
+    MigratedChunk, Migration, Unit, WNaiveDate, MIGRATIONS,
+};
 
 const NUMBER_OF_UNSAFE_DATES: usize = 2;
 
+/// Enough for a year of dates whatever the day
+const COLD_CHUNKS_IN_MEMORY: usize = 2;
+
 #[derive(Debug, Savefile, Serialize, Deserialize)]
 pub struct SerializedDateMap<T> {
     version: u32,
     map: BTreeMap<WNaiveDate, T>,
 }
 
+impl<T> MigratedChunk for SerializedDateMap<T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    type Key = WNaiveDate;
+    type Value = T;
+
+    fn version(&self) -> u32 {
+        self.version
+    }
+
+    fn set_version(&mut self, version: u32) {
+        self.version = version;
+    }
+
+    fn values_mut(&mut self) -> Box<dyn Iterator<Item = &mut T> + '_> {
+        Box::new(self.map.values_mut())
+    }
+
+    fn get(&self, key: &WNaiveDate) -> Option<&T> {
+        self.map.get(key)
+    }
+
+    fn keys(&self) -> Vec<WNaiveDate> {
+        self.map.keys().cloned().collect()
+    }
+
+    fn from_entries(version: u32, map: BTreeMap<WNaiveDate, T>) -> Self {
+        Self { version, map }
+    }
+}
+
 pub struct DateMap<T> {
     version: u32,
 
@@ -45,6 +86,11 @@ pub struct DateMap<T> {
 
     imported: BTreeMap<usize, SerializedDateMap<T>>,
     to_insert: BTreeMap<usize, BTreeMap<WNaiveDate, T>>,
+
+    metadata: MapMetadata,
+
+    /// Chunks older than the ones in `imported`, loaded by `get` when needed, `None` if missing or outdated
+    cold: Mutex<LruCache<usize, Option<SerializedDateMap<T>>>>,
 }
 
 impl<T> DateMap<T>
@@ -62,7 +108,13 @@ where
 {
     #[allow(unused)]
     pub fn new_bin(version: u32, path: &str) -> Self {
-        Self::new(version, path, Serialization::Binary, 1, true)
+        Self::new(
+            version,
+            path,
+            Serialization::Binary(Compression::default()),
+            1,
+            true,
+        )
     }
 
     #[allow(unused)]
@@ -70,7 +122,7 @@ where
         Self::new(
             version,
             path,
-            Serialization::Binary,
+            Serialization::Binary(Compression::default()),
             chunks_in_memory,
             export_last,
         )
@@ -92,6 +144,22 @@ where
         )
     }
 
+    /// See `MapMetadata`
+    pub fn with_metadata(mut self, unit: Unit, description: &'static str) -> Self {
+        self.metadata = MapMetadata::new(unit, description);
+        self
+    }
+
+    /// For binary maps whose values don't compress well, chunks are imported whatever they were
+    /// exported with
+    #[allow(unused)]
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        if let Serialization::Binary(_) = self.serialization {
+            self.serialization = Serialization::Binary(compression);
+        }
+        self
+    }
+
     fn new(
         version: u32,
         path: &str,
@@ -109,6 +177,16 @@ where
 
         fs::create_dir_all(&path_all).unwrap();
 
+        if let Err(report) = Migration::run::<SerializedDateMap<T>>(
+            MIGRATIONS,
+            &path,
+            "date",
+            version,
+            &serialization,
+        ) {
+            println!("Couldn't migrate {path}, skipping it: {report}");
+        }
+
         let path_last = {
             if export_last {
                 Some(serialization.append_extension(&format!("{path}/last")))
@@ -132,18 +210,26 @@ where
 
             to_insert: BTreeMap::default(),
             imported: BTreeMap::default(),
+
+            metadata: MapMetadata::default(),
+
+            cold: Mutex::new(LruCache::new(
+                NonZeroUsize::new(COLD_CHUNKS_IN_MEMORY).unwrap(),
+            )),
         };
 
         s.read_dir()
             .into_iter()
             .rev()
             .take(chunks_in_memory)
-            .for_each(|(chunk_start, path)| {
-                if let Ok(serialized) = s.import(&path) {
+            .for_each(|(chunk_start, path)| match s.import(&path) {
+                Ok(serialized) => {
                     if serialized.version == s.version {
                         s.imported.insert(chunk_start, serialized);
                     }
                 }
+                // Damaged chunks are rebuilt by parsing their blocks again
+                Err(report) => println!("Couldn't import {path:?}, skipping it: {report}"),
             });
 
         s.initial_last_date = s
@@ -188,11 +274,218 @@ where
             .or_else(|| {
                 self.imported
                     .get(&year)
-                    .and_then(|serialized| serialized.map.get(date))
-                    .cloned()
+                    .map(|serialized| serialized.map.get(date).cloned())
+                    .unwrap_or_else(|| self.get_cold(year, date))
+            })
+    }
+
+    fn get_cold(&self, year: usize, date: &WNaiveDate) -> Option<T> {
+        self.with_cold_chunk(year, |serialized| serialized.map.get(date).cloned())
+    }
+
+    fn with_cold_chunk<R>(
+        &self,
+        year: usize,
+        f: impl FnOnce(&SerializedDateMap<T>) -> Option<R>,
+    ) -> Option<R> {
+        let mut cold = self.cold.lock().unwrap();
+
+        let serialized = cold.get_or_insert(year, || {
+            let path = self
+                .serialization
+                .append_extension(&format!("{}/{}", self.path_all, year));
+
+            self.import(Path::new(&path))
+                .ok()
+                .filter(|serialized| serialized.version == self.version)
+        });
+
+        f(serialized.as_ref()?)
+    }
+
+    /// Sorted values whose dates are in `range`, wherever they are (memory, disk or not yet exported)
+    pub fn range(
+        &self,
+        range: impl RangeBounds<NaiveDate>,
+    ) -> impl Iterator<Item = (NaiveDate, T)> + '_ {
+        let range = (range.start_bound().cloned(), range.end_bound().cloned());
+
+        let first_year = match range.0 {
+            Bound::Included(date) | Bound::Excluded(date) => date.year() as usize,
+            Bound::Unbounded => 0,
+        };
+
+        let last_year = match range.1 {
+            Bound::Included(date) | Bound::Excluded(date) => date.year() as usize,
+            Bound::Unbounded => usize::MAX,
+        };
+
+        self.chunk_starts()
+            .into_iter()
+            .filter(move |year| (first_year..=last_year).contains(year))
+            .flat_map(move |year| self.chunk_values(year, &range))
+    }
+
+    pub fn first(&self) -> Option<(NaiveDate, T)> {
+        self.chunk_starts()
+            .into_iter()
+            .find_map(|year| self.chunk_dates(year))
+            .and_then(|dates| Some((*dates.start(), self.get(*dates.start())?)))
+    }
+
+    pub fn last(&self) -> Option<(NaiveDate, T)> {
+        self.chunk_starts()
+            .into_iter()
+            .rev()
+            .find_map(|year| self.chunk_dates(year))
+            .and_then(|dates| Some((*dates.end(), self.get(*dates.end())?)))
+    }
+
+    pub fn to_vec(&self, range: impl RangeBounds<NaiveDate>) -> Vec<T> {
+        self.range(range).map(|(_, value)| value).collect()
+    }
+
+    pub fn to_btreemap(&self, range: impl RangeBounds<NaiveDate>) -> BTreeMap<NaiveDate, T> {
+        self.range(range).collect()
+    }
+
+    fn chunk_starts(&self) -> BTreeSet<usize> {
+        self.read_dir()
+            .into_keys()
+            .chain(self.imported.keys().cloned())
+            .chain(self.to_insert.keys().cloned())
+            .collect()
+    }
+
+    /// Values of the chunk whose dates are in `range`, only those are cloned
+    fn chunk_values(
+        &self,
+        year: usize,
+        range: &(Bound<NaiveDate>, Bound<NaiveDate>),
+    ) -> BTreeMap<NaiveDate, T> {
+        let in_range = |map: &BTreeMap<WNaiveDate, T>| {
+            map.iter()
+                .map(|(date, value)| (**date, *value))
+                .filter(|(date, _)| range.contains(date))
+                .collect::<BTreeMap<_, _>>()
+        };
+
+        let mut values = self
+            .imported
+            .get(&year)
+            .map(|serialized| in_range(&serialized.map))
+            .or_else(|| self.with_cold_chunk(year, |serialized| Some(in_range(&serialized.map))))
+            .unwrap_or_default();
+
+        if let Some(map) = self.to_insert.get(&year) {
+            values.extend(in_range(map));
+        }
+
+        values
+    }
+
+    /// First and last dates with a value in the chunk, without cloning it
+    fn chunk_dates(&self, year: usize) -> Option<RangeInclusive<NaiveDate>> {
+        let bounds = |map: &BTreeMap<WNaiveDate, T>| {
+            Some((**map.first_key_value()?.0, **map.last_key_value()?.0))
+        };
+
+        let stored = self
+            .imported
+            .get(&year)
+            .map(|serialized| bounds(&serialized.map))
+            .unwrap_or_else(|| self.with_cold_chunk(year, |serialized| bounds(&serialized.map)));
+
+        let inserted = self.to_insert.get(&year).and_then(bounds);
+
+        let first = stored
+            .into_iter()
+            .chain(inserted)
+            .map(|(first, _)| first)
+            .min()?;
+        let last = stored
+            .into_iter()
+            .chain(inserted)
+            .map(|(_, last)| last)
+            .max()?;
+
+        Some(first..=last)
+    }
+
+    /// Writes the values aggregated by week, month, quarter and year next to the `date` folder, only
+    /// recomputing the periods from the first year being exported, to be called after `pre_export`
+    pub fn export_resampled(&self, aggregation: Aggregation) -> color_eyre::Result<()>
+    where
+        T: PartialOrd,
+    {
+        let Some(first_year) = self.to_insert.keys().next() else {
+            return Ok(());
+        };
+
+        let first_date = NaiveDate::from_ymd_opt(*first_year as i32, 1, 1).unwrap();
+
+        Granularity::ALL
+            .iter()
+            .try_for_each(|granularity| -> color_eyre::Result<()> {
+                let path = self.resampled_path(granularity);
+
+                // Damaged files are recomputed from scratch like missing ones
+                let mut resampled = Checksum::check(&path)
+                    .and_then(|_| self.serialization.import::<SerializedDateMap<T>>(&path))
+                    .ok()
+                    .filter(|serialized| serialized.version == self.version)
+                    .map(|serialized| serialized.map)
+                    .unwrap_or_default();
+
+                // Everything is computed the first time
+                let from = (!resampled.is_empty()).then(|| granularity.period_start(first_date));
+
+                if let Some(from) = from {
+                    resampled.retain(|date, _| **date < from);
+                }
+
+                self.range((
+                    from.map_or(Bound::Unbounded, Bound::Included),
+                    Bound::Unbounded,
+                ))
+                .group_by(|(date, _)| granularity.period_start(*date))
+                .into_iter()
+                .for_each(|(period_start, values)| {
+                    if let Some(value) = aggregation.aggregate(values.map(|(_, value)| value)) {
+                        resampled.insert(WNaiveDate::wrap(period_start), value);
+                    }
+                });
+
+                self.serialization.export(
+                    &path,
+                    &SerializedDateMap {
+                        version: self.version,
+                        map: resampled,
+                    },
+                )
             })
     }
 
+    /// Next to the `date` folder
+    fn resampled_path(&self, granularity: &Granularity) -> String {
+        let path = Path::new(&self.path_all)
+            .parent()
+            .unwrap()
+            .to_str()
+            .unwrap();
+
+        self.serialization
+            .append_extension(&format!("{path}/{}", granularity.name()))
+    }
+
+    /// Sorted values of the chunks in memory, `to_insert` is only part of it until `pre_export`
+    pub fn iter_imported(&self) -> impl Iterator<Item = (NaiveDate, T)> + '_ {
+        self.imported
+            .values()
+            .flat_map(|serialized| serialized.map.iter())
+            .map(|(date, value)| (**date, *value))
+    }
+
     #[inline(always)]
     pub fn is_date_safe(&self, date: NaiveDate) -> bool {
         self.initial_first_unsafe_date
@@ -232,9 +525,13 @@ where
             .collect()
     }
 
+    /// Fails on chunks whose checksum doesn't match
     fn import(&self, path: &Path) -> color_eyre::Result<SerializedDateMap<T>> {
-        self.serialization
-            .import::<SerializedDateMap<T>>(path.to_str().unwrap())
+        let path = path.to_str().unwrap();
+
+        Checksum::check(path)?;
+
+        self.serialization.import::<SerializedDateMap<T>>(path)
     }
 }
 
@@ -263,6 +560,14 @@ where
         std::any::type_name::<T>()
     }
 
+    fn version(&self) -> u32 {
+        self.version
+    }
+
+    fn metadata(&self) -> &MapMetadata {
+        &self.metadata
+    }
+
     fn reset(&mut self) -> color_eyre::Result<()> {
         fs::remove_dir(&self.path_all)?;
 
@@ -271,6 +576,7 @@ where
 
         self.imported.clear();
         self.to_insert.clear();
+        self.cold.get_mut().unwrap().clear();
 
         Ok(())
     }
@@ -280,11 +586,15 @@ where
             .iter_mut()
             .enumerate()
             .for_each(|(_, (chunk_start, map))| {
+                let cold = self.cold.get_mut().unwrap().pop(chunk_start).flatten();
+
                 self.imported
                     .entry(chunk_start.to_owned())
-                    .or_insert(SerializedDateMap {
-                        version: self.version,
-                        map: BTreeMap::default(),
+                    .or_insert_with(|| {
+                        cold.unwrap_or(SerializedDateMap {
+                            version: self.version,
+                            map: BTreeMap::default(),
+                        })
                     })
                     .map
                     .extend(mem::take(map));
@@ -325,7 +635,9 @@ where
             .enumerate()
             .filter(|(index, _)| index + self.chunks_in_memory < len)
             .for_each(|(_, key)| {
+                // Read back from disk by `get` if ever needed
                 self.imported.remove(&key);
+                self.cold.get_mut().unwrap().pop(&key);
             });
 
         self.to_insert.clear();
@@ -340,6 +652,20 @@ pub trait AnyDateMap: AnyMap {
     fn as_any_map(&self) -> &(dyn AnyMap + Send + Sync);
 
     fn as_any_mut_map(&mut self) -> &mut dyn AnyMap;
+
+    /// Every exported value within `dates`, including the chunks that aren't in memory
+    fn import_column(
+        &self,
+        dates: RangeInclusive<NaiveDate>,
+    ) -> color_eyre::Result<Box<dyn AnyMapColumn>>;
+
+    fn first_date(&self) -> Option<NaiveDate>;
+
+    fn last_date(&self) -> Option<NaiveDate>;
+
+    /// Years of the chunks whose checksum doesn't match or, without one, which fail to import, the
+    /// last date if the `last` file is damaged and every date if a resampled file is
+    fn find_damaged_dates(&self) -> Vec<RangeInclusive<NaiveDate>>;
 }
 
 impl<T> AnyDateMap for DateMap<T>
@@ -355,7 +681,8 @@ where
         + Send
         + savefile::Serialize
         + savefile::Deserialize
-        + savefile::ReprC,
+        + savefile::ReprC
+        + MapValue,
 {
     #[inline(always)]
     fn get_initial_first_unsafe_date(&self) -> Option<NaiveDate> {
@@ -374,6 +701,110 @@ where
     fn as_any_mut_map(&mut self) -> &mut dyn AnyMap {
         self
     }
+
+    /// Only reads the bounds of the first chunk that has any, unlike `first` which also gets its value
+    fn first_date(&self) -> Option<NaiveDate> {
+        self.chunk_starts()
+            .into_iter()
+            .find_map(|year| self.chunk_dates(year))
+            .map(|dates| *dates.start())
+    }
+
+    /// Without reading the last chunk from disk when it's in memory
+    fn last_date(&self) -> Option<NaiveDate> {
+        let last_inserted = self
+            .to_insert
+            .values()
+            .rev()
+            .find_map(|map| map.last_key_value().map(|(date, _)| **date));
+
+        let last_imported = self
+            .imported
+            .values()
+            .rev()
+            .find_map(|serialized| serialized.map.last_key_value().map(|(date, _)| **date));
+
+        last_inserted.max(last_imported).or_else(|| {
+            self.chunk_starts()
+                .into_iter()
+                .rev()
+                .find_map(|year| self.chunk_dates(year))
+                .map(|dates| *dates.end())
+        })
+    }
+
+    fn find_damaged_dates(&self) -> Vec<RangeInclusive<NaiveDate>> {
+        let mut damaged = self
+            .read_dir()
+            .into_iter()
+            .filter(|(_, path)| {
+                Checksum::verify(path.to_str().unwrap()).is_damaged(|| self.import(path))
+            })
+            .map(|(year, _)| {
+                let year = year as i32;
+
+                NaiveDate::from_ymd_opt(year, 1, 1).unwrap()
+                    ..=NaiveDate::from_ymd_opt(year, 12, 31).unwrap()
+            })
+            .collect_vec();
+
+        let is_last_damaged = self.path_last.as_ref().is_some_and(|path| {
+            Checksum::verify(path).is_damaged(|| self.serialization.import::<T>(path))
+        });
+
+        if is_last_damaged {
+            damaged.extend(self.last_date().map(|date| date..=date));
+        }
+
+        // Only the maps of a `BiMap` are resampled
+        let is_any_resampled_damaged = Granularity::ALL.iter().any(|granularity| {
+            let path = self.resampled_path(granularity);
+
+            (Path::new(&path).exists() || Path::new(&Checksum::path(&path)).exists())
+                && Checksum::verify(&path)
+                    .is_damaged(|| self.serialization.import::<SerializedDateMap<T>>(&path))
+        });
+
+        if is_any_resampled_damaged {
+            damaged.extend(
+                self.first_date()
+                    .zip(self.last_date())
+                    .map(|(first, last)| first..=last),
+            );
+        }
+
+        damaged
+    }
+
+    fn import_column(
+        &self,
+        dates: RangeInclusive<NaiveDate>,
+    ) -> color_eyre::Result<Box<dyn AnyMapColumn>> {
+        let first = *dates.start();
+
+        let mut column = MapColumn::new(date_index(first, *dates.end()) + 1);
+
+        self.read_dir()
+            .into_iter()
+            .filter(|(year, _)| {
+                (dates.start().year() as usize..=dates.end().year() as usize).contains(year)
+            })
+            .try_for_each(|(_, path)| -> color_eyre::Result<()> {
+                let serialized = self.import(&path)?;
+
+                if serialized.version == self.version {
+                    serialized
+                        .map
+                        .into_iter()
+                        .filter(|(date, _)| dates.contains(&**date))
+                        .for_each(|(date, value)| column.set(date_index(first, *date), value));
+                }
+
+                Ok(())
+            })?;
+
+        Ok(Box::new(column))
+    }
 }
 
 impl<T> DateMap<T>
@@ -656,7 +1087,7 @@ where
             + ToF32,
     {
         let to_subtract = date
-            .checked_sub_days(Days::new(x as u64 - 1))
+            .checked_sub_days(Days::new(x as u64))
             .and_then(|previous_date| source.get(previous_date))
             .unwrap_or_default()
             .to_f32();
@@ -669,7 +1100,7 @@ where
 
         let last_value: f32 = source.get(date).unwrap().to_f32();
 
-        let sum = previous_average * x as f32 - 0.0 + last_value;
+        let sum = previous_average * x as f32 - to_subtract + last_value;
 
         let average: T = (sum / x as f32).into();
 
@@ -800,6 +1231,159 @@ where
         median
     }
 
+    /// Exponential moving average with a smoothing factor of `2 / (x + 1)`, starting at the first value
+    #[allow(unused)]
+    pub fn insert_ema(&mut self, date: NaiveDate, source: &DateMap<T>, x: usize) -> T
+    where
+        T: Into<f32> + From<f32>,
+    {
+        let last_value: f32 = source.get(date).unwrap().into();
+
+        let ema = date
+            .checked_sub_days(Days::new(1))
+            .and_then(|previous_ema_date| self.get(previous_ema_date))
+            .map(|previous_ema| {
+                let previous_ema: f32 = previous_ema.into();
+
+                let alpha = 2.0 / (x as f32 + 1.0);
+
+                previous_ema + alpha * (last_value - previous_ema)
+            })
+            .unwrap_or(last_value);
+
+        let ema: T = ema.into();
+
+        self.insert(date, ema);
+
+        ema
+    }
+
+    /// Standard deviation over the last `x` values, updated from the previous one and from `sma`, the
+    /// `insert_simple_average` of `source` over the same `x` which needs to be computed first
+    #[allow(unused)]
+    pub fn insert_stddev(
+        &mut self,
+        date: NaiveDate,
+        source: &DateMap<T>,
+        sma: &DateMap<T>,
+        x: usize,
+    ) -> T
+    where
+        T: Into<f32> + From<f32>,
+    {
+        let to_f64 = |value: T| f64::from(value.into());
+
+        let to_subtract = to_f64(
+            date.checked_sub_days(Days::new(x as u64))
+                .and_then(|previous_date| source.get(previous_date))
+                .unwrap_or_default(),
+        );
+
+        let previous_date = date.checked_sub_days(Days::new(1));
+
+        let previous_stddev = to_f64(
+            previous_date
+                .and_then(|previous_date| self.get(previous_date))
+                .unwrap_or_default(),
+        );
+
+        let previous_average = to_f64(
+            previous_date
+                .and_then(|previous_date| sma.get(previous_date))
+                .unwrap_or_default(),
+        );
+
+        let last_value = to_f64(source.get(date).unwrap());
+
+        let average = to_f64(sma.get(date).unwrap());
+
+        let variance = previous_stddev.powi(2)
+            + (last_value - to_subtract) * (last_value + to_subtract - average - previous_average)
+                / x as f64;
+
+        let stddev: T = (variance.max(0.0).sqrt() as f32).into();
+
+        self.insert(date, stddev);
+
+        stddev
+    }
+
+    /// How many standard deviations the value of `source` is away from its average, `0` when they're
+    /// all the same
+    #[allow(unused)]
+    pub fn insert_zscore(
+        &mut self,
+        date: NaiveDate,
+        source: &DateMap<T>,
+        sma: &DateMap<T>,
+        stddev: &DateMap<T>,
+    ) -> T
+    where
+        T: Into<f32> + From<f32>,
+    {
+        let last_value: f32 = source.get(date).unwrap().into();
+        let average: f32 = sma.get(date).unwrap().into();
+        let stddev: f32 = stddev.get(date).unwrap().into();
+
+        let zscore: T = if stddev > 0.0 {
+            ((last_value - average) / stddev).into()
+        } else {
+            0.0.into()
+        };
+
+        self.insert(date, zscore);
+
+        zscore
+    }
+
+    /// Value below which the percentile of `window` of the values of `source` over the last dates
+    /// fall, interpolated between the closest ones, `0` while some are missing
+    ///
+    /// `window` is carried from one date to the next, it's only filled again from `source` when
+    /// the previous date isn't the last one it got
+    #[allow(unused)]
+    pub fn insert_rolling_percentile(
+        &mut self,
+        date: NaiveDate,
+        source: &DateMap<T>,
+        window: &mut RollingPercentile<NaiveDate, T>,
+    ) -> T
+    where
+        T: FloatCore,
+    {
+        let size = window.size() as u64;
+
+        if window.is_last_key(date.checked_sub_days(Days::new(1)).as_ref()) {
+            if let Some(value) = date
+                .checked_sub_days(Days::new(size))
+                .and_then(|previous_date| source.get(previous_date))
+            {
+                window.remove(value);
+            }
+        } else {
+            window.clear();
+
+            if let Some(start) = date.checked_sub_days(Days::new(size - 1)) {
+                start
+                    .iter_days()
+                    .take_while(|previous_date| *previous_date < date)
+                    .for_each(|previous_date| {
+                        if let Some(value) = source.get(previous_date) {
+                            window.push(previous_date, value);
+                        }
+                    });
+            }
+        }
+
+        window.push(date, source.get(date).unwrap());
+
+        let value = window.get().unwrap_or_default();
+
+        self.insert(date, value);
+
+        value
+    }
+
     #[allow(unused)]
     pub fn _median(map: &BTreeMap<WNaiveDate, T>, size: usize) -> BTreeMap<WNaiveDate, Option<T>>
     where
@@ -845,3 +1429,86 @@ where
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::io::Json;
+
+    use super::*;
+
+    #[test]
+    fn resamples_after_the_aggregation_of_the_map() {
+        let folder = std::env::temp_dir().join(format!("parser/{}/resampled", std::process::id()));
+        let _ = fs::remove_dir_all(&folder);
+        let path = folder.to_str().unwrap();
+
+        let mut map = DateMap::<u32>::new_json(1, path);
+
+        let first_date = NaiveDate::from_ymd_opt(2023, 12, 30).unwrap();
+
+        (0..40).for_each(|day| {
+            map.insert(first_date.checked_add_days(Days::new(day)).unwrap(), 1);
+        });
+
+        map.pre_export();
+        map.export().unwrap();
+        map.export_resampled(Aggregation::Sum).unwrap();
+
+        let months = Json::import::<SerializedDateMap<u32>>(&format!("{path}/month.json"))
+            .unwrap()
+            .map
+            .into_iter()
+            .map(|(date, value)| ((*date).to_string(), value))
+            .collect_vec();
+
+        assert_eq!(
+            months,
+            vec![
+                ("2023-12-01".to_owned(), 2),
+                ("2024-01-01".to_owned(), 31),
+                ("2024-02-01".to_owned(), 7)
+            ]
+        );
+
+        assert!(map.find_damaged_dates().is_empty());
+
+        fs::write(format!("{path}/week.json"), "{}").unwrap();
+
+        let last_date = first_date.checked_add_days(Days::new(39)).unwrap();
+
+        assert_eq!(map.find_damaged_dates(), vec![first_date..=last_date]);
+
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn inserts_a_rolling_percentile_for_every_date() {
+        let folder =
+            std::env::temp_dir().join(format!("parser/{}/percentiles", std::process::id()));
+        let _ = fs::remove_dir_all(&folder);
+        let path = folder.to_str().unwrap();
+
+        let mut source = DateMap::<f32>::new_json(1, &format!("{path}/source"));
+        let mut percentile = DateMap::<f32>::new_json(1, &format!("{path}/percentile"));
+        let mut window = RollingPercentile::new(2, 0.5);
+
+        let first = NaiveDate::from_ymd_opt(2020, 1, 1).unwrap();
+
+        // No value on the 3rd
+        [(0, 1.0), (1, 3.0), (3, 5.0), (4, 7.0)]
+            .into_iter()
+            .for_each(|(days, value)| {
+                let date = first + Days::new(days);
+                source.insert(date, value);
+                percentile.insert_rolling_percentile(date, &source, &mut window);
+            });
+
+        assert_eq!(
+            percentile.to_vec(..),
+            vec![0.0, 2.0, 0.0, 6.0],
+            "the window isn't full the day after the missing one"
+        );
+
+        fs::remove_dir_all(folder).unwrap();
+    }
+}
@@ -1,34 +1,85 @@
 use std::{
     cmp::Ordering,
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
     fmt::Debug,
     fs,
     iter::Sum,
     mem,
-    ops::{Add, RangeInclusive, Sub},
+    num::NonZeroUsize,
+    ops::{Add, Bound, RangeBounds, RangeInclusive, Sub},
     path::{Path, PathBuf},
+    sync::Mutex,
 };
 
 use itertools::Itertools;
+use lru::LruCache;
 use ordered_float::{FloatCore, OrderedFloat};
 use savefile_derive::Savefile;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use crate::{
     bitcoin::{BLOCKS_PER_HAVLING_EPOCH, NUMBER_OF_UNSAFE_BLOCKS},
-    io::{format_path, Serialization},
+    io::{format_path, Checksum, Compression, Serialization},
+    utils::RollingPercentile,
 };
 
-use super::AnyMap;
+use super::{
+    AnyMap, AnyMapColumn, MapColumn, MapMetadata, MapValue, MigratedChunk, Migration, Unit,
+    MIGRATIONS,
+};
 
 pub const HEIGHT_MAP_CHUNK_SIZE: usize = BLOCKS_PER_HAVLING_EPOCH / 16;
 
+/// Enough for a year of heights whatever the alignment of the chunks
+const COLD_CHUNKS_IN_MEMORY: usize = 6;
+
 #[derive(Debug, Savefile, Serialize, Deserialize)]
 pub struct SerializedHeightMap<T> {
     version: u32,
     map: Vec<T>,
 }
 
+impl<T> MigratedChunk for SerializedHeightMap<T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    type Key = usize;
+    type Value = T;
+
+    fn version(&self) -> u32 {
+        self.version
+    }
+
+    fn set_version(&mut self, version: u32) {
+        self.version = version;
+    }
+
+    fn values_mut(&mut self) -> Box<dyn Iterator<Item = &mut T> + '_> {
+        Box::new(self.map.iter_mut())
+    }
+
+    fn get(&self, key: &usize) -> Option<&T> {
+        self.map.get(*key)
+    }
+
+    fn keys(&self) -> Vec<usize> {
+        (0..self.map.len()).collect()
+    }
+
+    /// Stops at the first missing height since chunks can't have holes
+    fn from_entries(version: u32, entries: BTreeMap<usize, T>) -> Self {
+        Self {
+            version,
+            map: entries
+                .into_iter()
+                .enumerate()
+                .take_while(|(index, (key, _))| index == key)
+                .map(|(_, (_, value))| value)
+                .collect(),
+        }
+    }
+}
+
 pub struct HeightMap<T>
 where
     T: Clone + Default + Debug + savefile::Serialize + savefile::Deserialize,
@@ -47,6 +98,11 @@ where
 
     imported: BTreeMap<usize, SerializedHeightMap<T>>,
     to_insert: BTreeMap<usize, BTreeMap<usize, T>>,
+
+    metadata: MapMetadata,
+
+    /// Chunks older than the ones in `imported`, loaded by `get` when needed, `None` if missing or outdated
+    cold: Mutex<LruCache<usize, Option<SerializedHeightMap<T>>>>,
 }
 
 impl<T> HeightMap<T>
@@ -63,7 +119,13 @@ where
 {
     #[allow(unused)]
     pub fn new_bin(version: u32, path: &str) -> Self {
-        Self::new(version, path, Serialization::Binary, 1, true)
+        Self::new(
+            version,
+            path,
+            Serialization::Binary(Compression::default()),
+            1,
+            true,
+        )
     }
 
     #[allow(unused)]
@@ -71,7 +133,7 @@ where
         Self::new(
             version,
             path,
-            Serialization::Binary,
+            Serialization::Binary(Compression::default()),
             chunks_in_memory,
             export_last,
         )
@@ -93,6 +155,22 @@ where
         )
     }
 
+    /// See `MapMetadata`
+    pub fn with_metadata(mut self, unit: Unit, description: &'static str) -> Self {
+        self.metadata = MapMetadata::new(unit, description);
+        self
+    }
+
+    /// For binary maps whose values don't compress well, chunks are imported whatever they were
+    /// exported with
+    #[allow(unused)]
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        if let Serialization::Binary(_) = self.serialization {
+            self.serialization = Serialization::Binary(compression);
+        }
+        self
+    }
+
     fn new(
         version: u32,
         path: &str,
@@ -110,6 +188,16 @@ where
 
         fs::create_dir_all(&path_all).unwrap();
 
+        if let Err(report) = Migration::run::<SerializedHeightMap<T>>(
+            MIGRATIONS,
+            &path,
+            "height",
+            version,
+            &serialization,
+        ) {
+            println!("Couldn't migrate {path}, skipping it: {report}");
+        }
+
         let path_last = {
             if export_last {
                 Some(serialization.append_extension(&format!("{path}/last")))
@@ -133,18 +221,26 @@ where
 
             to_insert: BTreeMap::default(),
             imported: BTreeMap::default(),
+
+            metadata: MapMetadata::default(),
+
+            cold: Mutex::new(LruCache::new(
+                NonZeroUsize::new(COLD_CHUNKS_IN_MEMORY).unwrap(),
+            )),
         };
 
         s.read_dir()
             .into_iter()
             .rev()
             .take(chunks_in_memory)
-            .for_each(|(chunk_start, path)| {
-                if let Ok(serialized) = s.import(&path) {
+            .for_each(|(chunk_start, path)| match s.import(&path) {
+                Ok(serialized) => {
                     if serialized.version == s.version {
                         s.imported.insert(chunk_start, serialized);
                     }
                 }
+                // Damaged chunks are rebuilt by parsing their blocks again
+                Err(report) => println!("Couldn't import {path:?}, skipping it: {report}"),
             });
 
         s.initial_last_height = s
@@ -196,9 +292,146 @@ where
             .or_else(|| {
                 self.imported
                     .get(&chunk_start)
-                    .and_then(|serialized| serialized.map.get(height - chunk_start))
-                    .cloned()
+                    .map(|serialized| serialized.map.get(height - chunk_start).cloned())
+                    .unwrap_or_else(|| self.get_cold(chunk_start, height - chunk_start))
+            })
+    }
+
+    fn get_cold(&self, chunk_start: usize, index: usize) -> Option<T> {
+        self.with_cold_chunk(chunk_start, |serialized| serialized.map.get(index).cloned())
+    }
+
+    fn with_cold_chunk<R>(
+        &self,
+        chunk_start: usize,
+        f: impl FnOnce(&SerializedHeightMap<T>) -> Option<R>,
+    ) -> Option<R> {
+        let mut cold = self.cold.lock().unwrap();
+
+        let serialized = cold.get_or_insert(chunk_start, || {
+            let path = self.serialization.append_extension(&format!(
+                "{}/{}",
+                self.path_all,
+                Self::height_to_chunk_name(chunk_start)
+            ));
+
+            self.import(Path::new(&path))
+                .ok()
+                .filter(|serialized| serialized.version == self.version)
+        });
+
+        f(serialized.as_ref()?)
+    }
+
+    /// Sorted values whose heights are in `range`, wherever they are (memory, disk or not yet exported)
+    pub fn range(&self, range: impl RangeBounds<usize>) -> impl Iterator<Item = (usize, T)> + '_ {
+        let range = (range.start_bound().cloned(), range.end_bound().cloned());
+
+        let first_chunk_start = match range.0 {
+            Bound::Included(height) | Bound::Excluded(height) => {
+                Self::height_to_chunk_start(height)
+            }
+            Bound::Unbounded => 0,
+        };
+
+        self.chunk_starts()
+            .into_iter()
+            .skip_while(move |chunk_start| *chunk_start < first_chunk_start)
+            .take_while(move |chunk_start| match range.1 {
+                Bound::Included(height) => *chunk_start <= height,
+                Bound::Excluded(height) => *chunk_start < height,
+                Bound::Unbounded => true,
             })
+            .flat_map(move |chunk_start| self.chunk_values(chunk_start, &range))
+    }
+
+    pub fn first(&self) -> Option<(usize, T)> {
+        self.chunk_starts()
+            .into_iter()
+            .find_map(|chunk_start| self.chunk_heights(chunk_start))
+            .and_then(|heights| Some((*heights.start(), self.get(heights.start())?)))
+    }
+
+    pub fn last(&self) -> Option<(usize, T)> {
+        self.chunk_starts()
+            .into_iter()
+            .rev()
+            .find_map(|chunk_start| self.chunk_heights(chunk_start))
+            .and_then(|heights| Some((*heights.end(), self.get(heights.end())?)))
+    }
+
+    pub fn to_vec(&self, range: impl RangeBounds<usize>) -> Vec<T> {
+        self.range(range).map(|(_, value)| value).collect()
+    }
+
+    pub fn to_btreemap(&self, range: impl RangeBounds<usize>) -> BTreeMap<usize, T> {
+        self.range(range).collect()
+    }
+
+    fn chunk_starts(&self) -> BTreeSet<usize> {
+        self.read_dir()
+            .into_keys()
+            .chain(self.imported.keys().cloned())
+            .chain(self.to_insert.keys().cloned())
+            .collect()
+    }
+
+    /// Values of the chunk whose heights are in `range`, only those are cloned
+    fn chunk_values(
+        &self,
+        chunk_start: usize,
+        range: &(Bound<usize>, Bound<usize>),
+    ) -> BTreeMap<usize, T> {
+        let in_range = |serialized: &SerializedHeightMap<T>| {
+            serialized
+                .map
+                .iter()
+                .enumerate()
+                .map(|(index, value)| (chunk_start + index, *value))
+                .filter(|(height, _)| range.contains(height))
+                .collect::<BTreeMap<_, _>>()
+        };
+
+        let mut values = self
+            .imported
+            .get(&chunk_start)
+            .map(in_range)
+            .or_else(|| self.with_cold_chunk(chunk_start, |serialized| Some(in_range(serialized))))
+            .unwrap_or_default();
+
+        if let Some(map) = self.to_insert.get(&chunk_start) {
+            values.extend(
+                map.iter()
+                    .map(|(index, value)| (chunk_start + index, *value))
+                    .filter(|(height, _)| range.contains(height)),
+            );
+        }
+
+        values
+    }
+
+    /// First and last heights with a value in the chunk, without cloning it
+    fn chunk_heights(&self, chunk_start: usize) -> Option<RangeInclusive<usize>> {
+        let len = self
+            .imported
+            .get(&chunk_start)
+            .map(|serialized| serialized.map.len())
+            .or_else(|| self.with_cold_chunk(chunk_start, |serialized| Some(serialized.map.len())))
+            .unwrap_or_default();
+
+        let inserted = self.to_insert.get(&chunk_start);
+
+        let first = (len > 0)
+            .then_some(0)
+            .into_iter()
+            .chain(inserted.and_then(|map| map.keys().next().cloned()))
+            .min()?;
+
+        let last = len
+            .checked_sub(1)
+            .max(inserted.and_then(|map| map.keys().next_back().cloned()))?;
+
+        Some(chunk_start + first..=chunk_start + last)
     }
 
     #[inline(always)]
@@ -236,9 +469,13 @@ where
             .collect()
     }
 
+    /// Fails on chunks whose checksum doesn't match
     fn import(&self, path: &Path) -> color_eyre::Result<SerializedHeightMap<T>> {
-        self.serialization
-            .import::<SerializedHeightMap<T>>(path.to_str().unwrap())
+        let path = path.to_str().unwrap();
+
+        Checksum::check(path)?;
+
+        self.serialization.import::<SerializedHeightMap<T>>(path)
     }
 }
 
@@ -266,6 +503,14 @@ where
         std::any::type_name::<T>()
     }
 
+    fn version(&self) -> u32 {
+        self.version
+    }
+
+    fn metadata(&self) -> &MapMetadata {
+        &self.metadata
+    }
+
     fn reset(&mut self) -> color_eyre::Result<()> {
         fs::remove_dir(&self.path_all)?;
 
@@ -274,6 +519,7 @@ where
 
         self.imported.clear();
         self.to_insert.clear();
+        self.cold.get_mut().unwrap().clear();
 
         Ok(())
     }
@@ -283,13 +529,17 @@ where
             .iter_mut()
             .enumerate()
             .for_each(|(_, (chunk_start, map))| {
-                let serialized =
-                    self.imported
-                        .entry(chunk_start.to_owned())
-                        .or_insert(SerializedHeightMap {
+                let cold = self.cold.get_mut().unwrap().pop(chunk_start).flatten();
+
+                let serialized = self
+                    .imported
+                    .entry(chunk_start.to_owned())
+                    .or_insert_with(|| {
+                        cold.unwrap_or(SerializedHeightMap {
                             version: self.version,
                             map: vec![],
-                        });
+                        })
+                    });
 
                 mem::take(map)
                     .into_iter()
@@ -342,7 +592,9 @@ where
             .enumerate()
             .filter(|(index, _)| index + self.chunks_in_memory < len)
             .for_each(|(_, key)| {
+                // Read back from disk by `get` if ever needed
                 self.imported.remove(&key);
+                self.cold.get_mut().unwrap().pop(&key);
             });
 
         self.to_insert.clear();
@@ -357,6 +609,20 @@ pub trait AnyHeightMap: AnyMap {
     fn as_any_map(&self) -> &(dyn AnyMap + Send + Sync);
 
     fn as_any_mut_map(&mut self) -> &mut dyn AnyMap;
+
+    /// Every exported value within `heights`, including the chunks that aren't in memory
+    fn import_column(
+        &self,
+        heights: RangeInclusive<usize>,
+    ) -> color_eyre::Result<Box<dyn AnyMapColumn>>;
+
+    fn first_height(&self) -> Option<usize>;
+
+    fn last_height(&self) -> Option<usize>;
+
+    /// Heights of the chunks whose checksum doesn't match or, without one, which fail to import, and
+    /// the last height if the `last` file is damaged
+    fn find_damaged_heights(&self) -> Vec<RangeInclusive<usize>>;
 }
 
 impl<T> AnyHeightMap for HeightMap<T>
@@ -371,7 +637,8 @@ where
         + savefile::Serialize
         + savefile::Deserialize
         + savefile::ReprC
-        + Sync,
+        + Sync
+        + MapValue,
 {
     #[inline(always)]
     fn get_initial_first_unsafe_height(&self) -> Option<usize> {
@@ -390,6 +657,84 @@ where
     fn as_any_mut_map(&mut self) -> &mut dyn AnyMap {
         self
     }
+
+    /// Chunks are contiguous, so unlike `first` it doesn't need to load the first one
+    fn first_height(&self) -> Option<usize> {
+        self.chunk_starts().first().cloned()
+    }
+
+    /// Without cloning the last chunk like `last` when it's in memory
+    fn last_height(&self) -> Option<usize> {
+        let last_inserted = self
+            .to_insert
+            .iter()
+            .rev()
+            .find_map(|(chunk_start, map)| Some(chunk_start + map.last_key_value()?.0));
+
+        let last_imported = self
+            .imported
+            .iter()
+            .rev()
+            .find_map(|(chunk_start, serialized)| {
+                Some(chunk_start + serialized.map.len().checked_sub(1)?)
+            });
+
+        last_inserted
+            .max(last_imported)
+            .or_else(|| self.last().map(|(height, _)| height))
+    }
+
+    fn find_damaged_heights(&self) -> Vec<RangeInclusive<usize>> {
+        let mut damaged = self
+            .read_dir()
+            .into_iter()
+            .filter(|(_, path)| {
+                Checksum::verify(path.to_str().unwrap()).is_damaged(|| self.import(path))
+            })
+            .map(|(chunk_start, _)| chunk_start..=(chunk_start + HEIGHT_MAP_CHUNK_SIZE - 1))
+            .collect_vec();
+
+        let is_last_damaged = self.path_last.as_ref().is_some_and(|path| {
+            Checksum::verify(path).is_damaged(|| self.serialization.import::<T>(path))
+        });
+
+        if is_last_damaged {
+            damaged.extend(self.last_height().map(|height| height..=height));
+        }
+
+        damaged
+    }
+
+    fn import_column(
+        &self,
+        heights: RangeInclusive<usize>,
+    ) -> color_eyre::Result<Box<dyn AnyMapColumn>> {
+        let mut column = MapColumn::new(heights.clone().count());
+
+        self.read_dir()
+            .into_iter()
+            .filter(|(chunk_start, _)| {
+                *chunk_start <= *heights.end()
+                    && chunk_start + HEIGHT_MAP_CHUNK_SIZE > *heights.start()
+            })
+            .try_for_each(|(chunk_start, path)| -> color_eyre::Result<()> {
+                let serialized = self.import(&path)?;
+
+                if serialized.version == self.version {
+                    serialized
+                        .map
+                        .into_iter()
+                        .enumerate()
+                        .map(|(index, value)| (chunk_start + index, value))
+                        .filter(|(height, _)| heights.contains(height))
+                        .for_each(|(height, value)| column.set(height - heights.start(), value));
+                }
+
+                Ok(())
+            })?;
+
+        Ok(Box::new(column))
+    }
 }
 
 impl<T> HeightMap<T>
@@ -470,7 +815,7 @@ where
     where
         T: Into<f32> + From<f32>,
     {
-        let to_subtract: f32 = (height + 1)
+        let to_subtract: f32 = height
             .checked_sub(x)
             .map(|previous_height| source.get(&previous_height).unwrap())
             .unwrap_or_default()
@@ -559,4 +904,359 @@ where
 
         median
     }
+
+    /// Exponential moving average with a smoothing factor of `2 / (x + 1)`, starting at the first value
+    #[allow(unused)]
+    pub fn insert_ema(&mut self, height: usize, source: &HeightMap<T>, x: usize) -> T
+    where
+        T: Into<f32> + From<f32>,
+    {
+        let last_value: f32 = source.get(&height).unwrap().into();
+
+        let ema = height
+            .checked_sub(1)
+            .map(|previous_ema_height| {
+                let previous_ema: f32 = self.get(&previous_ema_height).unwrap().into();
+
+                let alpha = 2.0 / (x as f32 + 1.0);
+
+                previous_ema + alpha * (last_value - previous_ema)
+            })
+            .unwrap_or(last_value);
+
+        let ema: T = ema.into();
+
+        self.insert(height, ema);
+
+        ema
+    }
+
+    /// Standard deviation over the last `x` values, updated from the previous one and from `sma`, the
+    /// `insert_simple_average` of `source` over the same `x` which needs to be computed first
+    #[allow(unused)]
+    pub fn insert_stddev(
+        &mut self,
+        height: usize,
+        source: &HeightMap<T>,
+        sma: &HeightMap<T>,
+        x: usize,
+    ) -> T
+    where
+        T: Into<f32> + From<f32>,
+    {
+        let to_f64 = |value: T| f64::from(value.into());
+
+        let to_subtract = to_f64(
+            height
+                .checked_sub(x)
+                .map(|previous_height| source.get(&previous_height).unwrap())
+                .unwrap_or_default(),
+        );
+
+        let (previous_stddev, previous_average) = height
+            .checked_sub(1)
+            .map(|previous_height| {
+                (
+                    to_f64(self.get(&previous_height).unwrap()),
+                    to_f64(sma.get(&previous_height).unwrap()),
+                )
+            })
+            .unwrap_or_default();
+
+        let last_value = to_f64(source.get(&height).unwrap());
+
+        let average = to_f64(sma.get(&height).unwrap());
+
+        let variance = previous_stddev.powi(2)
+            + (last_value - to_subtract) * (last_value + to_subtract - average - previous_average)
+                / x as f64;
+
+        let stddev: T = (variance.max(0.0).sqrt() as f32).into();
+
+        self.insert(height, stddev);
+
+        stddev
+    }
+
+    /// How many standard deviations the value of `source` is away from its average, `0` when they're
+    /// all the same
+    #[allow(unused)]
+    pub fn insert_zscore(
+        &mut self,
+        height: usize,
+        source: &HeightMap<T>,
+        sma: &HeightMap<T>,
+        stddev: &HeightMap<T>,
+    ) -> T
+    where
+        T: Into<f32> + From<f32>,
+    {
+        let last_value: f32 = source.get(&height).unwrap().into();
+        let average: f32 = sma.get(&height).unwrap().into();
+        let stddev: f32 = stddev.get(&height).unwrap().into();
+
+        let zscore: T = if stddev > 0.0 {
+            ((last_value - average) / stddev).into()
+        } else {
+            0.0.into()
+        };
+
+        self.insert(height, zscore);
+
+        zscore
+    }
+
+    /// Value below which the percentile of `window` of the last values of `source` fall,
+    /// interpolated between the closest ones
+    ///
+    /// `window` is carried from one height to the next, it's only filled again from `source` when
+    /// the previous height isn't the last one it got
+    #[allow(unused)]
+    pub fn insert_rolling_percentile(
+        &mut self,
+        height: usize,
+        source: &HeightMap<T>,
+        window: &mut RollingPercentile<usize, T>,
+    ) -> T
+    where
+        T: FloatCore,
+    {
+        let size = window.size();
+
+        if window.is_last_key(height.checked_sub(1).as_ref()) {
+            if let Some(previous_height) = height.checked_sub(size) {
+                window.remove(source.get(&previous_height).unwrap());
+            }
+        } else {
+            window.clear();
+
+            (height.saturating_sub(size - 1)..height).for_each(|height| {
+                window.push(height, source.get(&height).unwrap());
+            });
+        }
+
+        window.push(height, source.get(&height).unwrap());
+
+        let value = window.get().unwrap_or_default();
+
+        self.insert(height, value);
+
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_evicted_chunks_from_disk() {
+        let folder = std::env::temp_dir().join(format!("parser/{}/lazy", std::process::id()));
+        let _ = fs::remove_dir_all(&folder);
+        let path = folder.to_str().unwrap();
+
+        let mut map = HeightMap::<u32>::new_json(1, path);
+
+        let heights = 0..HEIGHT_MAP_CHUNK_SIZE * 3;
+
+        heights.clone().for_each(|height| {
+            map.insert(height, height as u32);
+        });
+
+        map.pre_export();
+        map.export().unwrap();
+        map.post_export();
+
+        assert_eq!(map.imported.len(), 1);
+        assert!(map.cold.lock().unwrap().is_empty());
+        assert_eq!(map.get(&5), Some(5));
+        assert_eq!(map.cold.lock().unwrap().len(), 1);
+
+        let map = HeightMap::<u32>::new_json(1, path);
+
+        assert!(heights
+            .clone()
+            .all(|height| map.get(&height) == Some(height as u32)));
+        assert_eq!(map.get(&heights.end), None);
+
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn reads_any_range_whatever_is_in_memory() {
+        let folder = std::env::temp_dir().join(format!("parser/{}/range", std::process::id()));
+        let _ = fs::remove_dir_all(&folder);
+        let path = folder.to_str().unwrap();
+
+        let mut map = HeightMap::<u32>::new_json(1, path);
+
+        let heights = 0..HEIGHT_MAP_CHUNK_SIZE * 3;
+
+        heights.clone().for_each(|height| {
+            map.insert(height, height as u32);
+        });
+
+        map.pre_export();
+        map.export().unwrap();
+        map.post_export();
+
+        assert_eq!(map.first(), Some((0, 0)));
+        assert_eq!(map.last(), Some((heights.end - 1, heights.end as u32 - 1)));
+        assert_eq!(
+            map.to_vec(HEIGHT_MAP_CHUNK_SIZE - 1..=HEIGHT_MAP_CHUNK_SIZE),
+            vec![
+                HEIGHT_MAP_CHUNK_SIZE as u32 - 1,
+                HEIGHT_MAP_CHUNK_SIZE as u32
+            ]
+        );
+        assert_eq!(map.range(..).count(), heights.len());
+        assert_eq!(map.to_btreemap(heights.end..).len(), 0);
+
+        // Values not exported yet
+        map.insert(heights.end, 7);
+
+        assert_eq!(map.last(), Some((heights.end, 7)));
+        assert_eq!(
+            map.to_vec(heights.end - 1..),
+            vec![heights.end as u32 - 1, 7]
+        );
+
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn finds_damaged_chunks() {
+        let folder = std::env::temp_dir().join(format!("parser/{}/damaged", std::process::id()));
+        let _ = fs::remove_dir_all(&folder);
+        let path = folder.to_str().unwrap();
+
+        let mut map = HeightMap::<u32>::new_bin(1, path);
+
+        (0..HEIGHT_MAP_CHUNK_SIZE * 3).for_each(|height| {
+            map.insert(height, height as u32);
+        });
+
+        map.pre_export();
+        map.export().unwrap();
+        map.post_export();
+
+        assert!(map.find_damaged_heights().is_empty());
+
+        let chunks = map.read_dir();
+
+        let second = chunks.get(&HEIGHT_MAP_CHUNK_SIZE).unwrap();
+        let mut bytes = fs::read(second).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        fs::write(second, bytes).unwrap();
+
+        // Without a checksum, an importable chunk is trusted
+        let third = chunks.get(&(HEIGHT_MAP_CHUNK_SIZE * 2)).unwrap();
+        fs::remove_file(Checksum::path(third.to_str().unwrap())).unwrap();
+
+        assert_eq!(
+            map.find_damaged_heights(),
+            vec![HEIGHT_MAP_CHUNK_SIZE..=HEIGHT_MAP_CHUNK_SIZE * 2 - 1]
+        );
+
+        fs::write(third, [0]).unwrap();
+
+        assert_eq!(map.find_damaged_heights().len(), 2);
+
+        let last_height = HEIGHT_MAP_CHUNK_SIZE * 3 - 1;
+
+        fs::write(map.path_last.as_ref().unwrap(), [0]).unwrap();
+
+        assert_eq!(
+            map.find_damaged_heights().last(),
+            Some(&(last_height..=last_height))
+        );
+
+        // Damaged chunks aren't imported, even when they could be read
+        let map = HeightMap::<u32>::_new_bin(1, path, 3, true);
+
+        assert_eq!(map.imported.keys().collect_vec(), vec![&0]);
+
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn computes_rolling_statistics_from_the_previous_ones() {
+        let path = std::env::temp_dir().join(format!("parser/{}/statistics", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        let path = path.to_str().unwrap();
+
+        let values = [3.0, 1.0, 4.0, 1.0, 5.0, 9.0, 2.0, 6.0, 5.0, 3.0];
+        let x = 4;
+
+        let mut source = HeightMap::<f32>::new_json(1, &format!("{path}/source"));
+        let mut sma = HeightMap::<f32>::new_json(1, &format!("{path}/sma"));
+        let mut stddev = HeightMap::<f32>::new_json(1, &format!("{path}/stddev"));
+        let mut zscore = HeightMap::<f32>::new_json(1, &format!("{path}/zscore"));
+        let mut ema = HeightMap::<f32>::new_json(1, &format!("{path}/ema"));
+        let mut percentile = HeightMap::<f32>::new_json(1, &format!("{path}/percentile"));
+        let mut window = RollingPercentile::new(x, 0.25);
+
+        values.iter().enumerate().for_each(|(height, value)| {
+            source.insert(height, *value);
+            sma.insert_simple_average(height, &source, x);
+            stddev.insert_stddev(height, &source, &sma, x);
+            zscore.insert_zscore(height, &source, &sma, &stddev);
+            ema.insert_ema(height, &source, x);
+            percentile.insert_rolling_percentile(height, &source, &mut window);
+        });
+
+        let last = values.len() - 1;
+        let window = &values[values.len() - x..];
+
+        let mean = window.iter().sum::<f32>() / x as f32;
+        let deviation = (window.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / x as f32).sqrt();
+
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-4;
+
+        assert!(close(sma.get(&last).unwrap(), mean));
+        assert!(close(stddev.get(&last).unwrap(), deviation));
+        assert!(close(
+            zscore.get(&last).unwrap(),
+            (values[last] - mean) / deviation
+        ));
+        // Sorted window is 2, 3, 5, 6 and the rank of the first quartile 0.75
+        assert!(close(percentile.get(&last).unwrap(), 2.75));
+        assert_eq!(percentile.get(&(x - 2)), Some(0.0));
+
+        let expected_ema = values[1..]
+            .iter()
+            .fold(values[0], |ema, value| ema + 0.4 * (value - ema));
+        assert!(close(ema.get(&last).unwrap(), expected_ema));
+
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn exports_uncompressed_chunks_when_asked() {
+        let folder =
+            std::env::temp_dir().join(format!("parser/{}/uncompressed", std::process::id()));
+        let _ = fs::remove_dir_all(&folder);
+        let path = folder.to_str().unwrap();
+
+        let mut map = HeightMap::<u32>::new_bin(1, path).with_compression(Compression::None);
+
+        (0..HEIGHT_MAP_CHUNK_SIZE).for_each(|height| {
+            map.insert(height, 0);
+        });
+
+        map.pre_export();
+        map.export().unwrap();
+        map.post_export();
+
+        let chunk = map.read_dir().into_values().next().unwrap();
+
+        assert!(fs::metadata(&chunk).unwrap().len() > (HEIGHT_MAP_CHUNK_SIZE * 4) as u64);
+
+        let map = HeightMap::<u32>::new_bin(1, path);
+
+        assert_eq!(map.get(&(HEIGHT_MAP_CHUNK_SIZE - 1)), Some(0));
+
+        fs::remove_dir_all(path).unwrap();
+    }
 }
@@ -0,0 +1,335 @@
+use std::collections::BTreeMap;
+
+use bitcoin::{
+    opcodes::all::{OP_ENDIF, OP_IF, OP_PUSHNUM_1},
+    script::Instruction,
+    Script, Transaction,
+};
+
+const PROTOCOL_ID: &[u8] = b"ord";
+const CONTENT_TYPE_TAG: &[u8] = &[1];
+
+/// An ordinals envelope: `OP_FALSE OP_IF "ord" [tag value]* OP_0 [body]* OP_ENDIF`
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Envelope {
+    pub content_type: Option<String>,
+    pub body_len: usize,
+    /// Sum of all the pushes of the envelope
+    pub len: usize,
+}
+
+impl Envelope {
+    pub fn parse_all(script: &Script) -> Vec<Self> {
+        let mut envelopes = vec![];
+
+        let mut instructions = script.instructions().peekable();
+
+        while let Some(instruction) = instructions.next() {
+            let Ok(instruction) = instruction else {
+                break;
+            };
+
+            let is_op_false =
+                matches!(instruction, Instruction::PushBytes(bytes) if bytes.is_empty());
+
+            if !is_op_false
+                || !matches!(instructions.peek(), Some(Ok(Instruction::Op(op))) if *op == OP_IF)
+            {
+                continue;
+            }
+
+            instructions.next();
+
+            if !matches!(instructions.peek(), Some(Ok(Instruction::PushBytes(bytes))) if bytes.as_bytes() == PROTOCOL_ID)
+            {
+                continue;
+            }
+
+            instructions.next();
+
+            let mut envelope = Envelope {
+                len: PROTOCOL_ID.len(),
+                ..Default::default()
+            };
+
+            let mut in_body = false;
+            let mut pending_tag: Option<Vec<u8>> = None;
+            let mut is_complete = false;
+
+            for instruction in instructions.by_ref() {
+                let Ok(instruction) = instruction else {
+                    break;
+                };
+
+                match instruction {
+                    Instruction::Op(op) if op == OP_ENDIF => {
+                        is_complete = true;
+                        break;
+                    }
+                    // Some encoders push the content type tag as OP_1
+                    Instruction::Op(op)
+                        if op == OP_PUSHNUM_1 && !in_body && pending_tag.is_none() =>
+                    {
+                        pending_tag = Some(CONTENT_TYPE_TAG.to_vec());
+                    }
+                    Instruction::Op(_) => {}
+                    Instruction::PushBytes(bytes) => {
+                        let bytes = bytes.as_bytes();
+
+                        envelope.len += bytes.len();
+
+                        if in_body {
+                            envelope.body_len += bytes.len();
+                        } else if let Some(tag) = pending_tag.take() {
+                            if tag == CONTENT_TYPE_TAG {
+                                envelope.content_type =
+                                    Some(String::from_utf8_lossy(bytes).to_string());
+                            }
+                        } else if bytes.is_empty() {
+                            in_body = true;
+                        } else {
+                            pending_tag = Some(bytes.to_vec());
+                        }
+                    }
+                }
+            }
+
+            if is_complete {
+                envelopes.push(envelope);
+            }
+        }
+
+        envelopes
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentTypeGroup {
+    Text,
+    Json,
+    Html,
+    Image,
+    Audio,
+    Video,
+    Other,
+}
+
+impl ContentTypeGroup {
+    pub fn new(mime_type: Option<&str>) -> Self {
+        let Some(mime) = mime_type else {
+            return Self::Other;
+        };
+
+        if mime == "application/json" || mime.ends_with("+json") {
+            Self::Json
+        } else if mime == "text/html" {
+            Self::Html
+        } else if mime.starts_with("text/") {
+            Self::Text
+        } else if mime.starts_with("image/") {
+            Self::Image
+        } else if mime.starts_with("audio/") {
+            Self::Audio
+        } else if mime.starts_with("video/") {
+            Self::Video
+        } else {
+            Self::Other
+        }
+    }
+}
+
+const MIME_TYPE_MAX_LEN: usize = 127;
+
+/// Lowercase `type/subtype` of a content type without its parameters, `None` if it isn't a valid one
+/// since anything can be written in an envelope
+pub fn parse_mime_type(content_type: &str) -> Option<String> {
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase();
+
+    let (kind, subtype) = mime.split_once('/')?;
+
+    let is_token = |s: &str| {
+        !s.is_empty()
+            && s.chars()
+                .all(|c| c.is_ascii_alphanumeric() || "!#$&^_.+-".contains(c))
+    };
+
+    (mime.len() <= MIME_TYPE_MAX_LEN && is_token(kind) && is_token(subtype)).then_some(mime)
+}
+
+#[derive(Debug, Default)]
+pub struct SplitByContentTypeGroup<T> {
+    pub text: T,
+    pub json: T,
+    pub html: T,
+    pub image: T,
+    pub audio: T,
+    pub video: T,
+    pub other: T,
+}
+
+impl<T> SplitByContentTypeGroup<T> {
+    pub fn get_mut(&mut self, group: ContentTypeGroup) -> &mut T {
+        match group {
+            ContentTypeGroup::Text => &mut self.text,
+            ContentTypeGroup::Json => &mut self.json,
+            ContentTypeGroup::Html => &mut self.html,
+            ContentTypeGroup::Image => &mut self.image,
+            ContentTypeGroup::Audio => &mut self.audio,
+            ContentTypeGroup::Video => &mut self.video,
+            ContentTypeGroup::Other => &mut self.other,
+        }
+    }
+
+    pub fn as_vec(&self) -> Vec<&T> {
+        vec![
+            &self.text,
+            &self.json,
+            &self.html,
+            &self.image,
+            &self.audio,
+            &self.video,
+            &self.other,
+        ]
+    }
+
+    pub fn as_mut_vec(&mut self) -> Vec<&mut T> {
+        vec![
+            &mut self.text,
+            &mut self.json,
+            &mut self.html,
+            &mut self.image,
+            &mut self.audio,
+            &mut self.video,
+            &mut self.other,
+        ]
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct InscriptionsData {
+    pub witness_bytes: usize,
+    pub envelopes: usize,
+    pub envelope_bytes: usize,
+    pub content_types: SplitByContentTypeGroup<usize>,
+    /// By MIME type, only the valid ones, see `parse_mime_type`
+    pub mime_types: BTreeMap<String, usize>,
+    pub transactions: usize,
+    pub fees: u64,
+}
+
+impl InscriptionsData {
+    /// Returns true if the transaction carries at least one envelope
+    pub fn push_tx(&mut self, tx: &Transaction) -> bool {
+        let mut has_envelope = false;
+
+        tx.input
+            .iter()
+            .filter(|txin| !txin.witness.is_empty())
+            .for_each(|txin| {
+                self.witness_bytes += txin.witness.size();
+
+                if let Some(tapscript) = txin.witness.tapscript() {
+                    Envelope::parse_all(tapscript)
+                        .into_iter()
+                        .for_each(|envelope| {
+                            has_envelope = true;
+
+                            self.envelopes += 1;
+                            self.envelope_bytes += envelope.len;
+
+                            let mime_type =
+                                envelope.content_type.as_deref().and_then(parse_mime_type);
+
+                            *self
+                                .content_types
+                                .get_mut(ContentTypeGroup::new(mime_type.as_deref())) += 1;
+
+                            if let Some(mime_type) = mime_type {
+                                *self.mime_types.entry(mime_type).or_default() += 1;
+                            }
+                        });
+                }
+            });
+
+        if has_envelope {
+            self.transactions += 1;
+        }
+
+        has_envelope
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::{
+        opcodes::{all::*, OP_FALSE},
+        script::{Builder, PushBytesBuf},
+    };
+
+    use super::*;
+
+    fn push(builder: Builder, bytes: &[u8]) -> Builder {
+        builder.push_slice(PushBytesBuf::try_from(bytes.to_vec()).unwrap())
+    }
+
+    #[test]
+    fn parses_envelope() {
+        let mut builder = Builder::new()
+            .push_slice([0; 32])
+            .push_opcode(OP_CHECKSIG)
+            .push_opcode(OP_FALSE)
+            .push_opcode(OP_IF);
+        builder = push(builder, b"ord");
+        builder = push(builder, &[1]);
+        builder = push(builder, b"text/plain;charset=utf-8");
+        builder = builder.push_opcode(OP_FALSE);
+        builder = push(builder, b"Hello, world!");
+        builder = builder.push_opcode(OP_ENDIF);
+
+        let envelopes = Envelope::parse_all(builder.as_script());
+
+        assert_eq!(
+            envelopes,
+            vec![Envelope {
+                content_type: Some("text/plain;charset=utf-8".to_owned()),
+                body_len: 13,
+                len: 3 + 1 + 24 + 13,
+            }]
+        );
+
+        let mime_type = parse_mime_type(envelopes[0].content_type.as_ref().unwrap());
+
+        assert_eq!(mime_type.as_deref(), Some("text/plain"));
+        assert_eq!(
+            ContentTypeGroup::new(mime_type.as_deref()),
+            ContentTypeGroup::Text
+        );
+
+        assert_eq!(
+            parse_mime_type(" Image/SVG+XML ").as_deref(),
+            Some("image/svg+xml")
+        );
+        assert_eq!(parse_mime_type("image/"), None);
+        assert_eq!(parse_mime_type("text/plain\n<script>"), None);
+    }
+
+    #[test]
+    fn ignores_other_protocols_and_unterminated_envelopes() {
+        let mut other = Builder::new().push_opcode(OP_FALSE).push_opcode(OP_IF);
+        other = push(other, b"abc");
+        other = other.push_opcode(OP_ENDIF);
+
+        assert!(Envelope::parse_all(other.as_script()).is_empty());
+
+        let mut unterminated = Builder::new().push_opcode(OP_FALSE).push_opcode(OP_IF);
+        unterminated = push(unterminated, b"ord");
+        unterminated = unterminated.push_opcode(OP_FALSE);
+
+        assert!(Envelope::parse_all(unterminated.as_script()).is_empty());
+    }
+}
@@ -1,4 +1,4 @@
-use std::f32::EPSILON;
+use std::{f32::EPSILON, ops::Mul};
 
 use crate::bitcoin::sats_to_btc;
 
@@ -34,12 +34,15 @@ impl LiquidityClassification {
     }
 
     #[inline(always)]
-    pub fn split(&self, value: f32) -> LiquiditySplitResult {
+    pub fn split<T>(&self, value: T) -> LiquiditySplitResult<T>
+    where
+        T: Copy + From<f32> + Mul<Output = T>,
+    {
         LiquiditySplitResult {
             all: value,
-            illiquid: value * self.illiquid,
-            liquid: value * self.liquid,
-            highly_liquid: value * self.highly_liquid,
+            illiquid: value * T::from(self.illiquid),
+            liquid: value * T::from(self.liquid),
+            highly_liquid: value * T::from(self.highly_liquid),
         }
     }
 
@@ -65,11 +68,11 @@ impl LiquidityClassification {
 }
 
 #[derive(Debug, Default)]
-pub struct LiquiditySplitResult {
-    pub all: f32,
-    pub illiquid: f32,
-    pub liquid: f32,
-    pub highly_liquid: f32,
+pub struct LiquiditySplitResult<T = f32> {
+    pub all: T,
+    pub illiquid: T,
+    pub liquid: T,
+    pub highly_liquid: T,
 }
 
 #[derive(Debug, Default)]
@@ -0,0 +1,212 @@
+#[cfg(feature = "columnar")]
+use std::sync::Arc;
+
+#[cfg(feature = "columnar")]
+use arrow::array::{
+    ArrayRef, Date32Array, Float32Array, Float64Array, UInt16Array, UInt32Array, UInt64Array,
+    UInt8Array,
+};
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::io::Csv;
+
+use super::WNaiveDate;
+
+/// Value of a map, written as a cell in the CSV exports and as a typed array in the columnar ones
+pub trait MapValue: Copy + Serialize + Send + Sync + 'static {
+    /// `None` for values that aren't numbers
+    fn to_f64(self) -> Option<f64>;
+
+    #[cfg(feature = "columnar")]
+    fn to_array(values: Vec<Option<Self>>) -> ArrayRef;
+}
+
+impl MapValue for f32 {
+    fn to_f64(self) -> Option<f64> {
+        Some(f64::from(self))
+    }
+
+    /// NaN being missing like in the CSV exports
+    #[cfg(feature = "columnar")]
+    fn to_array(values: Vec<Option<Self>>) -> ArrayRef {
+        Arc::new(Float32Array::from(
+            values
+                .into_iter()
+                .map(|value| value.filter(|value| !value.is_nan()))
+                .collect::<Vec<_>>(),
+        ))
+    }
+}
+
+impl MapValue for f64 {
+    fn to_f64(self) -> Option<f64> {
+        Some(self)
+    }
+
+    #[cfg(feature = "columnar")]
+    fn to_array(values: Vec<Option<Self>>) -> ArrayRef {
+        Arc::new(Float64Array::from(
+            values
+                .into_iter()
+                .map(|value| value.filter(|value| !value.is_nan()))
+                .collect::<Vec<_>>(),
+        ))
+    }
+}
+
+impl MapValue for u8 {
+    fn to_f64(self) -> Option<f64> {
+        Some(f64::from(self))
+    }
+
+    #[cfg(feature = "columnar")]
+    fn to_array(values: Vec<Option<Self>>) -> ArrayRef {
+        Arc::new(UInt8Array::from(values))
+    }
+}
+
+impl MapValue for u16 {
+    fn to_f64(self) -> Option<f64> {
+        Some(f64::from(self))
+    }
+
+    #[cfg(feature = "columnar")]
+    fn to_array(values: Vec<Option<Self>>) -> ArrayRef {
+        Arc::new(UInt16Array::from(values))
+    }
+}
+
+impl MapValue for u32 {
+    fn to_f64(self) -> Option<f64> {
+        Some(f64::from(self))
+    }
+
+    #[cfg(feature = "columnar")]
+    fn to_array(values: Vec<Option<Self>>) -> ArrayRef {
+        Arc::new(UInt32Array::from(values))
+    }
+}
+
+impl MapValue for u64 {
+    fn to_f64(self) -> Option<f64> {
+        Some(self as f64)
+    }
+
+    #[cfg(feature = "columnar")]
+    fn to_array(values: Vec<Option<Self>>) -> ArrayRef {
+        Arc::new(UInt64Array::from(values))
+    }
+}
+
+impl MapValue for usize {
+    fn to_f64(self) -> Option<f64> {
+        Some(self as f64)
+    }
+
+    #[cfg(feature = "columnar")]
+    fn to_array(values: Vec<Option<Self>>) -> ArrayRef {
+        Arc::new(UInt64Array::from(
+            values
+                .into_iter()
+                .map(|value| value.map(|value| value as u64))
+                .collect::<Vec<_>>(),
+        ))
+    }
+}
+
+impl MapValue for WNaiveDate {
+    fn to_f64(self) -> Option<f64> {
+        None
+    }
+
+    #[cfg(feature = "columnar")]
+    fn to_array(values: Vec<Option<Self>>) -> ArrayRef {
+        let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+
+        Arc::new(Date32Array::from(
+            values
+                .into_iter()
+                .map(|value| value.map(|date| (*date - epoch).num_days() as i32))
+                .collect::<Vec<_>>(),
+        ))
+    }
+}
+
+/// Values of a map at every key of a range, `None` where the map has none
+pub trait AnyMapColumn: Send + Sync {
+    /// Empty when the map has no value at the `index`th key of the range
+    fn cell(&self, index: usize) -> String;
+
+    /// `None` when the map has no value at the `index`th key of the range or if it isn't a number
+    fn to_f64(&self, index: usize) -> Option<f64>;
+
+    #[cfg(feature = "columnar")]
+    fn into_array(self: Box<Self>) -> ArrayRef;
+}
+
+pub struct MapColumn<T>(Vec<Option<T>>);
+
+impl<T> MapColumn<T>
+where
+    T: MapValue,
+{
+    pub fn new(len: usize) -> Self {
+        Self(vec![None; len])
+    }
+
+    pub fn set(&mut self, index: usize, value: T) {
+        self.0[index] = Some(value);
+    }
+}
+
+impl<T> AnyMapColumn for MapColumn<T>
+where
+    T: MapValue,
+{
+    fn cell(&self, index: usize) -> String {
+        self.0[index].as_ref().map(Csv::to_cell).unwrap_or_default()
+    }
+
+    fn to_f64(&self, index: usize) -> Option<f64> {
+        self.0[index]?.to_f64()
+    }
+
+    #[cfg(feature = "columnar")]
+    fn into_array(self: Box<Self>) -> ArrayRef {
+        T::to_array(self.0)
+    }
+}
+
+/// Position of `date` in the range of dates starting at `first`
+pub fn date_index(first: NaiveDate, date: NaiveDate) -> usize {
+    (date - first).num_days() as usize
+}
+
+#[cfg(all(test, feature = "columnar"))]
+mod tests {
+    use arrow::{array::Array, datatypes::DataType};
+
+    use super::*;
+
+    #[test]
+    fn types_columns_after_their_values() {
+        let mut column = MapColumn::<f32>::new(3);
+        column.set(0, 0.5);
+        column.set(2, f32::NAN);
+
+        assert_eq!(column.cell(0), "0.5");
+        assert_eq!(column.cell(1), "");
+
+        let array = (Box::new(column) as Box<dyn AnyMapColumn>).into_array();
+        assert_eq!(array.data_type(), &DataType::Float32);
+        assert_eq!(array.null_count(), 2);
+
+        let array = usize::to_array(vec![Some(3)]);
+        assert_eq!(array.data_type(), &DataType::UInt64);
+
+        let date = WNaiveDate::wrap(NaiveDate::from_ymd_opt(1970, 1, 2).unwrap());
+        let array = WNaiveDate::to_array(vec![Some(date)]);
+        assert_eq!(array.data_type(), &DataType::Date32);
+    }
+}
@@ -0,0 +1,46 @@
+use serde::Serialize;
+
+/// What the values of a map are measured in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Unit {
+    #[serde(rename = "BTC")]
+    Bitcoin,
+    /// Replaced by the code of the currency in the catalog for the datasets converted to another one
+    #[serde(rename = "USD")]
+    Dollar,
+    #[serde(rename = "ratio")]
+    Ratio,
+    #[serde(rename = "count")]
+    Count,
+    #[serde(rename = "bytes")]
+    Bytes,
+    #[serde(rename = "coinblocks")]
+    Coinblocks,
+    #[serde(rename = "coindays")]
+    Coindays,
+    #[serde(rename = "height")]
+    Height,
+    #[serde(rename = "date")]
+    Date,
+    #[serde(rename = "timestamp")]
+    Timestamp,
+    /// Values are ids described by another file
+    #[serde(rename = "id")]
+    Id,
+}
+
+/// Declared by the datasets next to the constructor of each map and exported in the catalog
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MapMetadata {
+    pub unit: Option<Unit>,
+    pub description: &'static str,
+}
+
+impl MapMetadata {
+    pub fn new(unit: Unit, description: &'static str) -> Self {
+        Self {
+            unit: Some(unit),
+            description,
+        }
+    }
+}
@@ -0,0 +1,590 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Debug,
+    fs,
+    path::Path,
+};
+
+use chrono::Local;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use crate::io::{format_path, Checksum, Serialization};
+
+/// Every migration, applied when a map is created, in order
+///
+/// Paths are the ones given to the constructors of the maps, entries should never be removed
+/// as long as chunks at their `from_version` may still exist somewhere.
+pub const MIGRATIONS: &[Migration] = &[];
+
+/// Transforms the chunks of a map whose version is `from_version` into chunks at `to_version`
+/// instead of letting the map ignore them and recompute everything
+pub struct Migration {
+    pub path: &'static str,
+    pub from_version: u32,
+    pub to_version: u32,
+    pub kind: MigrationKind,
+}
+
+#[allow(unused)]
+pub enum MigrationKind {
+    /// The map used to be at `from`, its files are moved if the map has none yet, values are kept
+    Rename { from: &'static str },
+    /// Each value is rewritten, for a unit change for example, a chunk with a value that can't be
+    /// converted back (a `NaN` becomes `null`) isn't migrated
+    Map(fn(Value) -> Value),
+    /// Each value is computed from the ones at the same key in the `sources` maps, given with their
+    /// current version, which need to have the same type of values. Chunks that only exist in the
+    /// first source are created, the ones that no source has, or whose sources are damaged or at
+    /// another version, are left as they are.
+    Recompute {
+        sources: &'static [(&'static str, u32)],
+        compute: fn(&[Option<Value>]) -> Option<Value>,
+    },
+}
+
+/// Serialized chunk of a map, see `SerializedHeightMap` and `SerializedDateMap`
+pub trait MigratedChunk: Sized {
+    type Key: Ord + Clone;
+    type Value: Serialize + DeserializeOwned;
+
+    fn version(&self) -> u32;
+
+    fn set_version(&mut self, version: u32);
+
+    fn values_mut(&mut self) -> Box<dyn Iterator<Item = &mut Self::Value> + '_>;
+
+    fn get(&self, key: &Self::Key) -> Option<&Self::Value>;
+
+    fn keys(&self) -> Vec<Self::Key>;
+
+    fn from_entries(version: u32, entries: BTreeMap<Self::Key, Self::Value>) -> Self;
+}
+
+impl Migration {
+    /// Migrates the chunks in `{path}/{folder}` (`height` or `date`) up to `version`, `path` being
+    /// already formatted
+    ///
+    /// The `last` file is written again from the last chunk when it was migrated, and removed when a
+    /// chunk couldn't be since it might have been the last one
+    pub fn run<C>(
+        migrations: &[Migration],
+        path: &str,
+        folder: &str,
+        version: u32,
+        serialization: &Serialization,
+    ) -> color_eyre::Result<()>
+    where
+        C: MigratedChunk
+            + savefile::Serialize
+            + savefile::Deserialize
+            + Serialize
+            + DeserializeOwned
+            + Debug,
+        C::Value: savefile::Serialize,
+    {
+        let migrations = migrations
+            .iter()
+            .filter(|migration| format_path(migration.path) == path)
+            .collect::<Vec<_>>();
+
+        if migrations.is_empty() {
+            return Ok(());
+        }
+
+        let chunks_folder = format!("{path}/{folder}");
+
+        fs::create_dir_all(&chunks_folder)?;
+
+        migrations.iter().try_for_each(|migration| {
+            if let MigrationKind::Rename { from } = migration.kind {
+                Self::rename(&format_path(from), path, folder, serialization)
+            } else {
+                Ok(())
+            }
+        })?;
+
+        let mut chunk_names = Self::chunk_names(&chunks_folder, serialization)?;
+
+        if let Some(MigrationKind::Recompute { sources, .. }) = migrations
+            .iter()
+            .map(|migration| &migration.kind)
+            .find(|kind| matches!(kind, MigrationKind::Recompute { .. }))
+        {
+            if let Some((source, _)) = sources.first() {
+                chunk_names.extend(Self::chunk_names(
+                    &format!("{}/{folder}", format_path(source)),
+                    serialization,
+                )?);
+            }
+        }
+
+        let mut migrated = 0;
+        let mut failed = false;
+
+        // Chunk with the greatest key, kept when it was migrated
+        let mut last_chunk: Option<(C::Key, Option<C>)> = None;
+
+        chunk_names.into_iter().for_each(|chunk_name| {
+            let chunk_path = format!("{chunks_folder}/{chunk_name}");
+
+            match Self::migrate_chunk::<C>(
+                &migrations,
+                &chunk_path,
+                folder,
+                &chunk_name,
+                version,
+                serialization,
+            ) {
+                Ok((last_key, chunk)) => {
+                    if chunk.is_some() {
+                        migrated += 1;
+                    }
+
+                    if let Some(last_key) = last_key.filter(|last_key| {
+                        last_chunk.as_ref().is_none_or(|(key, _)| last_key > key)
+                    }) {
+                        last_chunk = Some((last_key, chunk));
+                    }
+                }
+                Err(report) => {
+                    println!("Couldn't migrate {chunk_path}, skipping it: {report}");
+                    failed = true;
+                }
+            }
+        });
+
+        let last = serialization.append_extension(&format!("{path}/last"));
+
+        if migrated > 0 && Path::new(&last).exists() {
+            if failed {
+                fs::remove_file(&last)?;
+
+                let checksum = Checksum::path(&last);
+
+                if Path::new(&checksum).exists() {
+                    fs::remove_file(checksum)?;
+                }
+            } else if let Some((key, Some(chunk))) = last_chunk {
+                if let Some(value) = chunk.get(&key) {
+                    serialization.export(&last, value)?;
+                }
+            }
+        }
+
+        if migrated > 0 {
+            println!(
+                "{:?} - Migrated {migrated} chunks of {chunks_folder} to version {version}",
+                Local::now()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Returns the last key of the chunk and the chunk if it was migrated, its file is left
+    /// untouched otherwise
+    fn migrate_chunk<C>(
+        migrations: &[&Migration],
+        chunk_path: &str,
+        folder: &str,
+        chunk_name: &str,
+        version: u32,
+        serialization: &Serialization,
+    ) -> color_eyre::Result<(Option<C::Key>, Option<C>)>
+    where
+        C: MigratedChunk
+            + savefile::Serialize
+            + savefile::Deserialize
+            + Serialize
+            + DeserializeOwned
+            + Debug,
+    {
+        let mut chunk = if Path::new(chunk_path).exists() {
+            Some(serialization.import::<C>(chunk_path)?)
+        } else {
+            None
+        };
+
+        let mut changed = false;
+
+        while chunk
+            .as_ref()
+            .is_none_or(|chunk| chunk.version() != version)
+        {
+            let chunk_version = chunk.as_ref().map(|chunk| chunk.version());
+
+            let Some(migration) = migrations.iter().find(|migration| {
+                migration.from_version != migration.to_version
+                    && chunk_version.map_or(
+                        matches!(migration.kind, MigrationKind::Recompute { .. }),
+                        |chunk_version| migration.from_version == chunk_version,
+                    )
+            }) else {
+                break;
+            };
+
+            let Some(sources) = migration.import_sources(folder, chunk_name, serialization)? else {
+                break;
+            };
+
+            chunk = Some(migration.apply(chunk, sources)?);
+
+            changed = true;
+        }
+
+        let last_key = chunk
+            .as_ref()
+            .and_then(|chunk| chunk.keys().last().cloned());
+
+        match chunk.filter(|_| changed) {
+            Some(chunk) => {
+                serialization.export(chunk_path, &chunk)?;
+                Ok((last_key, Some(chunk)))
+            }
+            None => Ok((last_key, None)),
+        }
+    }
+
+    /// Chunks of the sources of a recomputation, `None` when none has the chunk or when one of them
+    /// isn't at its current version yet, always empty for the other kinds
+    fn import_sources<C>(
+        &self,
+        folder: &str,
+        chunk_name: &str,
+        serialization: &Serialization,
+    ) -> color_eyre::Result<Option<Vec<Option<C>>>>
+    where
+        C: MigratedChunk + savefile::Deserialize + DeserializeOwned + Debug,
+    {
+        let MigrationKind::Recompute { sources, .. } = self.kind else {
+            return Ok(Some(vec![]));
+        };
+
+        let sources = sources
+            .iter()
+            .map(|(source, version)| {
+                let path = format!("{}/{folder}/{chunk_name}", format_path(source));
+
+                if !Path::new(&path).exists() {
+                    return Ok(None);
+                }
+
+                Checksum::check(&path)?;
+
+                let chunk = serialization.import::<C>(&path)?;
+
+                Ok(Some(
+                    Some(chunk).filter(|chunk| chunk.version() == *version),
+                ))
+            })
+            .collect::<color_eyre::Result<Vec<_>>>()?;
+
+        if sources.iter().all(Option::is_none)
+            || sources.iter().any(|source| matches!(source, Some(None)))
+        {
+            return Ok(None);
+        }
+
+        Ok(Some(sources.into_iter().map(Option::flatten).collect()))
+    }
+
+    fn apply<C>(&self, chunk: Option<C>, sources: Vec<Option<C>>) -> color_eyre::Result<C>
+    where
+        C: MigratedChunk + savefile::Deserialize + DeserializeOwned + Debug,
+    {
+        match &self.kind {
+            MigrationKind::Rename { .. } | MigrationKind::Map(_) => {
+                let mut chunk = chunk.unwrap();
+
+                if let MigrationKind::Map(map) = self.kind {
+                    chunk.values_mut().try_for_each(|value| {
+                        *value = serde_json::from_value(map(serde_json::to_value(&*value)?))?;
+
+                        color_eyre::Result::<()>::Ok(())
+                    })?;
+                }
+
+                chunk.set_version(self.to_version);
+
+                Ok(chunk)
+            }
+            MigrationKind::Recompute { compute, .. } => {
+                let keys = sources
+                    .iter()
+                    .flatten()
+                    .flat_map(|source| source.keys())
+                    .collect::<BTreeSet<_>>();
+
+                let entries = keys
+                    .into_iter()
+                    .filter_map(|key| {
+                        let values = sources
+                            .iter()
+                            .map(|source| {
+                                source
+                                    .as_ref()
+                                    .and_then(|source| source.get(&key))
+                                    .and_then(|value| serde_json::to_value(value).ok())
+                            })
+                            .collect::<Vec<_>>();
+
+                        let value = serde_json::from_value(compute(&values)?).ok()?;
+
+                        Some((key, value))
+                    })
+                    .collect();
+
+                Ok(C::from_entries(self.to_version, entries))
+            }
+        }
+    }
+
+    fn rename(
+        from: &str,
+        path: &str,
+        folder: &str,
+        serialization: &Serialization,
+    ) -> color_eyre::Result<()> {
+        let from_folder = format!("{from}/{folder}");
+        let to_folder = format!("{path}/{folder}");
+
+        if !Path::new(&from_folder).exists()
+            || !Self::chunk_names(&to_folder, serialization)?.is_empty()
+        {
+            return Ok(());
+        }
+
+        Self::chunk_names(&from_folder, serialization)?
+            .into_iter()
+            .try_for_each(|chunk_name| {
+                Checksum::rename(
+                    &format!("{from_folder}/{chunk_name}"),
+                    &format!("{to_folder}/{chunk_name}"),
+                )
+            })?;
+
+        let last = serialization.append_extension(&format!("{from}/last"));
+
+        if Path::new(&last).exists() {
+            Checksum::rename(
+                &last,
+                &serialization.append_extension(&format!("{path}/last")),
+            )?;
+        }
+
+        println!("{:?} - Moved {from_folder} to {to_folder}", Local::now());
+
+        Ok(())
+    }
+
+    fn chunk_names(
+        folder: &str,
+        serialization: &Serialization,
+    ) -> color_eyre::Result<BTreeSet<String>> {
+        if !Path::new(folder).exists() {
+            return Ok(BTreeSet::new());
+        }
+
+        Ok(fs::read_dir(folder)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .filter(|path| {
+                path.is_file()
+                    && path.extension().and_then(|extension| extension.to_str())
+                        == Some(serialization.to_extension())
+            })
+            .filter_map(|path| Some(path.file_name()?.to_str()?.to_owned()))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse::SerializedHeightMap;
+
+    use super::*;
+
+    const CHUNK: &str = "0..13125.json";
+
+    fn chunk(version: u32, values: &[f32]) -> SerializedHeightMap<f32> {
+        SerializedHeightMap::from_entries(version, values.iter().cloned().enumerate().collect())
+    }
+
+    fn values(path: &str) -> (u32, Vec<f32>) {
+        let mut chunk = Serialization::Json
+            .import::<SerializedHeightMap<f32>>(&format!("{path}/height/{CHUNK}"))
+            .unwrap();
+
+        (
+            chunk.version(),
+            chunk.values_mut().map(|value| *value).collect(),
+        )
+    }
+
+    #[test]
+    fn migrates_chunks_up_to_the_version_of_the_map() {
+        let folder = std::env::temp_dir().join(format!("parser/{}/migrations", std::process::id()));
+        let _ = fs::remove_dir_all(&folder);
+        let folder = folder.to_str().unwrap().to_owned();
+
+        // Migrations need static paths
+        let path = |name: &str| -> &'static str { format!("{folder}/{name}").leak() };
+
+        let (old, cents, dollars, double) =
+            (path("old"), path("cents"), path("dollars"), path("double"));
+
+        [(old, 1), (cents, 2)]
+            .into_iter()
+            .for_each(|(path, version)| {
+                fs::create_dir_all(format!("{path}/height")).unwrap();
+
+                Serialization::Json
+                    .export(
+                        &format!("{path}/height/{CHUNK}"),
+                        &chunk(version, &[100.0, 250.0]),
+                    )
+                    .unwrap();
+            });
+
+        Serialization::Json
+            .export(&format!("{old}/last.json"), &250.0_f32)
+            .unwrap();
+
+        let sources: &'static [(&'static str, u32)] = vec![(cents, 2), (dollars, 3)].leak();
+
+        let migrations = [
+            Migration {
+                path: dollars,
+                from_version: 1,
+                to_version: 2,
+                kind: MigrationKind::Rename { from: old },
+            },
+            Migration {
+                path: dollars,
+                from_version: 2,
+                to_version: 3,
+                kind: MigrationKind::Map(|cents| (cents.as_f64().unwrap() / 100.0).into()),
+            },
+            Migration {
+                path: double,
+                from_version: 0,
+                to_version: 1,
+                kind: MigrationKind::Recompute {
+                    sources,
+                    compute: |values| {
+                        let sum = values
+                            .iter()
+                            .map(|value| value.as_ref()?.as_f64())
+                            .sum::<Option<f64>>()?;
+                        Some(sum.into())
+                    },
+                },
+            },
+        ];
+
+        Migration::run::<SerializedHeightMap<f32>>(
+            &migrations,
+            dollars,
+            "height",
+            3,
+            &Serialization::Json,
+        )
+        .unwrap();
+
+        assert!(!Path::new(&format!("{old}/height/{CHUNK}")).exists());
+        assert_eq!(values(dollars), (3, vec![1.0, 2.5]));
+        assert_eq!(
+            Serialization::Json
+                .import::<f32>(&format!("{dollars}/last.json"))
+                .unwrap(),
+            2.5
+        );
+
+        Migration::run::<SerializedHeightMap<f32>>(
+            &migrations,
+            double,
+            "height",
+            1,
+            &Serialization::Json,
+        )
+        .unwrap();
+
+        assert_eq!(values(double), (1, vec![101.0, 252.5]));
+
+        // Chunks that no source has aren't recomputed
+        let other = format!("{double}/height/13125..26250.json");
+        Serialization::Json
+            .export(&other, &chunk(0, &[1.0]))
+            .unwrap();
+
+        Migration::run::<SerializedHeightMap<f32>>(
+            &migrations,
+            double,
+            "height",
+            1,
+            &Serialization::Json,
+        )
+        .unwrap();
+
+        let other = Serialization::Json
+            .import::<SerializedHeightMap<f32>>(&other)
+            .unwrap();
+        assert_eq!(other.version(), 0);
+
+        // Nor the ones whose sources are at another version or damaged
+        let outdated = "13125..26250.json";
+        Serialization::Json
+            .export(&format!("{cents}/height/{outdated}"), &chunk(1, &[1.0]))
+            .unwrap();
+
+        let damaged = "26250..39375.json";
+        Serialization::Json
+            .export(&format!("{cents}/height/{damaged}"), &chunk(2, &[1.0]))
+            .unwrap();
+        fs::write(
+            format!("{cents}/height/{damaged}"),
+            r#"{"version":2,"map":[2.0]}"#,
+        )
+        .unwrap();
+
+        Migration::run::<SerializedHeightMap<f32>>(
+            &migrations,
+            double,
+            "height",
+            1,
+            &Serialization::Json,
+        )
+        .unwrap();
+
+        assert!(!Path::new(&format!("{double}/height/{damaged}")).exists());
+        let other = Serialization::Json
+            .import::<SerializedHeightMap<f32>>(&format!("{double}/height/{outdated}"))
+            .unwrap();
+        assert_eq!(other.version(), 0);
+        fs::remove_file(format!("{cents}/height/{outdated}")).unwrap();
+        fs::remove_file(format!("{cents}/height/{damaged}")).unwrap();
+
+        // A value that can't be converted fails its chunk
+        let failing = [Migration {
+            path: cents,
+            from_version: 2,
+            to_version: 3,
+            kind: MigrationKind::Map(|_| Value::Null),
+        }];
+
+        Migration::run::<SerializedHeightMap<f32>>(
+            &failing,
+            cents,
+            "height",
+            3,
+            &Serialization::Json,
+        )
+        .unwrap();
+
+        assert_eq!(values(cents), (2, vec![100.0, 250.0]));
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+}
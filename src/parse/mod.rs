@@ -8,15 +8,22 @@ mod any_map;
 mod bi_map;
 mod block_data;
 mod block_path;
+mod coinjoin;
 mod counter;
 mod database;
 mod date_data;
 mod date_map;
 mod empty_address_data;
 mod height_map;
+mod inscription;
 mod liquidity;
+mod map_column;
+mod map_metadata;
+mod migration;
 mod partial_txout_data;
+mod resampling;
 mod tx_data;
+mod tx_shape;
 mod txout_index;
 mod wnaivedate;
 
@@ -30,14 +37,21 @@ pub use any_map::*;
 pub use bi_map::*;
 pub use block_data::*;
 pub use block_path::*;
+pub use coinjoin::*;
 pub use counter::*;
 pub use database::*;
 pub use date_data::*;
 pub use date_map::*;
 pub use empty_address_data::*;
 pub use height_map::*;
+pub use inscription::*;
 pub use liquidity::*;
+pub use map_column::*;
+pub use map_metadata::*;
+pub use migration::*;
 pub use partial_txout_data::*;
+pub use resampling::*;
 pub use tx_data::*;
+pub use tx_shape::*;
 pub use txout_index::*;
 pub use wnaivedate::*;
@@ -0,0 +1,106 @@
+use std::iter::Sum;
+
+use chrono::{Datelike, Days, NaiveDate};
+use serde::Serialize;
+
+/// How the daily values of a period are combined into the value of the period
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Aggregation {
+    /// For flows like volumes, fees or counts
+    Sum,
+    First,
+    /// For highs
+    Max,
+    /// For lows
+    Min,
+    /// For stocks like supply or realized cap and for prices and ratios
+    #[default]
+    Last,
+}
+
+impl Aggregation {
+    pub fn aggregate<T>(&self, mut values: impl Iterator<Item = T>) -> Option<T>
+    where
+        T: Sum + PartialOrd,
+    {
+        match self {
+            Self::Sum => {
+                let mut values = values.peekable();
+                values.peek()?;
+                Some(values.sum())
+            }
+            Self::First => values.next(),
+            Self::Max => values.reduce(|max, value| if value > max { value } else { max }),
+            Self::Min => values.reduce(|min, value| if value < min { value } else { min }),
+            Self::Last => values.last(),
+        }
+    }
+}
+
+/// Coarser periods than a day, each exported next to the `date` folder of a `BiMap`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Granularity {
+    /// Starting on monday
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+impl Granularity {
+    pub const ALL: [Self; 4] = [Self::Week, Self::Month, Self::Quarter, Self::Year];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Week => "week",
+            Self::Month => "month",
+            Self::Quarter => "quarter",
+            Self::Year => "year",
+        }
+    }
+
+    /// First date of the period containing `date`, which is also its key
+    pub fn period_start(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Self::Week => date
+                .checked_sub_days(Days::new(date.weekday().num_days_from_monday() as u64))
+                .unwrap(),
+            Self::Month => date.with_day(1).unwrap(),
+            Self::Quarter => {
+                NaiveDate::from_ymd_opt(date.year(), (date.month0() / 3 * 3) + 1, 1).unwrap()
+            }
+            Self::Year => NaiveDate::from_ymd_opt(date.year(), 1, 1).unwrap(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_dates_by_period() {
+        let date = NaiveDate::from_ymd_opt(2024, 8, 15).unwrap();
+
+        let starts = Granularity::ALL.map(|granularity| granularity.period_start(date));
+
+        assert_eq!(
+            starts.map(|start| start.to_string()),
+            ["2024-08-12", "2024-08-01", "2024-07-01", "2024-01-01"]
+        );
+
+        assert_eq!(
+            Aggregation::Sum.aggregate([1.0, 2.5].into_iter()),
+            Some(3.5)
+        );
+        assert_eq!(
+            Aggregation::Sum.aggregate(Vec::<f32>::new().into_iter()),
+            None
+        );
+        assert_eq!(Aggregation::First.aggregate([1, 2].into_iter()), Some(1));
+        assert_eq!(Aggregation::Last.aggregate([1, 2].into_iter()), Some(2));
+        assert_eq!(Aggregation::Max.aggregate([1, 3, 2].into_iter()), Some(3));
+        assert_eq!(Aggregation::Min.aggregate([2, 1, 3].into_iter()), Some(1));
+    }
+}
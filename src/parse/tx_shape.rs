@@ -0,0 +1,118 @@
+use bitcoin::Transaction;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxShape {
+    /// Many inputs merged into a single output
+    Consolidation,
+    /// Few inputs paying out to many outputs
+    Batch,
+    /// One input to one output, usually a wallet moving its own funds
+    SelfTransfer,
+    /// Everything else, mostly payment + change
+    Simple,
+}
+
+impl TxShape {
+    pub const CONSOLIDATION_MIN_INPUTS: usize = 3;
+    pub const BATCH_MIN_OUTPUTS: usize = 3;
+
+    /// OP_RETURNs aren't counted as outputs, they don't move any value
+    pub fn from_tx(tx: &Transaction) -> Self {
+        Self::new(
+            tx.input.len(),
+            tx.output
+                .iter()
+                .filter(|txout| !txout.script_pubkey.is_op_return())
+                .count(),
+        )
+    }
+
+    /// `outputs` shouldn't count OP_RETURNs, they don't move any value
+    pub fn new(inputs: usize, outputs: usize) -> Self {
+        if inputs >= Self::CONSOLIDATION_MIN_INPUTS && outputs == 1 {
+            Self::Consolidation
+        } else if outputs >= Self::BATCH_MIN_OUTPUTS && inputs < outputs {
+            Self::Batch
+        } else if inputs == 1 && outputs == 1 {
+            Self::SelfTransfer
+        } else {
+            Self::Simple
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SplitByTxShape<T> {
+    pub consolidation: T,
+    pub batch: T,
+    pub self_transfer: T,
+    pub simple: T,
+}
+
+impl<T> SplitByTxShape<T> {
+    pub fn get_mut(&mut self, shape: TxShape) -> &mut T {
+        match shape {
+            TxShape::Consolidation => &mut self.consolidation,
+            TxShape::Batch => &mut self.batch,
+            TxShape::SelfTransfer => &mut self.self_transfer,
+            TxShape::Simple => &mut self.simple,
+        }
+    }
+
+    pub fn as_vec(&self) -> Vec<&T> {
+        vec![
+            &self.consolidation,
+            &self.batch,
+            &self.self_transfer,
+            &self.simple,
+        ]
+    }
+
+    pub fn as_mut_vec(&mut self) -> Vec<&mut T> {
+        vec![
+            &mut self.consolidation,
+            &mut self.batch,
+            &mut self.self_transfer,
+            &mut self.simple,
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::{
+        absolute::LockTime, script::PushBytesBuf, transaction::Version, Amount, ScriptBuf, TxIn,
+        TxOut,
+    };
+
+    use super::*;
+
+    #[test]
+    fn classifies_at_the_boundaries() {
+        assert_eq!(TxShape::new(3, 1), TxShape::Consolidation);
+        assert_eq!(TxShape::new(2, 1), TxShape::Simple);
+        assert_eq!(TxShape::new(1, 3), TxShape::Batch);
+        assert_eq!(TxShape::new(2, 2), TxShape::Simple);
+        assert_eq!(TxShape::new(3, 3), TxShape::Simple);
+        assert_eq!(TxShape::new(3, 4), TxShape::Batch);
+        assert_eq!(TxShape::new(1, 1), TxShape::SelfTransfer);
+        assert_eq!(TxShape::new(1, 2), TxShape::Simple);
+
+        let output = |script_pubkey| TxOut {
+            value: Amount::from_sat(1),
+            script_pubkey,
+        };
+
+        let tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn::default()],
+            output: vec![
+                output(ScriptBuf::from_bytes(vec![0; 22])),
+                output(ScriptBuf::new_op_return(PushBytesBuf::new())),
+            ],
+        };
+
+        assert_eq!(TxShape::from_tx(&tx), TxShape::SelfTransfer);
+    }
+}
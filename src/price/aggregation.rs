@@ -0,0 +1,262 @@
+use serde::Deserialize;
+
+use super::{is_exchange_source, price_source_id, Candle};
+
+/// How the candles of the sources that have a minute are combined into its reference price
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceAggregation {
+    /// Candle of the first source by priority, the others aren't queried
+    #[default]
+    First,
+    /// Median of each field of the candles of the exchanges
+    Median,
+    /// Mean of each field of the candles of the exchanges weighted by their volume, median if no
+    /// source has volume
+    VolumeWeighted,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AggregatedCandle {
+    pub candle: Candle,
+    /// Id of the source if only one was kept, of `aggregate` otherwise
+    pub source: u8,
+    /// Number of sources kept after rejecting the outliers
+    pub sources_count: u8,
+    /// Difference between the highest and lowest close of all the sources, relative to the reference close
+    pub spread: f32,
+}
+
+impl PriceAggregation {
+    /// `candles` are by priority with their source id, those whose close is more than `max_deviation`
+    /// (a ratio) away from the median close are rejected
+    ///
+    /// Only the exchanges are aggregated, the other sources (`local`, `anchors`) are authoritative
+    /// when they have the minute before any exchange by priority, and only used when no exchange has
+    /// it otherwise
+    ///
+    /// Outliers need at least 3 candles to be told apart, 2 candles too far from each other keep the
+    /// first one by priority, as when every candle is rejected
+    pub fn aggregate(
+        &self,
+        candles: &[(u8, Candle)],
+        max_deviation: f32,
+    ) -> Option<AggregatedCandle> {
+        let exchanges = candles
+            .iter()
+            .filter(|(source, _)| is_exchange_source(*source))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let is_first_authoritative = candles
+            .first()
+            .is_some_and(|(source, _)| !is_exchange_source(*source));
+
+        let candles = if *self == Self::First || exchanges.is_empty() || is_first_authoritative {
+            &candles[..candles.len().min(1)]
+        } else {
+            &exchanges[..]
+        };
+
+        let median_close = median(candles.iter().map(|(_, candle)| candle.close).collect())?;
+
+        let is_close_enough = |close: f32, reference: f32| {
+            reference == 0.0 || ((close - reference) / reference).abs() <= max_deviation
+        };
+
+        let mut kept = match candles.len() {
+            0..=1 => candles.to_vec(),
+            2 if is_close_enough(candles[1].1.close, candles[0].1.close) => candles.to_vec(),
+            2 => vec![],
+            _ => candles
+                .iter()
+                .filter(|(_, candle)| is_close_enough(candle.close, median_close))
+                .cloned()
+                .collect::<Vec<_>>(),
+        };
+
+        if kept.is_empty() {
+            kept.push(candles[0]);
+        }
+
+        let candle = match self {
+            Self::First => kept.first()?.1,
+            Self::Median => median_candle(&kept)?,
+            Self::VolumeWeighted => {
+                volume_weighted_candle(&kept).or_else(|| median_candle(&kept))?
+            }
+        };
+
+        let (min_close, max_close) = candles.iter().fold(
+            (f32::MAX, f32::MIN),
+            |(min_close, max_close), (_, candle)| {
+                (min_close.min(candle.close), max_close.max(candle.close))
+            },
+        );
+
+        let spread = if candle.close == 0.0 {
+            0.0
+        } else {
+            (max_close - min_close) / candle.close
+        };
+
+        let source = if kept.len() == 1 {
+            kept[0].0
+        } else {
+            price_source_id("aggregate")
+        };
+
+        Some(AggregatedCandle {
+            candle,
+            source,
+            sources_count: kept.len() as u8,
+            spread,
+        })
+    }
+}
+
+fn median(mut values: Vec<f32>) -> Option<f32> {
+    if values.is_empty() {
+        return None;
+    }
+
+    values.sort_unstable_by(f32::total_cmp);
+
+    let middle = values.len() / 2;
+
+    Some(if values.len().is_multiple_of(2) {
+        (values[middle - 1] + values[middle]) / 2.0
+    } else {
+        values[middle]
+    })
+}
+
+fn median_candle(candles: &[(u8, Candle)]) -> Option<Candle> {
+    let field =
+        |get: fn(&Candle) -> f32| median(candles.iter().map(|(_, candle)| get(candle)).collect());
+
+    Some(Candle {
+        open: field(|candle| candle.open)?,
+        high: field(|candle| candle.high)?,
+        low: field(|candle| candle.low)?,
+        close: field(|candle| candle.close)?,
+        volume: candles.iter().map(|(_, candle)| candle.volume).sum(),
+    })
+}
+
+fn volume_weighted_candle(candles: &[(u8, Candle)]) -> Option<Candle> {
+    let volume = candles.iter().map(|(_, candle)| candle.volume).sum::<f32>();
+
+    if volume <= 0.0 {
+        return None;
+    }
+
+    let field = |get: fn(&Candle) -> f32| {
+        candles
+            .iter()
+            .map(|(_, candle)| get(candle) * candle.volume)
+            .sum::<f32>()
+            / volume
+    };
+
+    Some(Candle {
+        open: field(|candle| candle.open),
+        high: field(|candle| candle.high),
+        low: field(|candle| candle.low),
+        close: field(|candle| candle.close),
+        volume,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candle(close: f32, volume: f32) -> Candle {
+        Candle {
+            volume,
+            ..Candle::from_close(close)
+        }
+    }
+
+    #[test]
+    fn rejects_outliers_before_aggregating() {
+        let candles = [
+            (2, candle(100.0, 1.0)),
+            (3, candle(102.0, 3.0)),
+            (4, candle(1_000.0, 10.0)),
+        ];
+
+        let median = PriceAggregation::Median.aggregate(&candles, 0.05).unwrap();
+
+        assert_eq!(median.candle.close, 101.0);
+        assert_eq!(median.sources_count, 2);
+        assert_eq!(median.source, price_source_id("aggregate"));
+        assert_eq!(median.spread, 900.0 / 101.0);
+
+        let weighted = PriceAggregation::VolumeWeighted
+            .aggregate(&candles, 0.05)
+            .unwrap();
+
+        assert_eq!(weighted.candle.close, 101.5);
+        assert_eq!(weighted.candle.volume, 4.0);
+
+        let first = PriceAggregation::First
+            .aggregate(&candles[2..], 0.05)
+            .unwrap();
+
+        assert_eq!(first.candle.close, 1_000.0);
+        assert_eq!(first.source, 4);
+        assert_eq!(first.spread, 0.0);
+    }
+
+    #[test]
+    fn keeps_the_first_of_two_distant_candles() {
+        let candles = [(2, candle(100.0, 1.0)), (3, candle(110.0, 1.0))];
+
+        let median = PriceAggregation::Median.aggregate(&candles, 0.05).unwrap();
+
+        assert_eq!(median.candle.close, 100.0);
+        assert_eq!(median.source, 2);
+        assert_eq!(median.sources_count, 1);
+
+        let median = PriceAggregation::Median.aggregate(&candles, 0.2).unwrap();
+
+        assert_eq!(median.candle.close, 105.0);
+        assert_eq!(median.sources_count, 2);
+    }
+
+    #[test]
+    fn only_aggregates_exchanges_after_the_other_sources() {
+        let local = price_source_id("local");
+        let anchors = price_source_id("anchors");
+        let kraken = price_source_id("kraken");
+        let binance = price_source_id("binance");
+
+        let candles = [
+            (local, candle(50.0, 0.0)),
+            (kraken, candle(100.0, 1.0)),
+            (binance, candle(102.0, 1.0)),
+            (anchors, candle(60.0, 0.0)),
+        ];
+
+        let median = PriceAggregation::Median.aggregate(&candles, 0.05).unwrap();
+
+        assert_eq!(median.candle.close, 50.0);
+        assert_eq!(median.source, local);
+
+        let median = PriceAggregation::Median
+            .aggregate(&candles[1..], 0.05)
+            .unwrap();
+
+        assert_eq!(median.candle.close, 101.0);
+        assert_eq!(median.source, price_source_id("aggregate"));
+
+        let median = PriceAggregation::Median
+            .aggregate(&candles[3..], 0.05)
+            .unwrap();
+
+        assert_eq!(median.candle.close, 60.0);
+        assert_eq!(median.source, anchors);
+    }
+}
@@ -0,0 +1,142 @@
+use chrono::{NaiveDate, NaiveTime};
+use color_eyre::eyre::Error;
+
+use super::{Around, Candle, PriceSource};
+
+/// Approximate dollar prices of the first two years, before any exchange has minute candles
+///
+/// - 2009-10-05 is the first published rate (New Liberty Standard, 1,309.03 BTC for $1, derived from
+///   the electricity cost of mining), it's also used for every block before it as there was no market
+/// - Then month-end quotes of New Liberty Standard, BitcoinMarket.com (from 2010-03-17) and Mt.Gox
+///   (from 2010-07-17), rounded
+/// - 2011-01-01 (Mt.Gox) so that the last day of 2010 is covered until its end
+///
+/// Prices between two anchors are interpolated geometrically: `p0 * (p1 / p0) ^ ((t - t0) / (t1 - t0))`,
+/// which is linear on a log chart, the scale on which these prices are usually read
+const ANCHORS: [((i32, u32, u32), f32); 18] = [
+    ((2009, 10, 5), 0.000_764),
+    ((2009, 11, 30), 0.000_9),
+    ((2009, 12, 31), 0.001),
+    ((2010, 1, 31), 0.001),
+    ((2010, 2, 28), 0.001),
+    ((2010, 3, 17), 0.003),
+    ((2010, 4, 30), 0.003),
+    ((2010, 5, 22), 0.004_1),
+    ((2010, 6, 30), 0.008_6),
+    ((2010, 7, 17), 0.049_5),
+    ((2010, 7, 31), 0.067),
+    ((2010, 8, 31), 0.06),
+    ((2010, 9, 30), 0.062),
+    ((2010, 10, 31), 0.19),
+    ((2010, 11, 30), 0.28),
+    ((2010, 12, 15), 0.24),
+    ((2010, 12, 31), 0.3),
+    ((2011, 1, 1), 0.3),
+];
+
+/// Prices from height 0 to the end of 2010 without any file or network access, see `ANCHORS`
+#[derive(Default)]
+pub struct Anchors;
+
+impl Anchors {
+    fn anchor_timestamp(((year, month, day), _): &((i32, u32, u32), f32)) -> u32 {
+        NaiveDate::from_ymd_opt(*year, *month, *day)
+            .unwrap()
+            .and_time(NaiveTime::MIN)
+            .and_utc()
+            .timestamp() as u32
+    }
+
+    /// None after the last anchor, the exchanges take over from there
+    pub fn price(timestamp: u32) -> Option<f32> {
+        let next_index = ANCHORS
+            .iter()
+            .position(|anchor| timestamp <= Self::anchor_timestamp(anchor))?;
+
+        if next_index == 0 {
+            return Some(ANCHORS[0].1);
+        }
+
+        let previous = &ANCHORS[next_index - 1];
+        let next = &ANCHORS[next_index];
+
+        let previous_timestamp = Self::anchor_timestamp(previous);
+        let next_timestamp = Self::anchor_timestamp(next);
+
+        let ratio =
+            (timestamp - previous_timestamp) as f64 / (next_timestamp - previous_timestamp) as f64;
+
+        Some((previous.1 as f64 * (next.1 as f64 / previous.1 as f64).powf(ratio)) as f32)
+    }
+}
+
+impl PriceSource for Anchors {
+    fn name(&self) -> &str {
+        "anchors"
+    }
+
+    fn get_1mn(&mut self, timestamp: u32) -> color_eyre::Result<Candle> {
+        Self::price(timestamp)
+            .map(Candle::from_close)
+            .ok_or(Error::msg("Timestamp is after the last anchor"))
+    }
+
+    fn get_daily(&mut self, date: NaiveDate) -> color_eyre::Result<Candle> {
+        let start = date.and_time(NaiveTime::MIN).and_utc().timestamp() as u32;
+        let end = start + 24 * 60 * 60 - 60;
+
+        let open = Self::price(start).ok_or(Error::msg("Date is after the last anchor"))?;
+        let close = Self::price(end).unwrap_or(open);
+
+        // Interpolation is monotonic between two anchors, a day can only cross one
+        Ok(Candle {
+            open,
+            high: open.max(close),
+            low: open.min(close),
+            close,
+            volume: 0.0,
+        })
+    }
+
+    fn get_1mn_around(&mut self, timestamp: u32, _: u32) -> color_eyre::Result<Around> {
+        Ok(Around {
+            before: Self::price(timestamp).map(|price| (timestamp, Candle::from_close(price))),
+            after: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interpolates_geometrically_between_anchors() {
+        let genesis = 1_231_006_505;
+        assert_eq!(Anchors::price(genesis), Some(ANCHORS[0].1));
+
+        let (first, second) = (&ANCHORS[9], &ANCHORS[10]);
+        let (start, end) = (
+            Anchors::anchor_timestamp(first),
+            Anchors::anchor_timestamp(second),
+        );
+
+        assert_eq!(Anchors::price(start), Some(first.1));
+        assert_eq!(Anchors::price(end), Some(second.1));
+
+        let middle = Anchors::price(start + (end - start) / 2).unwrap();
+        assert!((middle - (first.1 * second.1).sqrt()).abs() < 1e-6);
+
+        assert_eq!(Anchors::price(end + 365 * 24 * 60 * 60), None);
+    }
+
+    #[test]
+    fn covers_the_last_day_of_2010() {
+        let date = NaiveDate::from_ymd_opt(2010, 12, 31).unwrap();
+        let noon = date.and_hms_opt(12, 0, 0).unwrap().and_utc().timestamp() as u32;
+
+        assert!(Anchors::price(noon).is_some());
+        assert!(Anchors.get_1mn(noon + 11 * 60 * 60 + 59 * 60).is_ok());
+        assert_eq!(Anchors.get_daily(date).unwrap().close, 0.3);
+    }
+}
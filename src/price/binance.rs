@@ -1,17 +1,30 @@
-#![allow(dead_code)]
-
 use std::{collections::BTreeMap, path::Path};
 
-use color_eyre::eyre::ContextCompat;
+use chrono::NaiveDate;
+use color_eyre::eyre::{ContextCompat, Error};
 use itertools::Itertools;
 use serde_json::Value;
 
 use crate::io::{Json, IMPORTS_FOLDER_PATH};
 
-pub struct Binance;
+use super::{
+    value_to_f32, Around, Candle, CandlesCache, HttpSourceConfig, PriceSource, PriceSourcesConfig,
+};
+
+pub struct Binance {
+    cache: CandlesCache,
+    config: HttpSourceConfig,
+}
 
 impl Binance {
-    pub fn read_har_file() -> color_eyre::Result<BTreeMap<u32, f32>> {
+    pub fn new(config: &PriceSourcesConfig) -> Self {
+        Self {
+            cache: config.candles_cache("binance"),
+            config: config.http_source_config("binance", "https://api.binance.com", "BTCUSDT"),
+        }
+    }
+
+    pub fn read_har_file() -> color_eyre::Result<BTreeMap<u32, Candle>> {
         println!("binance: read har file");
 
         let path_binance_har = Path::new(IMPORTS_FOLDER_PATH).join("binance.har");
@@ -19,8 +32,7 @@ impl Binance {
         let json: BTreeMap<String, Value> =
             Json::import(path_binance_har.to_str().unwrap()).unwrap_or_default();
 
-        Ok(json
-            .get("log")
+        json.get("log")
             .context("Expect object to have log attribute")?
             .as_object()
             .context("Expect to be an object")?
@@ -43,7 +55,7 @@ impl Binance {
                     .unwrap()
                     .contains("/uiKlines")
             })
-            .flat_map(|entry| {
+            .map(|entry| {
                 let response = entry
                     .as_object()
                     .unwrap()
@@ -57,7 +69,7 @@ impl Binance {
                 let text = content.get("text");
 
                 if text.is_none() {
-                    return vec![];
+                    return Ok(vec![]);
                 }
 
                 let text = text.unwrap().as_str().unwrap();
@@ -68,54 +80,101 @@ impl Binance {
                     .as_array()
                     .unwrap()
                     .iter()
-                    .map(|array| {
-                        let array = array.as_array().unwrap();
-
-                        let timestamp = (array.first().unwrap().as_u64().unwrap() / 1000) as u32;
-
-                        let price = array
-                            .get(4)
-                            .unwrap()
-                            .as_str()
-                            .unwrap()
-                            .parse::<f32>()
-                            .unwrap();
-
-                        (timestamp, price)
-                    })
-                    .collect_vec()
+                    .map(Self::parse_kline)
+                    .collect::<color_eyre::Result<Vec<_>>>()
             })
-            .collect::<BTreeMap<_, _>>())
+            .flatten_ok()
+            .collect::<color_eyre::Result<BTreeMap<_, _>>>()
     }
 
-    pub fn fetch_1mn_prices() -> color_eyre::Result<BTreeMap<u32, f32>> {
+    pub fn fetch_1mn_prices(
+        HttpSourceConfig {
+            base_url,
+            pair,
+            client,
+        }: &mut HttpSourceConfig,
+    ) -> color_eyre::Result<BTreeMap<u32, Candle>> {
         println!("binance: fetch 1mn");
 
-        let body: Value = reqwest::blocking::get(
-            "https://api.binance.com/api/v3/uiKlines?symbol=BTCUSDT&interval=1m&limit=1000",
-        )?
-        .json()?;
+        let body = client.get_json(&format!(
+            "{base_url}/api/v3/uiKlines?symbol={pair}&interval=1m&limit=1000"
+        ))?;
 
-        Ok(body
-            .as_array()
+        body.as_array()
             .context("Expect to be an array")?
             .iter()
-            .map(|value| {
-                // [timestamp, open, high, low, close, volume, ...]
-                let array = value.as_array().unwrap();
+            .map(Self::parse_kline)
+            .collect::<color_eyre::Result<BTreeMap<_, _>>>()
+    }
 
-                let timestamp = array.first().unwrap().as_u64().unwrap() as u32;
+    /// [timestamp in ms, open, high, low, close, volume, ...]
+    fn parse_kline(value: &Value) -> color_eyre::Result<(u32, Candle)> {
+        let array = value.as_array().context("Expect to be an array")?;
 
-                let price = array
-                    .get(4)
-                    .unwrap()
-                    .as_str()
-                    .unwrap()
-                    .parse::<f32>()
-                    .unwrap();
+        let timestamp = (array
+            .first()
+            .and_then(Value::as_u64)
+            .context("Expect a timestamp")?
+            / 1000) as u32;
 
-                (timestamp, price)
-            })
-            .collect::<BTreeMap<_, _>>())
+        let get = |index: usize| value_to_f32(array.get(index)).context("Expect a number");
+
+        let candle = Candle {
+            open: get(1)?,
+            high: get(2)?,
+            low: get(3)?,
+            close: get(4)?,
+            volume: get(5)?,
+        };
+
+        Ok((timestamp, candle))
+    }
+}
+
+impl PriceSource for Binance {
+    fn name(&self) -> &str {
+        "binance"
+    }
+
+    fn get_1mn(&mut self, timestamp: u32) -> color_eyre::Result<Candle> {
+        let Self { cache, config } = self;
+
+        cache.get_1mn("binance", timestamp, || Self::fetch_1mn_prices(config))
+    }
+
+    fn get_1mn_around(&mut self, timestamp: u32, max_gap: u32) -> color_eyre::Result<Around> {
+        let Self { cache, config } = self;
+
+        cache.get_1mn_around(timestamp, max_gap, || Self::fetch_1mn_prices(config))
+    }
+
+    fn get_daily(&mut self, _: NaiveDate) -> color_eyre::Result<Candle> {
+        Err(Error::msg("No daily candles in binance"))
+    }
+}
+
+/// Klines captured from the Binance website in a browser HAR export
+#[derive(Default)]
+pub struct BinanceHar {
+    cache: CandlesCache,
+}
+
+impl PriceSource for BinanceHar {
+    fn name(&self) -> &str {
+        "binance_har"
+    }
+
+    fn get_1mn(&mut self, timestamp: u32) -> color_eyre::Result<Candle> {
+        self.cache
+            .get_1mn("har binance", timestamp, Binance::read_har_file)
+    }
+
+    fn get_1mn_around(&mut self, timestamp: u32, max_gap: u32) -> color_eyre::Result<Around> {
+        self.cache
+            .get_1mn_around(timestamp, max_gap, Binance::read_har_file)
+    }
+
+    fn get_daily(&mut self, _: NaiveDate) -> color_eyre::Result<Candle> {
+        Err(Error::msg("No daily candles in har binance"))
     }
 }
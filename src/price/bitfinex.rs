@@ -0,0 +1,108 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use color_eyre::eyre::ContextCompat;
+use serde_json::Value;
+
+use crate::utils::timestamp_to_naive_date;
+
+use super::{
+    value_to_f32, Around, Candle, CandlesCache, HttpSourceConfig, PriceSource, PriceSourcesConfig,
+};
+
+pub struct Bitfinex {
+    cache: CandlesCache,
+    config: HttpSourceConfig,
+}
+
+impl Bitfinex {
+    pub fn new(config: &PriceSourcesConfig) -> Self {
+        Self {
+            cache: config.candles_cache("bitfinex"),
+            config: config.http_source_config("bitfinex", "https://api-pub.bitfinex.com", "BTCUSD"),
+        }
+    }
+
+    pub fn fetch_1mn_prices(
+        config: &mut HttpSourceConfig,
+    ) -> color_eyre::Result<BTreeMap<u32, Candle>> {
+        println!("bitfinex: fetch 1mn");
+
+        Self::fetch_candles(config, "1m")
+    }
+
+    pub fn fetch_daily_prices(
+        config: &mut HttpSourceConfig,
+    ) -> color_eyre::Result<BTreeMap<NaiveDate, Candle>> {
+        println!("bitfinex: fetch daily");
+
+        Ok(Self::fetch_candles(config, "1D")?
+            .into_iter()
+            .map(|(timestamp, candle)| (timestamp_to_naive_date(timestamp), candle))
+            .collect::<BTreeMap<_, _>>())
+    }
+
+    fn fetch_candles(
+        HttpSourceConfig {
+            base_url,
+            pair,
+            client,
+        }: &mut HttpSourceConfig,
+        timeframe: &str,
+    ) -> color_eyre::Result<BTreeMap<u32, Candle>> {
+        let body = client.get_json(&format!(
+            "{base_url}/v2/candles/trade:{timeframe}:t{pair}/hist?limit=10000"
+        ))?;
+
+        body.as_array()
+            .context("Expect to be an array")?
+            .iter()
+            .map(|value| {
+                // [timestamp in ms, open, close, high, low, volume]
+                let array = value.as_array().context("Expect to be an array")?;
+
+                let timestamp = (array
+                    .first()
+                    .and_then(Value::as_u64)
+                    .context("Expect a timestamp")?
+                    / 1000) as u32;
+
+                let get = |index: usize| value_to_f32(array.get(index)).context("Expect a number");
+
+                let candle = Candle {
+                    open: get(1)?,
+                    high: get(3)?,
+                    low: get(4)?,
+                    close: get(2)?,
+                    volume: get(5)?,
+                };
+
+                Ok((timestamp, candle))
+            })
+            .collect::<color_eyre::Result<BTreeMap<_, _>>>()
+    }
+}
+
+impl PriceSource for Bitfinex {
+    fn name(&self) -> &str {
+        "bitfinex"
+    }
+
+    fn get_1mn(&mut self, timestamp: u32) -> color_eyre::Result<Candle> {
+        let Self { cache, config } = self;
+
+        cache.get_1mn("bitfinex", timestamp, || Self::fetch_1mn_prices(config))
+    }
+
+    fn get_1mn_around(&mut self, timestamp: u32, max_gap: u32) -> color_eyre::Result<Around> {
+        let Self { cache, config } = self;
+
+        cache.get_1mn_around(timestamp, max_gap, || Self::fetch_1mn_prices(config))
+    }
+
+    fn get_daily(&mut self, date: NaiveDate) -> color_eyre::Result<Candle> {
+        let Self { cache, config } = self;
+
+        cache.get_daily("bitfinex", date, || Self::fetch_daily_prices(config))
+    }
+}
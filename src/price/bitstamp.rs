@@ -0,0 +1,118 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use color_eyre::eyre::ContextCompat;
+
+use crate::utils::timestamp_to_naive_date;
+
+use super::{
+    value_to_f32, Around, Candle, CandlesCache, HttpSourceConfig, PriceSource, PriceSourcesConfig,
+};
+
+pub struct Bitstamp {
+    cache: CandlesCache,
+    config: HttpSourceConfig,
+}
+
+impl Bitstamp {
+    pub fn new(config: &PriceSourcesConfig) -> Self {
+        Self {
+            cache: config.candles_cache("bitstamp"),
+            config: config.http_source_config("bitstamp", "https://www.bitstamp.net", "btcusd"),
+        }
+    }
+
+    pub fn fetch_1mn_prices(
+        config: &mut HttpSourceConfig,
+    ) -> color_eyre::Result<BTreeMap<u32, Candle>> {
+        println!("bitstamp: fetch 1mn");
+
+        Self::fetch_candles(config, 60)
+    }
+
+    pub fn fetch_daily_prices(
+        config: &mut HttpSourceConfig,
+    ) -> color_eyre::Result<BTreeMap<NaiveDate, Candle>> {
+        println!("bitstamp: fetch daily");
+
+        Ok(Self::fetch_candles(config, 86400)?
+            .into_iter()
+            .map(|(timestamp, candle)| (timestamp_to_naive_date(timestamp), candle))
+            .collect::<BTreeMap<_, _>>())
+    }
+
+    fn fetch_candles(
+        HttpSourceConfig {
+            base_url,
+            pair,
+            client,
+        }: &mut HttpSourceConfig,
+        step: usize,
+    ) -> color_eyre::Result<BTreeMap<u32, Candle>> {
+        let body = client.get_json(&format!(
+            "{base_url}/api/v2/ohlc/{pair}/?step={step}&limit=1000"
+        ))?;
+
+        body.as_object()
+            .context("Expect to be an object")?
+            .get("data")
+            .context("Expect object to have data")?
+            .as_object()
+            .context("Expect to be an object")?
+            .get("ohlc")
+            .context("Expect object to have ohlc")?
+            .as_array()
+            .context("Expect to be an array")?
+            .iter()
+            .map(|value| {
+                // { timestamp, open, high, low, close, volume }
+                let object = value.as_object().context("Expect to be an object")?;
+
+                let get = |key: &str| value_to_f32(object.get(key)).context("Expect a number");
+
+                let candle = Candle {
+                    open: get("open")?,
+                    high: get("high")?,
+                    low: get("low")?,
+                    close: get("close")?,
+                    volume: get("volume")?,
+                };
+
+                let timestamp = object
+                    .get("timestamp")
+                    .and_then(|timestamp| {
+                        timestamp
+                            .as_u64()
+                            .or_else(|| timestamp.as_str().and_then(|s| s.parse::<u64>().ok()))
+                    })
+                    .context("Expect a timestamp")? as u32;
+
+                Ok((timestamp, candle))
+            })
+            .collect::<color_eyre::Result<BTreeMap<_, _>>>()
+    }
+}
+
+impl PriceSource for Bitstamp {
+    fn name(&self) -> &str {
+        "bitstamp"
+    }
+
+    fn get_1mn(&mut self, timestamp: u32) -> color_eyre::Result<Candle> {
+        let Self { cache, config } = self;
+
+        cache.get_1mn("bitstamp", timestamp, || Self::fetch_1mn_prices(config))
+    }
+
+    fn get_1mn_around(&mut self, timestamp: u32, max_gap: u32) -> color_eyre::Result<Around> {
+        let Self { cache, config } = self;
+
+        cache.get_1mn_around(timestamp, max_gap, || Self::fetch_1mn_prices(config))
+    }
+
+    fn get_daily(&mut self, date: NaiveDate) -> color_eyre::Result<Candle> {
+        let Self { cache, config } = self;
+
+        cache.get_daily("bitstamp", date, || Self::fetch_daily_prices(config))
+    }
+}
@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Candle {
+    pub open: f32,
+    pub high: f32,
+    pub low: f32,
+    pub close: f32,
+    #[serde(default)]
+    pub volume: f32,
+}
+
+impl Candle {
+    /// For prices that aren't from a candle
+    pub fn from_close(close: f32) -> Self {
+        Self {
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 0.0,
+        }
+    }
+
+    /// Same candle closing at `close`, its high and low widened to include it
+    pub fn with_close(self, close: f32) -> Self {
+        Self {
+            high: self.high.max(close),
+            low: self.low.min(close),
+            close,
+            ..self
+        }
+    }
+
+    /// Merges consecutive candles into one, `candles` needs to be sorted by time
+    pub fn merge<'a>(mut candles: impl Iterator<Item = &'a Candle>) -> Option<Self> {
+        let first = *candles.next()?;
+
+        Some(candles.fold(first, |merged, candle| Self {
+            open: merged.open,
+            high: merged.high.max(candle.high),
+            low: merged.low.min(candle.low),
+            close: candle.close,
+            volume: merged.volume + candle.volume,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn widens_the_range_to_the_close() {
+        let candle = Candle {
+            open: 100.0,
+            high: 110.0,
+            low: 90.0,
+            close: 105.0,
+            volume: 1.0,
+        };
+
+        assert_eq!(candle.with_close(107.0).high, 110.0);
+        assert_eq!(candle.with_close(115.0).high, 115.0);
+        assert_eq!(candle.with_close(85.0).low, 85.0);
+        assert_eq!(candle.with_close(85.0).open, 100.0);
+    }
+}
@@ -0,0 +1,111 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use color_eyre::eyre::ContextCompat;
+use serde_json::Value;
+
+use crate::utils::timestamp_to_naive_date;
+
+use super::{
+    value_to_f32, Around, Candle, CandlesCache, HttpSourceConfig, PriceSource, PriceSourcesConfig,
+};
+
+pub struct Coinbase {
+    cache: CandlesCache,
+    config: HttpSourceConfig,
+}
+
+impl Coinbase {
+    pub fn new(config: &PriceSourcesConfig) -> Self {
+        Self {
+            cache: config.candles_cache("coinbase"),
+            config: config.http_source_config(
+                "coinbase",
+                "https://api.exchange.coinbase.com",
+                "BTC-USD",
+            ),
+        }
+    }
+
+    pub fn fetch_1mn_prices(
+        config: &mut HttpSourceConfig,
+    ) -> color_eyre::Result<BTreeMap<u32, Candle>> {
+        println!("coinbase: fetch 1mn");
+
+        Self::fetch_candles(config, 60)
+    }
+
+    pub fn fetch_daily_prices(
+        config: &mut HttpSourceConfig,
+    ) -> color_eyre::Result<BTreeMap<NaiveDate, Candle>> {
+        println!("coinbase: fetch daily");
+
+        Ok(Self::fetch_candles(config, 86400)?
+            .into_iter()
+            .map(|(timestamp, candle)| (timestamp_to_naive_date(timestamp), candle))
+            .collect::<BTreeMap<_, _>>())
+    }
+
+    fn fetch_candles(
+        HttpSourceConfig {
+            base_url,
+            pair,
+            client,
+        }: &mut HttpSourceConfig,
+        granularity: usize,
+    ) -> color_eyre::Result<BTreeMap<u32, Candle>> {
+        let body = client.get_json(&format!(
+            "{base_url}/products/{pair}/candles?granularity={granularity}"
+        ))?;
+
+        body.as_array()
+            .context("Expect to be an array")?
+            .iter()
+            .map(|value| {
+                // [timestamp, low, high, open, close, volume]
+                let array = value.as_array().context("Expect to be an array")?;
+
+                let timestamp = array
+                    .first()
+                    .and_then(Value::as_u64)
+                    .context("Expect a timestamp")? as u32;
+
+                let get = |index: usize| value_to_f32(array.get(index)).context("Expect a number");
+
+                let candle = Candle {
+                    open: get(3)?,
+                    high: get(2)?,
+                    low: get(1)?,
+                    close: get(4)?,
+                    volume: get(5)?,
+                };
+
+                Ok((timestamp, candle))
+            })
+            .collect::<color_eyre::Result<BTreeMap<_, _>>>()
+    }
+}
+
+impl PriceSource for Coinbase {
+    fn name(&self) -> &str {
+        "coinbase"
+    }
+
+    fn get_1mn(&mut self, timestamp: u32) -> color_eyre::Result<Candle> {
+        let Self { cache, config } = self;
+
+        cache.get_1mn("coinbase", timestamp, || Self::fetch_1mn_prices(config))
+    }
+
+    fn get_1mn_around(&mut self, timestamp: u32, max_gap: u32) -> color_eyre::Result<Around> {
+        let Self { cache, config } = self;
+
+        cache.get_1mn_around(timestamp, max_gap, || Self::fetch_1mn_prices(config))
+    }
+
+    fn get_daily(&mut self, date: NaiveDate) -> color_eyre::Result<Candle> {
+        let Self { cache, config } = self;
+
+        cache.get_daily("coinbase", date, || Self::fetch_daily_prices(config))
+    }
+}
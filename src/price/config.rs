@@ -0,0 +1,335 @@
+use std::{collections::BTreeMap, path::Path, time::Duration};
+
+use color_eyre::eyre::Error;
+use serde::{Deserialize, Serialize};
+
+use crate::io::{Json, IMPORTS_FOLDER_PATH};
+
+use super::{
+    Anchors, Binance, BinanceHar, Bitfinex, Bitstamp, CandlesCache, Coinbase, HttpClient, Kraken,
+    LocalCandles, PriceAggregation, PriceSource,
+};
+
+/// Names of the price sources, their position is their id in the price quality datasets, 0 being
+/// unknown (prices stored before it was recorded)
+pub const PRICE_SOURCE_NAMES: [&str; 9] = [
+    "local",
+    "kraken",
+    "binance",
+    "binance_har",
+    "bitstamp",
+    "coinbase",
+    "bitfinex",
+    "anchors",
+    "aggregate",
+];
+
+pub fn price_source_id(name: &str) -> u8 {
+    PRICE_SOURCE_NAMES
+        .iter()
+        .position(|source_name| *source_name == name)
+        .map_or(0, |index| index as u8 + 1)
+}
+
+/// Whether the source is an exchange, the others (`local`, `anchors`) aren't aggregated with them
+pub fn is_exchange_source(id: u8) -> bool {
+    id.checked_sub(1)
+        .and_then(|index| PRICE_SOURCE_NAMES.get(index as usize))
+        .is_some_and(|name| !["local", "anchors", "aggregate"].contains(name))
+}
+
+/// How a price was found, tried in the order of the config when no source has the exact minute
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceMethod {
+    /// Stored before the method was recorded
+    Unknown = 0,
+    Exact = 1,
+    /// Closest candle within `max_gap_minutes`
+    Nearest = 2,
+    /// Linear interpolation between the closes of the candles around, within `max_gap_minutes`
+    Interpolate = 3,
+    /// Close of the date
+    DailyClose = 4,
+    /// Close of the last block priced otherwise, at most `max_previous_blocks` before
+    PreviousBlock = 5,
+    /// Close of the last date priced otherwise, at most `max_previous_days` before, for the dates
+    /// without a daily candle and only if it's in the policy
+    PreviousDate = 6,
+}
+
+impl PriceMethod {
+    pub const ALL: [Self; 7] = [
+        Self::Unknown,
+        Self::Exact,
+        Self::Nearest,
+        Self::Interpolate,
+        Self::DailyClose,
+        Self::PreviousBlock,
+        Self::PreviousDate,
+    ];
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ProviderConfig {
+    pub base_url: Option<String>,
+    pub pair: Option<String>,
+}
+
+/// Read from `imports/price_sources.json`, every field is optional
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PriceSourcesConfig {
+    /// Sources of the per height prices, by priority
+    pub order: Vec<String>,
+    /// Sources of the per date prices, by priority
+    pub daily_order: Vec<String>,
+    pub retries: usize,
+    /// Minimum delay between two requests to the same provider
+    pub rate_limit_ms: u64,
+    pub providers: BTreeMap<String, ProviderConfig>,
+    /// Fallbacks when no source has the minute of a block, tried in order and only those, parsing
+    /// stops when none of them finds a price
+    pub gap_policy: Vec<PriceMethod>,
+    pub max_gap_minutes: u32,
+    pub max_previous_blocks: usize,
+    pub max_previous_days: usize,
+    /// `First` by default so that the sources placed before the exchanges, like `local`, keep
+    /// parses reproducible
+    pub aggregation: PriceAggregation,
+    /// Ratio from the median close above which the candle of a source is rejected
+    pub max_deviation: f32,
+    /// Keep every fetched 1mn candle in `./price/candles` and look there first on later runs
+    pub store_candles: bool,
+}
+
+impl Default for PriceSourcesConfig {
+    fn default() -> Self {
+        Self {
+            order: ["local", "anchors", "kraken", "binance", "binance_har"]
+                .map(String::from)
+                .to_vec(),
+            daily_order: ["local", "anchors", "kraken"].map(String::from).to_vec(),
+            retries: 2,
+            rate_limit_ms: 1000,
+            providers: BTreeMap::default(),
+            gap_policy: vec![
+                PriceMethod::Nearest,
+                PriceMethod::Interpolate,
+                PriceMethod::DailyClose,
+                PriceMethod::PreviousBlock,
+            ],
+            max_gap_minutes: 60,
+            max_previous_blocks: 6,
+            max_previous_days: 1,
+            aggregation: PriceAggregation::default(),
+            max_deviation: 0.02,
+            store_candles: true,
+        }
+    }
+}
+
+pub struct HttpSourceConfig {
+    pub base_url: String,
+    pub pair: String,
+    pub client: HttpClient,
+}
+
+impl PriceSourcesConfig {
+    pub fn import() -> color_eyre::Result<Self> {
+        let path = Path::new(IMPORTS_FOLDER_PATH).join("price_sources.json");
+
+        let config: Self = if path.exists() {
+            Json::import(path.to_str().unwrap())?
+        } else {
+            Self::default()
+        };
+
+        if let Some(name) = config
+            .order
+            .iter()
+            .chain(config.daily_order.iter())
+            .find(|name| !Self::is_source(name))
+        {
+            return Err(Error::msg(format!(
+                "Unknown price source in {}: {name}",
+                path.display()
+            )));
+        }
+
+        Ok(config)
+    }
+
+    /// Every name but the aggregate which isn't a source of its own
+    fn is_source(name: &str) -> bool {
+        name != "aggregate" && PRICE_SOURCE_NAMES.contains(&name)
+    }
+
+    pub fn http_source_config(
+        &self,
+        name: &str,
+        default_base_url: &str,
+        default_pair: &str,
+    ) -> HttpSourceConfig {
+        let provider = self.providers.get(name).cloned().unwrap_or_default();
+
+        HttpSourceConfig {
+            base_url: provider
+                .base_url
+                .unwrap_or(default_base_url.to_owned())
+                .trim_end_matches('/')
+                .to_owned(),
+            pair: provider.pair.unwrap_or(default_pair.to_owned()),
+            client: HttpClient::new(
+                name,
+                self.retries,
+                Duration::from_millis(self.rate_limit_ms),
+            ),
+        }
+    }
+
+    pub fn candles_cache(&self, name: &str) -> CandlesCache {
+        if self.store_candles {
+            CandlesCache::stored(name)
+        } else {
+            CandlesCache::default()
+        }
+    }
+
+    pub fn sources(&self) -> Vec<Box<dyn PriceSource + Send + Sync>> {
+        self.build_sources(&self.order)
+    }
+
+    /// Of `PreviousDate`, `None` when it isn't in the gap policy
+    pub fn max_previous_days(&self) -> Option<usize> {
+        self.gap_policy
+            .contains(&PriceMethod::PreviousDate)
+            .then_some(self.max_previous_days)
+    }
+
+    pub fn daily_sources(&self) -> Vec<Box<dyn PriceSource + Send + Sync>> {
+        self.build_sources(&self.daily_order)
+    }
+
+    fn build_sources(&self, names: &[String]) -> Vec<Box<dyn PriceSource + Send + Sync>> {
+        names
+            .iter()
+            .map(|name| -> Box<dyn PriceSource + Send + Sync> {
+                match name.as_str() {
+                    "local" => Box::<LocalCandles>::default(),
+                    "anchors" => Box::<Anchors>::default(),
+                    "kraken" => Box::new(Kraken::new(self)),
+                    "binance" => Box::new(Binance::new(self)),
+                    "binance_har" => Box::<BinanceHar>::default(),
+                    "bitstamp" => Box::new(Bitstamp::new(self)),
+                    "coinbase" => Box::new(Coinbase::new(self)),
+                    "bitfinex" => Box::new(Bitfinex::new(self)),
+                    _ => unreachable!("Unknown price sources are rejected by import"),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        thread,
+    };
+
+    use super::{super::Candle, *};
+
+    /// Answers every request with the body of the first route whose prefix matches the path
+    fn serve(routes: Vec<(&'static str, &'static str)>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+
+                let mut request_line = String::new();
+                BufReader::new(&stream)
+                    .read_line(&mut request_line)
+                    .unwrap();
+
+                let path = request_line.split(' ').nth(1).unwrap_or_default();
+
+                let response = match routes.iter().find(|(prefix, _)| path.starts_with(prefix)) {
+                    Some((_, body)) => format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    ),
+                    None => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                        .to_owned(),
+                };
+
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        format!("http://{address}")
+    }
+
+    #[test]
+    fn fetches_candles_from_configured_providers() {
+        let base_url = serve(vec![
+            (
+                "/0/public/OHLC",
+                r#"{"error":[],"result":{"XXBTZUSD":[[1700000040,"100.0","110.0","90.0","105.0","102.0","3.5",12]],"last":1700000040}}"#,
+            ),
+            (
+                "/api/v2/ohlc/btcusd/",
+                r#"{"data":{"pair":"BTC/USD","ohlc":[{"timestamp":"1700000040","open":"100","high":"110","low":"90","close":"105","volume":"3.5"}]}}"#,
+            ),
+            (
+                "/products/BTC-USD/candles",
+                "[[1700000040,90,110,100,105,3.5]]",
+            ),
+            (
+                "/v2/candles/trade:1m:tBTCUSD/hist",
+                "[[1700000040000,100,105,110,90,3.5]]",
+            ),
+        ]);
+
+        let provider = ProviderConfig {
+            base_url: Some(format!("{base_url}/")),
+            pair: None,
+        };
+
+        let config = PriceSourcesConfig {
+            order: ["kraken", "bitstamp", "coinbase", "bitfinex"]
+                .map(String::from)
+                .to_vec(),
+            retries: 0,
+            rate_limit_ms: 0,
+            store_candles: false,
+            providers: ["kraken", "bitstamp", "coinbase", "bitfinex"]
+                .into_iter()
+                .map(|name| (name.to_owned(), provider.clone()))
+                .collect(),
+            ..Default::default()
+        };
+
+        let expected = Candle {
+            open: 100.0,
+            high: 110.0,
+            low: 90.0,
+            close: 105.0,
+            volume: 3.5,
+        };
+
+        config.sources().iter_mut().for_each(|source| {
+            assert_eq!(
+                source.get_1mn(1_700_000_040).unwrap(),
+                expected,
+                "{}",
+                source.name()
+            );
+
+            assert!(source.get_1mn(1_700_000_100).is_err(), "{}", source.name());
+        });
+    }
+}
@@ -0,0 +1,130 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::NaiveDate;
+use color_eyre::eyre::{ContextCompat, Error};
+use itertools::Itertools;
+
+use crate::{
+    io::{Json, IMPORTS_FOLDER_PATH},
+    parse::BlockData,
+    utils::timestamp_to_naive_date,
+};
+
+/// A currency other than the dollar, every price is fetched in dollars then converted with daily
+/// exchange rates read from `imports/fx/{code}.json`
+///
+/// The file is an object of `YYYY-MM-DD` dates to the amount of the currency one dollar buys, a
+/// missing date (week-ends, holidays) uses the last known rate before it or the first one
+pub struct Currency {
+    pub code: String,
+    rates: BTreeMap<NaiveDate, f64>,
+}
+
+impl Currency {
+    pub fn folder_path() -> PathBuf {
+        Path::new(IMPORTS_FOLDER_PATH).join("fx")
+    }
+
+    pub fn import_all() -> color_eyre::Result<Vec<Self>> {
+        let folder_path = Self::folder_path();
+
+        if !folder_path.exists() {
+            return Ok(vec![]);
+        }
+
+        fs::read_dir(folder_path)?
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| {
+                path.extension().and_then(|extension| extension.to_str()) == Some("json")
+            })
+            .sorted()
+            .map(|path| Self::import(&path))
+            .collect()
+    }
+
+    fn import(path: &Path) -> color_eyre::Result<Self> {
+        let code = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .context("Expect file to have a name")?
+            .to_lowercase();
+
+        if code == "usd" || !code.chars().all(|char| char.is_ascii_alphanumeric()) {
+            return Err(Error::msg(format!("Invalid currency code: {code}")));
+        }
+
+        let json: BTreeMap<String, f64> = Json::import(path.to_str().unwrap())?;
+
+        let rates = json
+            .into_iter()
+            .map(|(date, rate)| {
+                NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+                    .map(|date| (date, rate))
+                    .map_err(|_| Error::msg(format!("Invalid date in {code} rates: {date}")))
+            })
+            .collect::<color_eyre::Result<BTreeMap<_, _>>>()?;
+
+        if rates.is_empty() {
+            return Err(Error::msg(format!("No rates for {code}")));
+        }
+
+        Ok(Self::new(code, rates))
+    }
+
+    pub fn new(code: String, rates: BTreeMap<NaiveDate, f64>) -> Self {
+        Self { code, rates }
+    }
+
+    pub fn rate(&self, date: NaiveDate) -> f64 {
+        self.rates
+            .range(..=date)
+            .next_back()
+            .or_else(|| self.rates.first_key_value())
+            .map(|(_, rate)| *rate)
+            .unwrap()
+    }
+
+    pub fn convert(&self, dollars: f64, date: NaiveDate) -> f64 {
+        dollars * self.rate(date)
+    }
+
+    /// Price of the block in the currency, at the rate of its own date
+    pub fn block_price(&self, block_data: &BlockData) -> f64 {
+        self.convert(
+            block_data.price,
+            timestamp_to_naive_date(block_data.timestamp),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2020, 1, day).unwrap()
+    }
+
+    #[test]
+    fn uses_the_last_known_rate() {
+        let currency = Currency::new(
+            "eur".to_owned(),
+            BTreeMap::from([(date(3), 0.9), (date(6), 0.8)]),
+        );
+
+        // Before the first rate
+        assert_eq!(currency.rate(date(1)), 0.9);
+        assert_eq!(currency.rate(date(3)), 0.9);
+        // Week-end
+        assert_eq!(currency.rate(date(5)), 0.9);
+        assert_eq!(currency.rate(date(6)), 0.8);
+        assert_eq!(currency.rate(date(31)), 0.8);
+
+        assert_eq!(currency.convert(10_000.0, date(4)), 9_000.0);
+        assert_eq!(currency.convert(10_000.0, date(7)), 8_000.0);
+    }
+}
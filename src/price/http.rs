@@ -0,0 +1,103 @@
+use std::{
+    collections::BTreeMap,
+    sync::Mutex,
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+use color_eyre::eyre::Error;
+use serde_json::Value;
+
+/// Time of the next allowed request by provider, shared by every client of the run since
+/// the per height and per date sources each have their own
+static NEXT_REQUESTS: Mutex<BTreeMap<String, Instant>> = Mutex::new(BTreeMap::new());
+
+/// Blocking JSON client with retries and a minimum delay between two requests to the same provider
+pub struct HttpClient {
+    provider: String,
+    retries: usize,
+    rate_limit: Duration,
+}
+
+impl HttpClient {
+    pub fn new(provider: &str, retries: usize, rate_limit: Duration) -> Self {
+        Self {
+            provider: provider.to_owned(),
+            retries,
+            rate_limit,
+        }
+    }
+
+    pub fn get_json(&mut self, url: &str) -> color_eyre::Result<Value> {
+        let mut last_error = Error::msg(format!("Couldn't fetch {url}"));
+
+        for attempt in 0..=self.retries {
+            if attempt != 0 {
+                println!("retrying {url} ({attempt}/{})", self.retries);
+            }
+
+            self.wait();
+
+            match reqwest::blocking::get(url)
+                .and_then(|response| response.error_for_status())
+                .and_then(|response| response.json::<Value>())
+            {
+                Ok(json) => return Ok(json),
+                Err(error) => last_error = error.into(),
+            }
+        }
+
+        Err(last_error)
+    }
+
+    fn wait(&self) {
+        let now = Instant::now();
+
+        let request = {
+            let mut next_requests = NEXT_REQUESTS.lock().unwrap();
+
+            let request = next_requests
+                .get(&self.provider)
+                .map_or(now, |next_request| now.max(*next_request));
+
+            next_requests.insert(self.provider.to_owned(), request + self.rate_limit);
+
+            request
+        };
+
+        sleep(request - now);
+    }
+}
+
+/// Exchanges send numbers either as JSON numbers or as strings
+pub fn value_to_f32(value: Option<&Value>) -> Option<f32> {
+    value.and_then(|value| {
+        value
+            .as_f64()
+            .or_else(|| value.as_str().and_then(|s| s.parse::<f64>().ok()))
+            .map(|f| f as f32)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shares_the_rate_limit_between_clients_of_a_provider() {
+        let rate_limit = Duration::from_millis(50);
+
+        let first = HttpClient::new("rate_limit_test", 0, rate_limit);
+        let second = HttpClient::new("rate_limit_test", 0, rate_limit);
+        let other = HttpClient::new("rate_limit_test_other", 0, rate_limit);
+
+        let start = Instant::now();
+
+        first.wait();
+        other.wait();
+        assert!(start.elapsed() < rate_limit);
+
+        second.wait();
+        assert!(start.elapsed() >= rate_limit);
+    }
+}
@@ -1,88 +1,118 @@
-#![allow(dead_code)]
-
-use std::collections::{BTreeMap, HashMap};
+use std::collections::BTreeMap;
 
+use chrono::NaiveDate;
 use color_eyre::eyre::ContextCompat;
 use serde_json::Value;
 
 use crate::utils::timestamp_to_naive_date;
 
-pub struct Kraken;
+use super::{
+    value_to_f32, Around, Candle, CandlesCache, HttpSourceConfig, PriceSource, PriceSourcesConfig,
+};
+
+pub struct Kraken {
+    cache: CandlesCache,
+    config: HttpSourceConfig,
+}
 
 impl Kraken {
-    pub fn fetch_1mn_prices() -> color_eyre::Result<BTreeMap<u32, f32>> {
+    pub fn new(config: &PriceSourcesConfig) -> Self {
+        Self {
+            cache: config.candles_cache("kraken"),
+            config: config.http_source_config("kraken", "https://api.kraken.com", "XBTUSD"),
+        }
+    }
+
+    pub fn fetch_1mn_prices(
+        config: &mut HttpSourceConfig,
+    ) -> color_eyre::Result<BTreeMap<u32, Candle>> {
         println!("kraken: fetch 1mn");
 
-        let body: Value =
-            reqwest::blocking::get("https://api.kraken.com/0/public/OHLC?pair=XBTUSD&interval=1")?
-                .json()?;
+        Self::fetch_candles(config, 1)
+    }
 
-        Ok(body
-            .as_object()
+    pub fn fetch_daily_prices(
+        config: &mut HttpSourceConfig,
+    ) -> color_eyre::Result<BTreeMap<NaiveDate, Candle>> {
+        println!("fetch kraken daily");
+
+        Ok(Self::fetch_candles(config, 1440)?
+            .into_iter()
+            .map(|(timestamp, candle)| (timestamp_to_naive_date(timestamp), candle))
+            .collect::<BTreeMap<_, _>>())
+    }
+
+    fn fetch_candles(
+        HttpSourceConfig {
+            base_url,
+            pair,
+            client,
+        }: &mut HttpSourceConfig,
+        interval: usize,
+    ) -> color_eyre::Result<BTreeMap<u32, Candle>> {
+        let body = client.get_json(&format!(
+            "{base_url}/0/public/OHLC?pair={pair}&interval={interval}"
+        ))?;
+
+        // The result is keyed by Kraken's own name of the pair (XXBTZUSD for XBTUSD) next to `last`
+        body.as_object()
             .context("Expect to be an object")?
             .get("result")
             .context("Expect object to have result")?
             .as_object()
             .context("Expect to be an object")?
-            .get("XXBTZUSD")
-            .context("Expect to have XXBTZUSD")?
+            .iter()
+            .find(|(key, _)| *key != "last")
+            .context("Expect to have the pair")?
+            .1
             .as_array()
             .context("Expect to be an array")?
             .iter()
             .map(|value| {
-                let array = value.as_array().unwrap();
+                // [timestamp, open, high, low, close, vwap, volume, count]
+                let array = value.as_array().context("Expect to be an array")?;
+
+                let timestamp = array
+                    .first()
+                    .and_then(Value::as_u64)
+                    .context("Expect a timestamp")? as u32;
 
-                let timestamp = array.first().unwrap().as_u64().unwrap() as u32;
+                let get = |index: usize| value_to_f32(array.get(index)).context("Expect a number");
 
-                let price = array
-                    .get(4)
-                    .unwrap()
-                    .as_str()
-                    .unwrap()
-                    .parse::<f32>()
-                    .unwrap();
+                let candle = Candle {
+                    open: get(1)?,
+                    high: get(2)?,
+                    low: get(3)?,
+                    close: get(4)?,
+                    volume: get(6)?,
+                };
 
-                (timestamp, price)
+                Ok((timestamp, candle))
             })
-            .collect::<BTreeMap<_, _>>())
+            .collect::<color_eyre::Result<BTreeMap<_, _>>>()
+    }
+}
+
+impl PriceSource for Kraken {
+    fn name(&self) -> &str {
+        "kraken"
     }
 
-    pub fn fetch_daily_prices() -> color_eyre::Result<HashMap<String, f32>> {
-        println!("fetch kraken daily");
+    fn get_1mn(&mut self, timestamp: u32) -> color_eyre::Result<Candle> {
+        let Self { cache, config } = self;
 
-        let body: Value = reqwest::blocking::get(
-            "https://api.kraken.com/0/public/OHLC?pair=XBTUSD&interval=1440",
-        )?
-        .json()?;
+        cache.get_1mn("kraken", timestamp, || Self::fetch_1mn_prices(config))
+    }
 
-        Ok(body
-            .as_object()
-            .context("Expect to be an object")?
-            .get("result")
-            .context("Expect object to have result")?
-            .as_object()
-            .context("Expect to be an object")?
-            .get("XXBTZUSD")
-            .context("Expect to have XXBTZUSD")?
-            .as_array()
-            .context("Expect to be an array")?
-            .iter()
-            .map(|value| {
-                let array = value.as_array().unwrap();
+    fn get_1mn_around(&mut self, timestamp: u32, max_gap: u32) -> color_eyre::Result<Around> {
+        let Self { cache, config } = self;
 
-                let date = timestamp_to_naive_date(array.first().unwrap().as_u64().unwrap() as u32)
-                    .to_string();
+        cache.get_1mn_around(timestamp, max_gap, || Self::fetch_1mn_prices(config))
+    }
 
-                let price = array
-                    .get(4)
-                    .unwrap()
-                    .as_str()
-                    .unwrap()
-                    .parse::<f32>()
-                    .unwrap();
+    fn get_daily(&mut self, date: NaiveDate) -> color_eyre::Result<Candle> {
+        let Self { cache, config } = self;
 
-                (date, price)
-            })
-            .collect::<HashMap<_, _>>())
+        cache.get_daily("kraken", date, || Self::fetch_daily_prices(config))
     }
 }
@@ -0,0 +1,301 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, NaiveDate, NaiveTime};
+use color_eyre::eyre::{ContextCompat, Error};
+use itertools::Itertools;
+use serde_json::Value;
+
+use crate::io::{Json, IMPORTS_FOLDER_PATH};
+
+use super::{value_to_f32, Around, Candle, PriceSource};
+
+const ONE_MINUTE_IN_SECONDS: u32 = 60;
+const ONE_DAY_IN_SECONDS: u32 = 24 * 60 * 60;
+
+/// Candles of one local file, the interval is the smallest gap between two of them
+struct CandlesFile {
+    interval: u32,
+    candles: BTreeMap<u32, Candle>,
+}
+
+impl CandlesFile {
+    fn find(&self, timestamp: u32) -> Option<Candle> {
+        self.candles
+            .range(..=timestamp)
+            .next_back()
+            .filter(|(start, _)| timestamp < *start + self.interval)
+            .map(|(_, candle)| *candle)
+    }
+
+    /// Merges the candles of the day, only if they cover it until its end
+    fn find_day(&self, date: NaiveDate) -> Option<Candle> {
+        let start = date.and_time(NaiveTime::MIN).and_utc().timestamp() as u32;
+        let end = start + ONE_DAY_IN_SECONDS;
+
+        let candles = self.candles.range(start..end).collect_vec();
+
+        let (last_start, _) = candles.last()?;
+
+        if **last_start + self.interval < end {
+            return None;
+        }
+
+        Candle::merge(candles.into_iter().map(|(_, candle)| candle))
+    }
+}
+
+/// Reads OHLC candles from `imports/candles/*.{csv,json}`, of any interval
+///
+/// CSV: `timestamp,open,high,low,close[,volume]` with an optional header
+///
+/// JSON: an array of `[timestamp, open, high, low, close, volume?]` or of objects with the same keys
+/// (`time` is accepted for `timestamp`)
+///
+/// Timestamps can be in seconds, in milliseconds, `YYYY-MM-DD` dates or RFC 3339 date times
+///
+/// Only files of 1mn or less are exact prices, coarser intraday ones are only used around a minute
+/// and daily or coarser ones only for daily candles so that the price quality tells which method was used
+#[derive(Default)]
+pub struct LocalCandles {
+    files: Option<Vec<CandlesFile>>,
+}
+
+impl LocalCandles {
+    pub fn folder_path() -> PathBuf {
+        Path::new(IMPORTS_FOLDER_PATH).join("candles")
+    }
+
+    /// A malformed file fails the first call and leaves no candles for the rest of the run
+    fn files(&mut self) -> color_eyre::Result<&Vec<CandlesFile>> {
+        if self.files.is_none() {
+            match Self::read_files() {
+                Ok(files) => {
+                    self.files.replace(files);
+                }
+                Err(report) => {
+                    self.files.replace(vec![]);
+                    return Err(report);
+                }
+            }
+        }
+
+        Ok(self.files.as_ref().unwrap())
+    }
+
+    fn read_files() -> color_eyre::Result<Vec<CandlesFile>> {
+        let folder_path = Self::folder_path();
+
+        if !folder_path.exists() {
+            return Ok(vec![]);
+        }
+
+        println!("local: read candles");
+
+        let mut files = fs::read_dir(folder_path)?
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.is_file())
+            .sorted()
+            .filter_map(|path| {
+                let candles = match path.extension().and_then(|extension| extension.to_str()) {
+                    Some("csv") => Self::read_csv(&path),
+                    Some("json") => Self::read_json(&path),
+                    _ => return None,
+                };
+
+                Some(candles.map_err(|report| {
+                    Error::msg(format!(
+                        "Couldn't read candles file {}: {report}",
+                        path.display()
+                    ))
+                }))
+            })
+            .collect::<color_eyre::Result<Vec<_>>>()?
+            .into_iter()
+            .filter(|candles| !candles.is_empty())
+            .map(|candles| {
+                let interval = candles
+                    .keys()
+                    .tuple_windows()
+                    .map(|(previous, next)| next - previous)
+                    .min()
+                    .unwrap_or(ONE_DAY_IN_SECONDS);
+
+                CandlesFile { interval, candles }
+            })
+            .collect_vec();
+
+        // Most precise first
+        files.sort_by_key(|file| file.interval);
+
+        Ok(files)
+    }
+
+    fn read_csv(path: &Path) -> color_eyre::Result<BTreeMap<u32, Candle>> {
+        Ok(fs::read_to_string(path)?
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .filter_map(|line| {
+                let cells = line.split([',', ';']).map(|cell| cell.trim()).collect_vec();
+
+                let timestamp = Self::parse_timestamp(&Value::from(*cells.first()?))?;
+
+                let get = |index: usize| cells.get(index).and_then(|cell| cell.parse::<f32>().ok());
+
+                // Header or malformed line
+                let candle = Candle {
+                    open: get(1)?,
+                    high: get(2)?,
+                    low: get(3)?,
+                    close: get(4)?,
+                    volume: get(5).unwrap_or_default(),
+                };
+
+                Some((timestamp, candle))
+            })
+            .collect())
+    }
+
+    fn read_json(path: &Path) -> color_eyre::Result<BTreeMap<u32, Candle>> {
+        let json: Value = Json::import(path.to_str().unwrap())?;
+
+        json.as_array()
+            .context("Expect to be an array")?
+            .iter()
+            .map(|value| {
+                let (timestamp, candle) = if let Some(array) = value.as_array() {
+                    (
+                        array.first(),
+                        Candle {
+                            open: value_to_f32(array.get(1)).context("Expect an open")?,
+                            high: value_to_f32(array.get(2)).context("Expect a high")?,
+                            low: value_to_f32(array.get(3)).context("Expect a low")?,
+                            close: value_to_f32(array.get(4)).context("Expect a close")?,
+                            volume: value_to_f32(array.get(5)).unwrap_or_default(),
+                        },
+                    )
+                } else if let Some(object) = value.as_object() {
+                    (
+                        object.get("timestamp").or_else(|| object.get("time")),
+                        Candle {
+                            open: value_to_f32(object.get("open")).context("Expect an open")?,
+                            high: value_to_f32(object.get("high")).context("Expect a high")?,
+                            low: value_to_f32(object.get("low")).context("Expect a low")?,
+                            close: value_to_f32(object.get("close")).context("Expect a close")?,
+                            volume: value_to_f32(object.get("volume")).unwrap_or_default(),
+                        },
+                    )
+                } else {
+                    return Err(Error::msg("Expect candle to be an array or an object"));
+                };
+
+                let timestamp = timestamp
+                    .and_then(Self::parse_timestamp)
+                    .context("Expect a valid timestamp")?;
+
+                Ok((timestamp, candle))
+            })
+            .collect()
+    }
+
+    fn parse_timestamp(value: &Value) -> Option<u32> {
+        let from_number = |number: u64| {
+            // Milliseconds
+            if number > 100_000_000_000 {
+                (number / 1000) as u32
+            } else {
+                number as u32
+            }
+        };
+
+        if let Some(number) = value.as_u64() {
+            return Some(from_number(number));
+        }
+
+        let s = value.as_str()?;
+
+        if let Ok(number) = s.parse::<u64>() {
+            return Some(from_number(number));
+        }
+
+        if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+            return Some(date.and_time(NaiveTime::MIN).and_utc().timestamp() as u32);
+        }
+
+        DateTime::parse_from_rfc3339(s)
+            .ok()
+            .map(|date_time| date_time.timestamp() as u32)
+    }
+}
+
+impl PriceSource for LocalCandles {
+    fn name(&self) -> &str {
+        "local"
+    }
+
+    fn get_1mn(&mut self, timestamp: u32) -> color_eyre::Result<Candle> {
+        self.files()?
+            .iter()
+            .filter(|file| file.interval <= ONE_MINUTE_IN_SECONDS)
+            .find_map(|file| file.find(timestamp))
+            .ok_or(Error::msg("Couldn't find timestamp in local candles"))
+    }
+
+    /// From the most precise intraday file that has candles around
+    fn get_1mn_around(&mut self, timestamp: u32, max_gap: u32) -> color_eyre::Result<Around> {
+        Ok(self
+            .files()?
+            .iter()
+            .filter(|file| file.interval < ONE_DAY_IN_SECONDS)
+            .map(|file| Around::find(&file.candles, timestamp, max_gap))
+            .find(|around| !around.is_empty())
+            .unwrap_or_default())
+    }
+
+    fn get_daily(&mut self, date: NaiveDate) -> color_eyre::Result<Candle> {
+        self.files()?
+            .iter()
+            .find_map(|file| file.find_day(date))
+            .ok_or(Error::msg("Couldn't find date in local candles"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(interval: u32, start: u32, closes: &[f32]) -> CandlesFile {
+        CandlesFile {
+            interval,
+            candles: closes
+                .iter()
+                .enumerate()
+                .map(|(index, close)| (start + index as u32 * interval, Candle::from_close(*close)))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn only_uses_daily_candles_for_days() {
+        let date = NaiveDate::from_ymd_opt(2020, 1, 2).unwrap();
+        let start = date.and_time(NaiveTime::MIN).and_utc().timestamp() as u32;
+
+        let mut local = LocalCandles {
+            files: Some(vec![file(ONE_DAY_IN_SECONDS, start, &[7000.0])]),
+        };
+
+        assert!(local.get_1mn(start + 600).is_err());
+        assert!(local.get_1mn_around(start + 600, 3600).unwrap().is_empty());
+        assert_eq!(local.get_daily(date).unwrap().close, 7000.0);
+
+        let mut local = LocalCandles {
+            files: Some(vec![file(ONE_MINUTE_IN_SECONDS, start, &[7000.0, 7001.0])]),
+        };
+
+        assert_eq!(local.get_1mn(start + 60).unwrap().close, 7001.0);
+    }
+}
@@ -1,5 +1,29 @@
+mod aggregation;
+mod anchors;
 mod binance;
+mod bitfinex;
+mod bitstamp;
+mod candle;
+mod coinbase;
+mod config;
+mod currency;
+mod http;
 mod kraken;
+mod local;
+mod source;
+mod store;
 
+pub use aggregation::*;
+pub use anchors::*;
 pub use binance::*;
+pub use bitfinex::*;
+pub use bitstamp::*;
+pub use candle::*;
+pub use coinbase::*;
+pub use config::*;
+pub use currency::*;
+pub use http::*;
 pub use kraken::*;
+pub use local::*;
+pub use source::*;
+pub use store::*;
@@ -0,0 +1,225 @@
+use std::{
+    collections::BTreeMap,
+    ops::RangeInclusive,
+    time::{Duration, Instant},
+};
+
+use chrono::NaiveDate;
+use color_eyre::eyre::Error;
+
+use super::{Candle, CandleStore};
+
+pub trait PriceSource {
+    fn name(&self) -> &str;
+
+    /// `timestamp` is the start of the minute
+    fn get_1mn(&mut self, timestamp: u32) -> color_eyre::Result<Candle>;
+
+    fn get_daily(&mut self, date: NaiveDate) -> color_eyre::Result<Candle>;
+
+    /// Closest 1mn candles before and after `timestamp`, at most `max_gap` seconds away
+    fn get_1mn_around(&mut self, timestamp: u32, max_gap: u32) -> color_eyre::Result<Around>;
+}
+
+/// Candles surrounding a timestamp with their own timestamps
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Around {
+    pub before: Option<(u32, Candle)>,
+    pub after: Option<(u32, Candle)>,
+}
+
+impl Around {
+    pub fn find(candles: &BTreeMap<u32, Candle>, timestamp: u32, max_gap: u32) -> Self {
+        Self {
+            before: candles
+                .range(timestamp.saturating_sub(max_gap)..=timestamp)
+                .next_back()
+                .map(|(timestamp, candle)| (*timestamp, *candle)),
+            after: candles
+                .range(timestamp..=timestamp.saturating_add(max_gap))
+                .next()
+                .map(|(timestamp, candle)| (*timestamp, *candle)),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.before.is_none() && self.after.is_none()
+    }
+}
+
+/// Delay before fetching again after a failure, blocks would otherwise all wait for the retries of an
+/// unreachable source
+const FETCH_RETRY_DELAY: Duration = Duration::from_secs(10 * 60);
+
+/// Lazily fetched candles of a source, kept for the whole run
+///
+/// When created with `stored`, 1mn candles are first looked up in the source's `CandleStore`
+/// and fetched ones are merged into it
+#[derive(Default)]
+pub struct CandlesCache {
+    store: Option<CandleStore>,
+    fetched_1mn: bool,
+    failed_1mn_at: Option<Instant>,
+    candles_1mn: Option<BTreeMap<u32, Candle>>,
+    candles_daily: Option<BTreeMap<NaiveDate, Candle>>,
+}
+
+impl CandlesCache {
+    pub fn stored(name: &str) -> Self {
+        Self {
+            store: Some(CandleStore::new(name)),
+            ..Default::default()
+        }
+    }
+
+    /// Only fetches if `has` is false with what's already known, successfully at most once per run and
+    /// not within `FETCH_RETRY_DELAY` of a failure, `timestamps` being the range `has` looks at
+    fn candles_1mn<F, H>(
+        &mut self,
+        timestamps: RangeInclusive<u32>,
+        has: H,
+        fetch: F,
+    ) -> color_eyre::Result<&BTreeMap<u32, Candle>>
+    where
+        F: FnOnce() -> color_eyre::Result<BTreeMap<u32, Candle>>,
+        H: Fn(&BTreeMap<u32, Candle>) -> bool,
+    {
+        let candles = self.candles_1mn.get_or_insert_with(BTreeMap::default);
+
+        if let Some(store) = self.store.as_mut() {
+            store.load(timestamps, candles);
+        }
+
+        let store = &self.store;
+
+        let can_fetch = self
+            .failed_1mn_at
+            .is_none_or(|failed_at| failed_at.elapsed() >= FETCH_RETRY_DELAY);
+
+        if !self.fetched_1mn && can_fetch && !has(candles) {
+            let fetched = fetch().inspect_err(|_| {
+                self.failed_1mn_at.replace(Instant::now());
+            })?;
+
+            self.fetched_1mn = true;
+
+            if let Some(store) = store {
+                if let Err(report) = store.merge(&fetched) {
+                    println!("Couldn't store fetched candles: {report}");
+                }
+            }
+
+            candles.extend(fetched);
+        }
+
+        Ok(candles)
+    }
+
+    pub fn get_1mn<F>(&mut self, name: &str, timestamp: u32, fetch: F) -> color_eyre::Result<Candle>
+    where
+        F: FnOnce() -> color_eyre::Result<BTreeMap<u32, Candle>>,
+    {
+        self.candles_1mn(
+            timestamp..=timestamp,
+            |candles| candles.contains_key(&timestamp),
+            fetch,
+        )?
+        .get(&timestamp)
+        .cloned()
+        .ok_or(Error::msg(format!("Couldn't find timestamp in 1mn {name}")))
+    }
+
+    pub fn get_1mn_around<F>(
+        &mut self,
+        timestamp: u32,
+        max_gap: u32,
+        fetch: F,
+    ) -> color_eyre::Result<Around>
+    where
+        F: FnOnce() -> color_eyre::Result<BTreeMap<u32, Candle>>,
+    {
+        let has =
+            |candles: &BTreeMap<u32, Candle>| !Around::find(candles, timestamp, max_gap).is_empty();
+
+        Ok(Around::find(
+            self.candles_1mn(
+                timestamp.saturating_sub(max_gap)..=timestamp.saturating_add(max_gap),
+                has,
+                fetch,
+            )?,
+            timestamp,
+            max_gap,
+        ))
+    }
+
+    pub fn get_daily<F>(
+        &mut self,
+        name: &str,
+        date: NaiveDate,
+        fetch: F,
+    ) -> color_eyre::Result<Candle>
+    where
+        F: FnOnce() -> color_eyre::Result<BTreeMap<NaiveDate, Candle>>,
+    {
+        if self.candles_daily.is_none() {
+            self.candles_daily.replace(fetch()?);
+        }
+
+        self.candles_daily
+            .as_ref()
+            .unwrap()
+            .get(&date)
+            .cloned()
+            .ok_or(Error::msg(format!("Couldn't find date in daily {name}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use color_eyre::eyre::eyre;
+
+    use super::*;
+
+    #[test]
+    fn fetches_again_a_while_after_a_failure() {
+        let mut cache = CandlesCache::default();
+
+        let fetches = Cell::new(0);
+
+        let fetch = |result: color_eyre::Result<BTreeMap<u32, Candle>>| {
+            || {
+                fetches.set(fetches.get() + 1);
+                result
+            }
+        };
+
+        assert!(cache
+            .get_1mn("test", 60, fetch(Err(eyre!("Down"))))
+            .is_err());
+        assert_eq!(fetches.get(), 1);
+
+        // Too soon
+        let candles = BTreeMap::from([(60, Candle::from_close(1.0))]);
+
+        assert!(cache
+            .get_1mn("test", 60, fetch(Ok(candles.clone())))
+            .is_err());
+        assert_eq!(fetches.get(), 1);
+
+        cache.failed_1mn_at = Instant::now().checked_sub(FETCH_RETRY_DELAY);
+
+        assert_eq!(
+            cache.get_1mn("test", 60, fetch(Ok(candles))).unwrap().close,
+            1.0
+        );
+        assert_eq!(fetches.get(), 2);
+
+        // Fetched once for the run
+        assert!(cache
+            .get_1mn("test", 120, fetch(Ok(BTreeMap::default())))
+            .is_err());
+        assert_eq!(fetches.get(), 2);
+    }
+}
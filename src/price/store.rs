@@ -0,0 +1,179 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    ops::RangeInclusive,
+    path::Path,
+};
+
+use chrono::{Datelike, Months};
+use itertools::Itertools;
+
+use crate::{
+    io::{Json, PRICE_FOLDER_PATH},
+    utils::timestamp_to_naive_date,
+};
+
+use super::Candle;
+
+/// Every 1mn candle ever fetched from a source, kept on disk in `candles/{source}/{YYYY-MM}.json` of
+/// the price folder
+///
+/// Sources like Kraken only return the last hours, storing what was fetched allows later runs to still find prices
+/// of blocks that were parsed long after their candles were available
+pub struct CandleStore {
+    path: String,
+    /// Months already read, each file is read at most once per run
+    loaded_months: BTreeSet<String>,
+}
+
+impl CandleStore {
+    pub fn new(name: &str) -> Self {
+        Self::at(format!("{PRICE_FOLDER_PATH}/candles/{name}"))
+    }
+
+    fn at(path: String) -> Self {
+        Self {
+            path,
+            loaded_months: BTreeSet::default(),
+        }
+    }
+
+    /// Adds the stored candles of the months of `timestamps` which weren't read yet to `candles`,
+    /// without overwriting the ones already there
+    pub fn load(&mut self, timestamps: RangeInclusive<u32>, candles: &mut BTreeMap<u32, Candle>) {
+        Self::months(timestamps).into_iter().for_each(|month| {
+            if !self.loaded_months.insert(month.clone()) {
+                return;
+            }
+
+            let path = format!("{}/{month}.json", self.path);
+
+            if !Path::new(&path).exists() {
+                return;
+            }
+
+            Json::import::<BTreeMap<u32, Candle>>(&path)
+                .unwrap_or_else(|report| {
+                    println!("Couldn't read stored candles {path}: {report}");
+                    BTreeMap::default()
+                })
+                .into_iter()
+                .for_each(|(timestamp, candle)| {
+                    candles.entry(timestamp).or_insert(candle);
+                });
+        });
+    }
+
+    fn months(timestamps: RangeInclusive<u32>) -> Vec<String> {
+        let end = timestamp_to_naive_date(*timestamps.end());
+
+        let mut month = timestamp_to_naive_date(*timestamps.start())
+            .with_day(1)
+            .unwrap();
+
+        let mut months = vec![];
+
+        while month <= end {
+            months.push(month.format("%Y-%m").to_string());
+            month = month + Months::new(1);
+        }
+
+        months
+    }
+
+    /// Merges `candles` in the stored ones, newly fetched candles overwrite stored ones of the same minute
+    pub fn merge(&self, candles: &BTreeMap<u32, Candle>) -> color_eyre::Result<()> {
+        if candles.is_empty() {
+            return Ok(());
+        }
+
+        fs::create_dir_all(&self.path)?;
+
+        candles
+            .iter()
+            .group_by(|(timestamp, _)| {
+                timestamp_to_naive_date(**timestamp)
+                    .format("%Y-%m")
+                    .to_string()
+            })
+            .into_iter()
+            .try_for_each(|(month, candles)| {
+                let path = format!("{}/{month}.json", self.path);
+
+                let mut stored = if Path::new(&path).exists() {
+                    Json::import::<BTreeMap<u32, Candle>>(&path)?
+                } else {
+                    BTreeMap::default()
+                };
+
+                stored.extend(candles.map(|(timestamp, candle)| (*timestamp, *candle)));
+
+                Json::export(&path, &stored)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_candles_by_month() {
+        let path = std::env::temp_dir().join(format!("parser/{}/candles", std::process::id()));
+
+        let _ = fs::remove_dir_all(&path);
+
+        let store = CandleStore::at(path.to_str().unwrap().to_owned());
+
+        let candle = |close: f32| Candle::from_close(close);
+
+        // 2024-01-31 23:59 and 2024-02-01 00:00
+        store
+            .merge(&BTreeMap::from([
+                (1706745540, candle(1.0)),
+                (1706745600, candle(2.0)),
+            ]))
+            .unwrap();
+
+        store
+            .merge(&BTreeMap::from([
+                (1706745600, candle(3.0)),
+                (1706745660, candle(4.0)),
+            ]))
+            .unwrap();
+
+        let mut stored = BTreeMap::default();
+
+        CandleStore::at(store.path.clone()).load(1706745540..=1706745660, &mut stored);
+
+        assert_eq!(
+            stored,
+            BTreeMap::from([
+                (1706745540, candle(1.0)),
+                (1706745600, candle(3.0)),
+                (1706745660, candle(4.0)),
+            ])
+        );
+
+        let mut store = CandleStore::at(store.path);
+
+        // February only
+        let mut stored = BTreeMap::from([(1706745660, candle(5.0))]);
+
+        store.load(1706745600..=1706745660, &mut stored);
+
+        assert_eq!(
+            stored,
+            BTreeMap::from([(1706745600, candle(3.0)), (1706745660, candle(5.0))])
+        );
+
+        // Already read
+        stored.clear();
+
+        store.load(1706745600..=1706745600, &mut stored);
+
+        assert!(stored.is_empty());
+
+        fs::remove_dir_all(&store.path).unwrap();
+    }
+}
@@ -1,6 +1,6 @@
-use std::{fmt::Debug, fs, io};
+use std::{fmt::Debug, fs, io, path::Path};
 
-use crate::io::{Binary, OUTPUTS_FOLDER_PATH};
+use crate::io::{Binary, Checksum, OUTPUTS_FOLDER_PATH};
 
 // https://github.com/djkoloski/rust_serialization_benchmark
 pub trait AnyState
@@ -41,5 +41,13 @@ where
         Binary::export(&Self::full_path(), self)
     }
 
+    /// A state which was never exported isn't damaged
+    fn is_damaged() -> bool {
+        let path = Self::full_path();
+
+        Path::new(&path).exists()
+            && Checksum::verify(&path).is_damaged(|| Binary::import::<Self>(&path))
+    }
+
     fn clear(&mut self);
 }
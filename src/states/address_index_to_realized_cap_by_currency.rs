@@ -0,0 +1,189 @@
+use std::collections::BTreeMap;
+
+use crate::{parse::AddressData, price::Currency};
+
+use super::AnyState;
+
+use derive_deref::{Deref, DerefMut};
+use savefile_derive::Savefile;
+
+/// Realized caps of the non empty addresses in each currency, by code then by address index, in
+/// sats times cents of the currency like `AddressData::realized_cap`
+#[derive(Default, Deref, DerefMut, Debug, Savefile)]
+pub struct AddressIndexToRealizedCapByCurrency(BTreeMap<String, BTreeMap<u32, u128>>);
+
+impl AddressIndexToRealizedCapByCurrency {
+    /// Keeps the currencies given only, the new ones starting without any realized cap
+    pub fn init(&mut self, currencies: &[&Currency]) {
+        self.0
+            .retain(|code, _| currencies.iter().any(|currency| &currency.code == code));
+
+        currencies.iter().for_each(|currency| {
+            self.0.entry(currency.code.to_owned()).or_default();
+        });
+    }
+
+    /// Realized cap of the address in each currency, by code
+    pub fn get_all(&self, address_index: u32) -> BTreeMap<String, u128> {
+        self.0
+            .iter()
+            .map(|(code, realized_caps)| {
+                (
+                    code.to_owned(),
+                    realized_caps
+                        .get(&address_index)
+                        .cloned()
+                        .unwrap_or_default(),
+                )
+            })
+            .collect()
+    }
+
+    /// `price` is called with each currency and returns the price of the received sats in it
+    pub fn receive(
+        &mut self,
+        address_index: u32,
+        sat_amount: u64,
+        currencies: &[&Currency],
+        price: impl Fn(&Currency) -> f64,
+    ) {
+        currencies.iter().for_each(|currency| {
+            let realized_cap = self
+                .0
+                .get_mut(&currency.code)
+                .unwrap()
+                .entry(address_index)
+                .or_default();
+
+            AddressData::receive_realized_cap(realized_cap, sat_amount, price(currency));
+        });
+    }
+
+    /// Returns the realized profit (or loss if negative) in each currency, by code, `amount` being the
+    /// one of the address before spending
+    pub fn spend(
+        &mut self,
+        address_index: u32,
+        amount: u64,
+        sat_amount: u64,
+        currencies: &[&Currency],
+        price: impl Fn(&Currency) -> f64,
+    ) -> Vec<(String, f64)> {
+        currencies
+            .iter()
+            .map(|currency| {
+                let realized_caps = self.0.get_mut(&currency.code).unwrap();
+
+                let realized_cap = realized_caps.get_mut(&address_index).unwrap();
+
+                let realized_profit_or_loss = AddressData::spend_realized_cap(
+                    realized_cap,
+                    amount,
+                    sat_amount,
+                    price(currency),
+                );
+
+                (currency.code.to_owned(), realized_profit_or_loss)
+            })
+            .collect()
+    }
+
+    /// `amount` is the one of the address before the removal
+    pub fn remove_unspendable(&mut self, address_index: u32, amount: u64, sat_amount: u64) {
+        self.0.values_mut().for_each(|realized_caps| {
+            AddressData::remove_unspendable_realized_cap(
+                realized_caps.get_mut(&address_index).unwrap(),
+                amount,
+                sat_amount,
+            );
+        });
+    }
+
+    /// Once the address is empty
+    pub fn remove(&mut self, address_index: u32) {
+        self.0.values_mut().for_each(|realized_caps| {
+            realized_caps.remove(&address_index);
+        });
+    }
+}
+
+impl AnyState for AddressIndexToRealizedCapByCurrency {
+    fn name<'a>() -> &'a str {
+        "address_index_to_realized_cap_by_currency"
+    }
+
+    fn clear(&mut self) {
+        self.0
+            .values_mut()
+            .for_each(|realized_caps| realized_caps.clear());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::parse::AddressType;
+
+    use super::*;
+
+    fn currency(code: &str, rate: f64) -> Currency {
+        Currency::new(
+            code.to_owned(),
+            BTreeMap::from([(NaiveDate::from_ymd_opt(2020, 1, 1).unwrap(), rate)]),
+        )
+    }
+
+    #[test]
+    fn follows_the_dollar_realized_cap_in_each_currency() {
+        let eur = currency("eur", 0.5);
+        let jpy = currency("jpy", 100.0);
+        let currencies = [&eur, &jpy];
+
+        let date = NaiveDate::from_ymd_opt(2020, 1, 1).unwrap();
+        let price = |dollars: f64| move |currency: &Currency| currency.convert(dollars, date);
+
+        let mut realized_caps = AddressIndexToRealizedCapByCurrency::default();
+        realized_caps.init(&currencies);
+
+        let mut address_data = AddressData::new(AddressType::P2WPKH);
+
+        address_data.receive(100_000_000, 10_000.0);
+        realized_caps.receive(7, 100_000_000, &currencies, price(10_000.0));
+
+        address_data.receive(100_000_000, 20_000.0);
+        realized_caps.receive(7, 100_000_000, &currencies, price(20_000.0));
+
+        let amount = address_data.amount;
+        let profit = address_data.spend(100_000_000, 10_000.0);
+        let profits = realized_caps.spend(7, amount, 100_000_000, &currencies, price(10_000.0));
+
+        assert_eq!(
+            profits,
+            vec![
+                ("eur".to_owned(), profit * 0.5),
+                ("jpy".to_owned(), profit * 100.0)
+            ]
+        );
+
+        assert_eq!(
+            realized_caps.get_all(7),
+            BTreeMap::from([
+                ("eur".to_owned(), address_data.realized_cap / 2),
+                ("jpy".to_owned(), address_data.realized_cap * 100),
+            ])
+        );
+
+        realized_caps.remove(7);
+
+        assert_eq!(
+            realized_caps.get_all(7),
+            BTreeMap::from([("eur".to_owned(), 0), ("jpy".to_owned(), 0)])
+        );
+
+        // A currency which isn't configured anymore is dropped
+        realized_caps.init(&[&eur]);
+
+        assert_eq!(realized_caps.keys().collect::<Vec<_>>(), vec!["eur"]);
+    }
+}
@@ -17,6 +17,7 @@ impl AddressCohortDurableStates {
         amount: u64,
         utxo_count: usize,
         mean_price_paid_in_cents: u64,
+        realized_cap: u128,
         split_sat_amount: &LiquiditySplitResult,
         split_utxo_count: &LiquiditySplitResult,
     ) {
@@ -24,24 +25,33 @@ impl AddressCohortDurableStates {
 
         self.split
             .all
-            .increment(amount, utxo_count, mean_price_paid_in_cents);
+            .increment(amount, utxo_count, mean_price_paid_in_cents, realized_cap);
+
+        let illiquid_sat_amount = split_sat_amount.illiquid.round() as u64;
 
         self.split.illiquid.increment(
-            split_sat_amount.illiquid.round() as u64,
+            illiquid_sat_amount,
             split_utxo_count.illiquid.round() as usize,
             mean_price_paid_in_cents,
+            Self::split_realized_cap(realized_cap, amount, illiquid_sat_amount),
         );
 
+        let liquid_sat_amount = split_sat_amount.liquid.round() as u64;
+
         self.split.liquid.increment(
-            split_sat_amount.liquid.round() as u64,
+            liquid_sat_amount,
             split_utxo_count.liquid.round() as usize,
             mean_price_paid_in_cents,
+            Self::split_realized_cap(realized_cap, amount, liquid_sat_amount),
         );
 
+        let highly_liquid_sat_amount = split_sat_amount.highly_liquid.round() as u64;
+
         self.split.highly_liquid.increment(
-            split_sat_amount.highly_liquid.round() as u64,
+            highly_liquid_sat_amount,
             split_utxo_count.highly_liquid.round() as usize,
             mean_price_paid_in_cents,
+            Self::split_realized_cap(realized_cap, amount, highly_liquid_sat_amount),
         );
     }
 
@@ -50,6 +60,7 @@ impl AddressCohortDurableStates {
         amount: u64,
         utxo_count: usize,
         mean_price_paid_in_cents: u64,
+        realized_cap: u128,
         split_sat_amount: &LiquiditySplitResult,
         split_utxo_count: &LiquiditySplitResult,
     ) {
@@ -57,31 +68,45 @@ impl AddressCohortDurableStates {
 
         self.split
             .all
-            .decrement(amount, utxo_count, mean_price_paid_in_cents);
+            .decrement(amount, utxo_count, mean_price_paid_in_cents, realized_cap);
+
+        let illiquid_sat_amount = split_sat_amount.illiquid.round() as u64;
 
         self.split.illiquid.decrement(
-            split_sat_amount.illiquid.round() as u64,
+            illiquid_sat_amount,
             split_utxo_count.illiquid.round() as usize,
             mean_price_paid_in_cents,
+            Self::split_realized_cap(realized_cap, amount, illiquid_sat_amount),
         );
 
+        let liquid_sat_amount = split_sat_amount.liquid.round() as u64;
+
         self.split.liquid.decrement(
-            split_sat_amount.liquid.round() as u64,
+            liquid_sat_amount,
             split_utxo_count.liquid.round() as usize,
             mean_price_paid_in_cents,
+            Self::split_realized_cap(realized_cap, amount, liquid_sat_amount),
         );
 
+        let highly_liquid_sat_amount = split_sat_amount.highly_liquid.round() as u64;
+
         self.split.highly_liquid.decrement(
-            split_sat_amount.highly_liquid.round() as u64,
+            highly_liquid_sat_amount,
             split_utxo_count.highly_liquid.round() as usize,
             mean_price_paid_in_cents,
+            Self::split_realized_cap(realized_cap, amount, highly_liquid_sat_amount),
         );
     }
 
+    /// Share of the realized cap of `sat_amount` out of `amount`, deterministic so that decrements cancel increments
+    fn split_realized_cap(realized_cap: u128, amount: u64, sat_amount: u64) -> u128 {
+        realized_cap * sat_amount as u128 / amount as u128
+    }
+
     pub fn compute_one_shot_states(
         &self,
-        block_price: f32,
-        date_price: Option<f32>,
+        block_price: f64,
+        date_price: Option<f64>,
     ) -> SplitByLiquidity<OneShotStates> {
         thread::scope(|scope| {
             let all_handle = scope.spawn(|| {
@@ -1,4 +1,4 @@
-use std::thread;
+use std::{collections::BTreeMap, thread};
 
 use derive_deref::{Deref, DerefMut};
 
@@ -19,7 +19,29 @@ impl AddressCohortsDurableStates {
 
         address_index_to_address_data
             .iter()
-            .for_each(|(_, address_data)| s.increment(address_data));
+            .for_each(|(_, address_data)| s.increment(address_data, address_data.realized_cap));
+
+        s
+    }
+
+    /// Same as `init` but with the realized caps of another currency
+    pub fn init_in_currency(
+        address_index_to_address_data: &AddressIndexToAddressData,
+        address_index_to_realized_cap: &BTreeMap<u32, u128>,
+    ) -> Self {
+        let mut s = Self::default();
+
+        address_index_to_address_data
+            .iter()
+            .for_each(|(address_index, address_data)| {
+                s.increment(
+                    address_data,
+                    address_index_to_realized_cap
+                        .get(address_index)
+                        .cloned()
+                        .unwrap_or_default(),
+                )
+            });
 
         s
     }
@@ -29,21 +51,40 @@ impl AddressCohortsDurableStates {
         address_realized_data: &AddressRealizedData,
         current_address_data: &AddressData,
     ) {
-        self.decrement(&address_realized_data.initial_address_data);
-        self.increment(current_address_data);
+        self.iterate_in_currency(
+            address_realized_data,
+            current_address_data,
+            address_realized_data.initial_address_data.realized_cap,
+            current_address_data.realized_cap,
+        );
+    }
+
+    /// Same as `iterate` but with the realized caps of another currency
+    pub fn iterate_in_currency(
+        &mut self,
+        address_realized_data: &AddressRealizedData,
+        current_address_data: &AddressData,
+        initial_realized_cap: u128,
+        current_realized_cap: u128,
+    ) {
+        self.decrement(
+            &address_realized_data.initial_address_data,
+            initial_realized_cap,
+        );
+        self.increment(current_address_data, current_realized_cap);
     }
 
     /// Should always increment using current address data state
-    fn increment(&mut self, address_data: &AddressData) {
-        self._crement(address_data, true)
+    fn increment(&mut self, address_data: &AddressData, realized_cap: u128) {
+        self._crement(address_data, realized_cap, true)
     }
 
     /// Should always decrement using initial address data state
-    fn decrement(&mut self, address_data: &AddressData) {
-        self._crement(address_data, false)
+    fn decrement(&mut self, address_data: &AddressData, realized_cap: u128) {
+        self._crement(address_data, realized_cap, false)
     }
 
-    fn _crement(&mut self, address_data: &AddressData, increment: bool) {
+    fn _crement(&mut self, address_data: &AddressData, realized_cap: u128, increment: bool) {
         let amount = address_data.amount;
         let utxo_count = address_data.outputs_len as usize;
 
@@ -52,8 +93,9 @@ impl AddressCohortsDurableStates {
             return;
         }
 
-        let mean_price_paid_in_cents =
-            convert_price_to_significant_cents(address_data.mean_price_paid);
+        let mean_price_paid_in_cents = convert_price_to_significant_cents(
+            AddressData::compute_mean_price_paid(realized_cap, amount),
+        );
 
         let liquidity_classification = address_data.compute_liquidity_classification();
 
@@ -67,6 +109,7 @@ impl AddressCohortsDurableStates {
                         amount,
                         utxo_count,
                         mean_price_paid_in_cents,
+                        realized_cap,
                         &split_sat_amount_amount,
                         &split_utxo_count,
                     );
@@ -75,6 +118,7 @@ impl AddressCohortsDurableStates {
                         amount,
                         utxo_count,
                         mean_price_paid_in_cents,
+                        realized_cap,
                         &split_sat_amount_amount,
                         &split_utxo_count,
                     )
@@ -84,8 +128,8 @@ impl AddressCohortsDurableStates {
 
     pub fn compute_one_shot_states(
         &mut self,
-        block_price: f32,
-        date_price: Option<f32>,
+        block_price: f64,
+        date_price: Option<f64>,
     ) -> AddressCohortsOneShotStates {
         thread::scope(|scope| {
             let all_handle =
@@ -1,7 +1,7 @@
 use derive_deref::{Deref, DerefMut};
 
 use crate::{
-    parse::{AddressRealizedData, LiquidityClassification, SplitByLiquidity},
+    parse::{AddressData, AddressRealizedData, LiquidityClassification, SplitByLiquidity},
     states::RealizedState,
 };
 
@@ -16,9 +16,22 @@ impl AddressCohortsRealizedStates {
         realized_data: &AddressRealizedData,
         liquidity_classification: &LiquidityClassification,
     ) {
-        let profit = realized_data.profit;
-        let loss = realized_data.loss;
+        self.iterate_profit_and_loss(
+            &realized_data.initial_address_data,
+            realized_data.profit,
+            realized_data.loss,
+            liquidity_classification,
+        );
+    }
 
+    /// Same as `iterate_realized` with the profit and the loss of another currency
+    pub fn iterate_profit_and_loss(
+        &mut self,
+        initial_address_data: &AddressData,
+        profit: f64,
+        loss: f64,
+        liquidity_classification: &LiquidityClassification,
+    ) {
         let split_profit = liquidity_classification.split(profit);
         let split_loss = liquidity_classification.split(loss);
 
@@ -36,6 +49,6 @@ impl AddressCohortsRealizedStates {
                 .iterate(split_profit.highly_liquid, split_loss.highly_liquid);
         };
 
-        self.iterate(&realized_data.initial_address_data, iterate);
+        self.iterate(initial_address_data, iterate);
     }
 }
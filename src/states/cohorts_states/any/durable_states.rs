@@ -3,13 +3,21 @@ use super::{OneShotStates, PriceInCentsToAmount, SupplyState, UTXOState};
 #[derive(Default, Debug)]
 pub struct DurableStates {
     price_in_cents_to_amount: PriceInCentsToAmount,
+    /// In sats times cents, exact unlike the prices in `price_in_cents_to_amount` which are rounded
+    realized_cap: u128,
 
     pub supply_state: SupplyState,
     pub utxo_state: UTXOState,
 }
 
 impl DurableStates {
-    pub fn increment(&mut self, amount: u64, utxo_count: usize, price_in_cents: u64) {
+    pub fn increment(
+        &mut self,
+        amount: u64,
+        utxo_count: usize,
+        price_in_cents: u64,
+        realized_cap: u128,
+    ) {
         if amount == 0 {
             if utxo_count != 0 {
                 unreachable!("Shouldn't be possible")
@@ -21,9 +29,16 @@ impl DurableStates {
         self.utxo_state.increment(utxo_count);
         self.price_in_cents_to_amount
             .increment(price_in_cents, amount);
+        self.realized_cap += realized_cap;
     }
 
-    pub fn decrement(&mut self, amount: u64, utxo_count: usize, price_in_cents: u64) {
+    pub fn decrement(
+        &mut self,
+        amount: u64,
+        utxo_count: usize,
+        price_in_cents: u64,
+        realized_cap: u128,
+    ) {
         if amount == 0 {
             if utxo_count != 0 {
                 unreachable!("Shouldn't be possible")
@@ -35,15 +50,17 @@ impl DurableStates {
         self.utxo_state.decrement(utxo_count);
         self.price_in_cents_to_amount
             .decrement(price_in_cents, amount);
+        self.realized_cap -= realized_cap;
     }
 
     pub fn compute_one_shot_states(
         &self,
-        block_price: f32,
-        date_price: Option<f32>,
+        block_price: f64,
+        date_price: Option<f64>,
     ) -> OneShotStates {
         self.price_in_cents_to_amount.compute_on_shot_states(
             self.supply_state.supply,
+            self.realized_cap,
             block_price,
             date_price,
         )
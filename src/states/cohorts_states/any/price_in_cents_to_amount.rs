@@ -2,7 +2,7 @@ use std::collections::BTreeMap;
 
 use derive_deref::{Deref, DerefMut};
 
-use crate::bitcoin::sats_to_btc;
+use crate::{bitcoin::sats_to_btc_f64, utils::convert_sats_cents_to_dollars};
 
 use super::{OneShotStates, UnrealizedState};
 
@@ -34,11 +34,14 @@ impl PriceInCentsToAmount {
     pub fn compute_on_shot_states(
         &self,
         supply: u64,
-        block_price: f32,
-        date_price: Option<f32>,
+        realized_cap: u128,
+        block_price: f64,
+        date_price: Option<f64>,
     ) -> OneShotStates {
         let mut one_shot_states = OneShotStates::default();
 
+        one_shot_states.price_paid_state.realized_cap = convert_sats_cents_to_dollars(realized_cap);
+
         if date_price.is_some() {
             one_shot_states
                 .unrealized_date_state
@@ -51,16 +54,13 @@ impl PriceInCentsToAmount {
             .for_each(|(mean_price_paid_in_cent, sat_amount)| {
                 processed_amount += sat_amount;
 
-                let mean_price_paid = (*mean_price_paid_in_cent as f32) / 100.0;
+                let mean_price_paid = (*mean_price_paid_in_cent as f64) / 100.0;
 
-                let btc_amount = sats_to_btc(*sat_amount);
+                let btc_amount = sats_to_btc_f64(*sat_amount);
 
-                one_shot_states.price_paid_state.iterate(
-                    mean_price_paid,
-                    btc_amount,
-                    *sat_amount,
-                    supply,
-                );
+                one_shot_states
+                    .price_paid_state
+                    .iterate(mean_price_paid, *sat_amount, supply);
 
                 one_shot_states.unrealized_block_state.iterate(
                     mean_price_paid,
@@ -1,35 +1,36 @@
 #[derive(Default, Debug)]
 pub struct PricePaidState {
-    pub realized_cap: f32,
-
-    pub pp_05p: Option<f32>,
-    pub pp_10p: Option<f32>,
-    pub pp_15p: Option<f32>,
-    pub pp_20p: Option<f32>,
-    pub pp_25p: Option<f32>,
-    pub pp_30p: Option<f32>,
-    pub pp_35p: Option<f32>,
-    pub pp_40p: Option<f32>,
-    pub pp_45p: Option<f32>,
-    pub pp_median: Option<f32>,
-    pub pp_55p: Option<f32>,
-    pub pp_60p: Option<f32>,
-    pub pp_65p: Option<f32>,
-    pub pp_70p: Option<f32>,
-    pub pp_75p: Option<f32>,
-    pub pp_80p: Option<f32>,
-    pub pp_85p: Option<f32>,
-    pub pp_90p: Option<f32>,
-    pub pp_95p: Option<f32>,
+    /// Not computed by `iterate` but from the exact realized cap of the durable states
+    pub realized_cap: f64,
+
+    pub pp_05p: Option<f64>,
+    pub pp_10p: Option<f64>,
+    pub pp_15p: Option<f64>,
+    pub pp_20p: Option<f64>,
+    pub pp_25p: Option<f64>,
+    pub pp_30p: Option<f64>,
+    pub pp_35p: Option<f64>,
+    pub pp_40p: Option<f64>,
+    pub pp_45p: Option<f64>,
+    pub pp_median: Option<f64>,
+    pub pp_55p: Option<f64>,
+    pub pp_60p: Option<f64>,
+    pub pp_65p: Option<f64>,
+    pub pp_70p: Option<f64>,
+    pub pp_75p: Option<f64>,
+    pub pp_80p: Option<f64>,
+    pub pp_85p: Option<f64>,
+    pub pp_90p: Option<f64>,
+    pub pp_95p: Option<f64>,
 
     pub processed_amount: u64,
 }
 
 impl PricePaidState {
-    pub fn iterate(&mut self, price: f32, btc_amount: f32, sat_amount: u64, total_supply: u64) {
+    pub fn iterate(&mut self, price: f64, sat_amount: u64, total_supply: u64) {
         let PricePaidState {
             processed_amount,
-            realized_cap,
+            realized_cap: _,
             pp_05p,
             pp_10p,
             pp_15p,
@@ -51,15 +52,13 @@ impl PricePaidState {
             pp_95p,
         } = self;
 
-        *realized_cap += btc_amount * price;
-
         *processed_amount += sat_amount;
 
         if pp_95p.is_some() {
             return;
         }
 
-        if *processed_amount as f32 >= total_supply as f32 * 0.95 {
+        if *processed_amount as f64 >= total_supply as f64 * 0.95 {
             pp_95p.replace(price);
         }
 
@@ -67,7 +66,7 @@ impl PricePaidState {
             return;
         }
 
-        if *processed_amount as f32 >= total_supply as f32 * 0.9 {
+        if *processed_amount as f64 >= total_supply as f64 * 0.9 {
             pp_90p.replace(price);
         }
 
@@ -75,7 +74,7 @@ impl PricePaidState {
             return;
         }
 
-        if *processed_amount as f32 >= total_supply as f32 * 0.85 {
+        if *processed_amount as f64 >= total_supply as f64 * 0.85 {
             pp_85p.replace(price);
         }
 
@@ -83,7 +82,7 @@ impl PricePaidState {
             return;
         }
 
-        if *processed_amount as f32 >= total_supply as f32 * 0.8 {
+        if *processed_amount as f64 >= total_supply as f64 * 0.8 {
             pp_80p.replace(price);
         }
 
@@ -91,7 +90,7 @@ impl PricePaidState {
             return;
         }
 
-        if *processed_amount as f32 >= total_supply as f32 * 0.75 {
+        if *processed_amount as f64 >= total_supply as f64 * 0.75 {
             pp_75p.replace(price);
         }
 
@@ -99,7 +98,7 @@ impl PricePaidState {
             return;
         }
 
-        if *processed_amount as f32 >= total_supply as f32 * 0.7 {
+        if *processed_amount as f64 >= total_supply as f64 * 0.7 {
             pp_70p.replace(price);
         }
 
@@ -107,7 +106,7 @@ impl PricePaidState {
             return;
         }
 
-        if *processed_amount as f32 >= total_supply as f32 * 0.65 {
+        if *processed_amount as f64 >= total_supply as f64 * 0.65 {
             pp_65p.replace(price);
         }
 
@@ -115,7 +114,7 @@ impl PricePaidState {
             return;
         }
 
-        if *processed_amount as f32 >= total_supply as f32 * 0.6 {
+        if *processed_amount as f64 >= total_supply as f64 * 0.6 {
             pp_60p.replace(price);
         }
 
@@ -123,7 +122,7 @@ impl PricePaidState {
             return;
         }
 
-        if *processed_amount as f32 >= total_supply as f32 * 0.55 {
+        if *processed_amount as f64 >= total_supply as f64 * 0.55 {
             pp_55p.replace(price);
         }
 
@@ -131,7 +130,7 @@ impl PricePaidState {
             return;
         }
 
-        if *processed_amount as f32 >= total_supply as f32 * 0.5 {
+        if *processed_amount as f64 >= total_supply as f64 * 0.5 {
             pp_median.replace(price);
         }
 
@@ -139,7 +138,7 @@ impl PricePaidState {
             return;
         }
 
-        if *processed_amount as f32 >= total_supply as f32 * 0.45 {
+        if *processed_amount as f64 >= total_supply as f64 * 0.45 {
             pp_45p.replace(price);
         }
 
@@ -147,7 +146,7 @@ impl PricePaidState {
             return;
         }
 
-        if *processed_amount as f32 >= total_supply as f32 * 0.4 {
+        if *processed_amount as f64 >= total_supply as f64 * 0.4 {
             pp_40p.replace(price);
         }
 
@@ -155,7 +154,7 @@ impl PricePaidState {
             return;
         }
 
-        if *processed_amount as f32 >= total_supply as f32 * 0.35 {
+        if *processed_amount as f64 >= total_supply as f64 * 0.35 {
             pp_35p.replace(price);
         }
 
@@ -163,7 +162,7 @@ impl PricePaidState {
             return;
         }
 
-        if *processed_amount as f32 >= total_supply as f32 * 0.3 {
+        if *processed_amount as f64 >= total_supply as f64 * 0.3 {
             pp_30p.replace(price);
         }
 
@@ -171,7 +170,7 @@ impl PricePaidState {
             return;
         }
 
-        if *processed_amount as f32 >= total_supply as f32 * 0.25 {
+        if *processed_amount as f64 >= total_supply as f64 * 0.25 {
             pp_25p.replace(price);
         }
 
@@ -179,7 +178,7 @@ impl PricePaidState {
             return;
         }
 
-        if *processed_amount as f32 >= total_supply as f32 * 0.2 {
+        if *processed_amount as f64 >= total_supply as f64 * 0.2 {
             pp_20p.replace(price);
         }
 
@@ -187,7 +186,7 @@ impl PricePaidState {
             return;
         }
 
-        if *processed_amount as f32 >= total_supply as f32 * 0.15 {
+        if *processed_amount as f64 >= total_supply as f64 * 0.15 {
             pp_15p.replace(price);
         }
 
@@ -195,7 +194,7 @@ impl PricePaidState {
             return;
         }
 
-        if *processed_amount as f32 >= total_supply as f32 * 0.1 {
+        if *processed_amount as f64 >= total_supply as f64 * 0.1 {
             pp_10p.replace(price);
         }
 
@@ -203,7 +202,7 @@ impl PricePaidState {
             return;
         }
 
-        if *processed_amount as f32 >= total_supply as f32 * 0.05 {
+        if *processed_amount as f64 >= total_supply as f64 * 0.05 {
             pp_05p.replace(price);
         }
     }
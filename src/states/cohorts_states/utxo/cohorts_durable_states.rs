@@ -4,10 +4,11 @@ use derive_deref::{Deref, DerefMut};
 
 use crate::{
     parse::BlockData,
+    price::Currency,
     states::{DateDataVec, DurableStates},
     utils::{
-        convert_price_to_significant_cents, difference_in_days_between_timestamps,
-        timestamp_to_year,
+        compute_sats_cents, convert_price_to_cents, convert_price_to_significant_cents,
+        difference_in_days_between_timestamps, timestamp_to_year,
     },
 };
 
@@ -17,7 +18,8 @@ use super::{SplitByUTXOCohort, UTXOCohortsOneShotStates};
 pub struct UTXOCohortsDurableStates(SplitByUTXOCohort<DurableStates>);
 
 impl UTXOCohortsDurableStates {
-    pub fn init(date_data_vec: &DateDataVec) -> Self {
+    /// Prices paid are in dollars without a currency
+    pub fn init(date_data_vec: &DateDataVec, currency: Option<&Currency>) -> Self {
         let mut s = Self::default();
 
         if let Some(last_date_data) = date_data_vec.last() {
@@ -27,7 +29,7 @@ impl UTXOCohortsDurableStates {
                 .iter()
                 .flat_map(|date_data| &date_data.blocks)
                 .for_each(|block_data| {
-                    s.iterate(block_data, last_block_data, None);
+                    s.iterate(block_data, last_block_data, None, currency);
                 });
         }
 
@@ -39,6 +41,7 @@ impl UTXOCohortsDurableStates {
         block_data: &BlockData,
         last_block_data: &BlockData,
         previous_last_block_data: Option<&BlockData>,
+        currency: Option<&Currency>,
     ) {
         let amount = block_data.amount;
         let utxo_count = block_data.spendable_outputs as usize;
@@ -48,7 +51,13 @@ impl UTXOCohortsDurableStates {
             return;
         }
 
-        let price_in_cents = convert_price_to_significant_cents(block_data.price);
+        let price = currency.map_or(block_data.price, |currency| {
+            currency.block_price(block_data)
+        });
+
+        let price_in_cents = convert_price_to_significant_cents(price);
+
+        let realized_cap = compute_sats_cents(amount, convert_price_to_cents(price));
 
         let increment_days_old =
             difference_in_days_between_timestamps(block_data.timestamp, last_block_data.timestamp);
@@ -80,7 +89,7 @@ impl UTXOCohortsDurableStates {
                     .filter(|id| !increment_ids.contains(id))
                     .for_each(|id| {
                         self.get_mut(id)
-                            .decrement(amount, utxo_count, price_in_cents)
+                            .decrement(amount, utxo_count, price_in_cents, realized_cap)
                     });
 
                 increment_ids
@@ -88,7 +97,7 @@ impl UTXOCohortsDurableStates {
                     .filter(|id| !decrement_ids.contains(id))
                     .for_each(|id| {
                         self.get_mut(id)
-                            .increment(amount, utxo_count, price_in_cents)
+                            .increment(amount, utxo_count, price_in_cents, realized_cap)
                     });
 
                 return;
@@ -96,14 +105,14 @@ impl UTXOCohortsDurableStates {
         }
 
         self.filtered_apply(&increment_days_old, &increment_year, |state| {
-            state.increment(amount, utxo_count, price_in_cents);
+            state.increment(amount, utxo_count, price_in_cents, realized_cap);
         })
     }
 
     pub fn compute_one_shot_states(
         &mut self,
-        block_price: f32,
-        date_price: Option<f32>,
+        block_price: f64,
+        date_price: Option<f64>,
     ) -> UTXOCohortsOneShotStates {
         thread::scope(|scope| {
             let sth_handle =
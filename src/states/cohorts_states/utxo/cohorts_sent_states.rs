@@ -4,8 +4,9 @@ use derive_deref::{Deref, DerefMut};
 
 use crate::{
     actions::SpentData,
-    bitcoin::sats_to_btc,
+    bitcoin::{sats_to_btc, sats_to_btc_f64},
     parse::BlockPath,
+    price::Currency,
     states::{DateDataVec, InputState, RealizedState},
     utils::{difference_in_days_between_timestamps, timestamp_to_year},
 };
@@ -25,14 +26,15 @@ impl UTXOCohortsSentStates {
     pub fn compute(
         &mut self,
         date_data_vec: &DateDataVec,
-        block_path_to_spent_data: BTreeMap<BlockPath, SpentData>,
-        current_price: f32,
+        block_path_to_spent_data: &BTreeMap<BlockPath, SpentData>,
+        current_price: f64,
+        currency: Option<&Currency>,
     ) {
         if let Some(last_date_data) = date_data_vec.last() {
             let last_block_data = last_date_data.blocks.last().unwrap();
 
             block_path_to_spent_data
-                .into_iter()
+                .iter()
                 .map(|(block_path, data)| {
                     let block_data = date_data_vec
                         .get(block_path.date_index as usize)
@@ -51,22 +53,22 @@ impl UTXOCohortsSentStates {
 
                     let year = timestamp_to_year(block_data.timestamp);
 
-                    let previous_price = block_data.price;
+                    let previous_price = currency.map_or(block_data.price, |currency| {
+                        currency.block_price(block_data)
+                    });
 
                     let btc_spent = sats_to_btc(spent_data.volume);
 
+                    let previous_value = previous_price * sats_to_btc_f64(spent_data.volume);
+                    let current_value = current_price * sats_to_btc_f64(spent_data.volume);
+
                     self.filtered_apply(&days_old, &year, |state| {
                         state.input.iterate(spent_data.count as f32, btc_spent);
 
-                        let previous_dollar_amount = previous_price * btc_spent;
-                        let current_dollar_amount = current_price * btc_spent;
-
-                        if previous_dollar_amount < current_dollar_amount {
-                            state.realized.realized_profit +=
-                                current_dollar_amount - previous_dollar_amount;
-                        } else if current_dollar_amount < previous_dollar_amount {
-                            state.realized.realized_loss +=
-                                previous_dollar_amount - current_dollar_amount;
+                        if previous_value < current_value {
+                            state.realized.realized_profit += current_value - previous_value;
+                        } else if current_value < previous_value {
+                            state.realized.realized_loss += previous_value - current_value;
                         }
                     })
                 })
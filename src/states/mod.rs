@@ -1,32 +1,44 @@
-use std::thread;
+use std::{collections::BTreeMap, thread};
+
+use crate::price::Currency;
 
 mod _trait;
 mod address_index_to_address_data;
+mod address_index_to_realized_cap_by_currency;
 mod cohorts_states;
 mod counters;
 mod date_data_vec;
 mod tx_index_to_tx_data;
 mod txout_index_to_address_index;
+mod txout_index_to_coinjoin_anonset;
 mod txout_index_to_sats;
 
 pub use _trait::*;
 use address_index_to_address_data::*;
+pub use address_index_to_realized_cap_by_currency::*;
 pub use cohorts_states::*;
 use counters::*;
-use date_data_vec::*;
+pub use date_data_vec::*;
 use tx_index_to_tx_data::*;
 use txout_index_to_address_index::*;
+use txout_index_to_coinjoin_anonset::*;
 use txout_index_to_sats::*;
 
 #[derive(Default)]
 pub struct States {
     pub address_index_to_address_data: AddressIndexToAddressData,
+    pub address_index_to_realized_cap_by_currency: AddressIndexToRealizedCapByCurrency,
     pub counters: Counters,
     pub date_data_vec: DateDataVec,
     pub address_cohorts_durable_states: AddressCohortsDurableStates,
+    /// Same as `address_cohorts_durable_states` but with realized caps in each currency, by code
+    pub address_cohorts_durable_states_by_currency: BTreeMap<String, AddressCohortsDurableStates>,
     pub utxo_cohorts_durable_states: UTXOCohortsDurableStates,
+    /// Same as `utxo_cohorts_durable_states` but with prices paid in each currency, by code
+    pub utxo_cohorts_durable_states_by_currency: BTreeMap<String, UTXOCohortsDurableStates>,
     pub tx_index_to_tx_data: TxIndexToTxData,
     pub txout_index_to_address_index: TxoutIndexToAddressIndex,
+    pub txout_index_to_coinjoin_anonset: TxoutIndexToCoinJoinAnonset,
     pub txout_index_to_sats: TxoutIndexToSats,
 }
 
@@ -40,14 +52,25 @@ impl States {
 
         let txout_index_to_address_index_handle = thread::spawn(TxoutIndexToAddressIndex::import);
 
+        let txout_index_to_coinjoin_anonset_handle =
+            thread::spawn(TxoutIndexToCoinJoinAnonset::import);
+
         let date_data_vec_handle = thread::spawn(DateDataVec::import);
 
         let counters = Counters::import()?;
 
+        // Missing until exported once, with a currency its datasets are new and everything is parsed
+        // again from scratch anyway
+        let address_index_to_realized_cap_by_currency =
+            AddressIndexToRealizedCapByCurrency::import().unwrap_or_default();
+
         let date_data_vec = date_data_vec_handle.join().unwrap()?;
 
         let txout_index_to_address_index = txout_index_to_address_index_handle.join().unwrap()?;
 
+        let txout_index_to_coinjoin_anonset =
+            txout_index_to_coinjoin_anonset_handle.join().unwrap()?;
+
         let txout_index_to_sats = txout_index_to_sats_handle.join().unwrap()?;
 
         let tx_index_to_tx_data = tx_index_to_tx_data_handle.join().unwrap()?;
@@ -57,41 +80,118 @@ impl States {
         let address_cohorts_durable_states =
             AddressCohortsDurableStates::init(&address_index_to_address_data);
 
-        let utxo_cohorts_durable_states = UTXOCohortsDurableStates::init(&date_data_vec);
+        let utxo_cohorts_durable_states = UTXOCohortsDurableStates::init(&date_data_vec, None);
 
         Ok(Self {
             address_cohorts_durable_states,
+            address_cohorts_durable_states_by_currency: BTreeMap::default(),
             address_index_to_address_data,
+            address_index_to_realized_cap_by_currency,
             counters,
             date_data_vec,
             tx_index_to_tx_data,
             txout_index_to_address_index,
+            txout_index_to_coinjoin_anonset,
             txout_index_to_sats,
             utxo_cohorts_durable_states,
+            utxo_cohorts_durable_states_by_currency: BTreeMap::default(),
         })
     }
 
+    pub fn init_currencies(&mut self, currencies: &[&Currency]) {
+        self.address_index_to_realized_cap_by_currency
+            .init(currencies);
+
+        self.address_cohorts_durable_states_by_currency = self
+            .address_index_to_realized_cap_by_currency
+            .iter()
+            .map(|(code, address_index_to_realized_cap)| {
+                (
+                    code.to_owned(),
+                    AddressCohortsDurableStates::init_in_currency(
+                        &self.address_index_to_address_data,
+                        address_index_to_realized_cap,
+                    ),
+                )
+            })
+            .collect();
+
+        self.utxo_cohorts_durable_states_by_currency = currencies
+            .iter()
+            .map(|currency| {
+                (
+                    currency.code.to_owned(),
+                    UTXOCohortsDurableStates::init(&self.date_data_vec, Some(currency)),
+                )
+            })
+            .collect();
+    }
+
     pub fn reset(&mut self) {
         println!("Reseting all states...");
 
         let _ = self.address_index_to_address_data.reset();
+        let _ = self.address_index_to_realized_cap_by_currency.reset();
         let _ = self.counters.reset();
         let _ = self.date_data_vec.reset();
         let _ = self.tx_index_to_tx_data.reset();
         let _ = self.txout_index_to_address_index.reset();
+        let _ = self.txout_index_to_coinjoin_anonset.reset();
         let _ = self.txout_index_to_sats.reset();
 
         self.address_cohorts_durable_states = AddressCohortsDurableStates::default();
+        self.address_cohorts_durable_states_by_currency
+            .values_mut()
+            .for_each(|states| *states = AddressCohortsDurableStates::default());
         self.utxo_cohorts_durable_states = UTXOCohortsDurableStates::default();
+        self.utxo_cohorts_durable_states_by_currency
+            .values_mut()
+            .for_each(|states| *states = UTXOCohortsDurableStates::default());
+    }
+
+    /// Names of the states which can't be trusted, checked one after the other to import at most one at a time
+    pub fn find_damaged() -> Vec<&'static str> {
+        [
+            (
+                AddressIndexToAddressData::name(),
+                AddressIndexToAddressData::is_damaged as fn() -> bool,
+            ),
+            (
+                AddressIndexToRealizedCapByCurrency::name(),
+                AddressIndexToRealizedCapByCurrency::is_damaged,
+            ),
+            (Counters::name(), Counters::is_damaged),
+            (DateDataVec::name(), DateDataVec::is_damaged),
+            (TxIndexToTxData::name(), TxIndexToTxData::is_damaged),
+            (
+                TxoutIndexToAddressIndex::name(),
+                TxoutIndexToAddressIndex::is_damaged,
+            ),
+            (
+                TxoutIndexToCoinJoinAnonset::name(),
+                TxoutIndexToCoinJoinAnonset::is_damaged,
+            ),
+            (TxoutIndexToSats::name(), TxoutIndexToSats::is_damaged),
+        ]
+        .into_iter()
+        .filter(|(_, is_damaged)| is_damaged())
+        .map(|(name, _)| name)
+        .collect()
     }
 
     pub fn export(&self) -> color_eyre::Result<()> {
         thread::scope(|s| {
             s.spawn(|| self.address_index_to_address_data.export().unwrap());
+            s.spawn(|| {
+                self.address_index_to_realized_cap_by_currency
+                    .export()
+                    .unwrap()
+            });
             s.spawn(|| self.counters.export().unwrap());
             s.spawn(|| self.date_data_vec.export().unwrap());
             s.spawn(|| self.tx_index_to_tx_data.export().unwrap());
             s.spawn(|| self.txout_index_to_address_index.export().unwrap());
+            s.spawn(|| self.txout_index_to_coinjoin_anonset.export().unwrap());
             s.spawn(|| self.txout_index_to_sats.export().unwrap());
         });
 
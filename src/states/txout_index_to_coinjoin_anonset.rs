@@ -0,0 +1,22 @@
+use std::collections::BTreeMap;
+
+use derive_deref::{Deref, DerefMut};
+use savefile_derive::Savefile;
+
+use crate::parse::TxoutIndex;
+
+use super::AnyState;
+
+/// Unspent equal outputs of detected CoinJoins
+#[derive(Default, Deref, DerefMut, Debug, Savefile)]
+pub struct TxoutIndexToCoinJoinAnonset(BTreeMap<TxoutIndex, u16>);
+
+impl AnyState for TxoutIndexToCoinJoinAnonset {
+    fn name<'a>() -> &'a str {
+        "txout_index_to_coinjoin_anonset"
+    }
+
+    fn clear(&mut self) {
+        self.0.clear();
+    }
+}
@@ -40,6 +40,7 @@ pub trait ArrayOperations<T> {
     where
         T: Sum + Copy + Default + AddAssign + SubAssign;
 
+    #[allow(unused)]
     fn moving_average(&self, x: usize) -> Vec<f32>
     where
         T: Sum + Copy + Default + AddAssign + SubAssign + ToF32;
@@ -137,10 +138,8 @@ impl<T> ArrayOperations<T> for &[T] {
             .map(|(index, value)| {
                 sum += *value;
 
-                if index >= x - 1 {
-                    let previous_index = index + 1 - x;
-
-                    sum -= *self.get(previous_index).unwrap()
+                if index >= x {
+                    sum -= *self.get(index - x).unwrap()
                 }
 
                 sum
@@ -160,8 +159,8 @@ impl<T> ArrayOperations<T> for &[T] {
             .map(|(index, value)| {
                 sum += *value;
 
-                if index >= x - 1 {
-                    sum -= *self.get(index + 1 - x).unwrap()
+                if index >= x {
+                    sum -= *self.get(index - x).unwrap()
                 }
 
                 sum.to_f32() / x as f32
@@ -192,7 +191,7 @@ impl<T> ArrayOperations<T> for &[T] {
     where
         T: FloatCore,
     {
-        let even = size % 2 == 0;
+        let even = size.is_multiple_of(2);
         let median_index = size / 2;
 
         if size < 3 {
@@ -226,3 +225,18 @@ impl<T> ArrayOperations<T> for &[T] {
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sums_full_windows_of_the_last_x_values() {
+        let values = [1.0, 2.0, 3.0, 4.0];
+        let values = values.as_slice();
+
+        assert_eq!(values.last_x_sum(2), vec![1.0, 3.0, 5.0, 7.0]);
+        assert_eq!(values.last_x_sum(1), values.to_vec());
+        assert_eq!(values.moving_average(2), vec![0.5, 1.5, 2.5, 3.5]);
+    }
+}
@@ -1,9 +1,13 @@
+mod arr;
 mod date;
 mod float;
 mod price;
+mod rolling_percentile;
 mod time;
 
+pub use arr::*;
 pub use date::*;
 pub use float::*;
 pub use price::*;
+pub use rolling_percentile::*;
 pub use time::*;
@@ -1,5 +1,14 @@
-pub fn convert_price_to_significant_cents(price: f32) -> u64 {
-    let mut price_in_cents = (price * 100.0).round() as u64;
+use crate::bitcoin::SATOSHIS_PER_BITCOIN;
+
+/// Realized values are kept in sats times cents so that they can be summed and subtracted without any rounding
+pub const SATS_CENTS_PER_DOLLAR: u128 = SATOSHIS_PER_BITCOIN as u128 * 100;
+
+pub fn convert_price_to_cents(price: f64) -> u64 {
+    (price * 100.0).round() as u64
+}
+
+pub fn convert_price_to_significant_cents(price: f64) -> u64 {
+    let mut price_in_cents = convert_price_to_cents(price);
 
     let ilog10 = price_in_cents.checked_ilog10().unwrap_or(0) as i32;
 
@@ -15,3 +24,12 @@ pub fn convert_price_to_significant_cents(price: f32) -> u64 {
 
     price_in_cents
 }
+
+/// Value of `sat_amount` at `price_in_cents`, in sats times cents
+pub fn compute_sats_cents(sat_amount: u64, price_in_cents: u64) -> u128 {
+    sat_amount as u128 * price_in_cents as u128
+}
+
+pub fn convert_sats_cents_to_dollars(sats_cents: u128) -> f64 {
+    sats_cents as f64 / SATS_CENTS_PER_DOLLAR as f64
+}
@@ -0,0 +1,194 @@
+use std::collections::BTreeMap;
+
+use ordered_float::{FloatCore, OrderedFloat};
+
+/// Sorted values of a sliding window split around the rank of a percentile, carried between the
+/// calls of `insert_rolling_percentile` so that each one only adds and removes a value
+pub struct RollingPercentile<K, T> {
+    size: usize,
+    percentile: f32,
+    /// Smallest values of the window, up to the one at the rank included, with their counts
+    lower: BTreeMap<OrderedFloat<T>, usize>,
+    lower_len: usize,
+    /// Every other value with their counts
+    upper: BTreeMap<OrderedFloat<T>, usize>,
+    upper_len: usize,
+    /// Key of the last value pushed, the window needs to be filled again when it isn't the previous one
+    last_key: Option<K>,
+}
+
+impl<K, T> RollingPercentile<K, T>
+where
+    K: PartialEq,
+    T: FloatCore,
+{
+    /// `percentile` being between `0` and `1`
+    pub fn new(size: usize, percentile: f32) -> Self {
+        if size < 1 {
+            panic!("A window needs at least one value");
+        }
+
+        if !(0.0..=1.0).contains(&percentile) {
+            panic!("A percentile needs to be between 0 and 1");
+        }
+
+        Self {
+            size,
+            percentile,
+            lower: BTreeMap::default(),
+            lower_len: 0,
+            upper: BTreeMap::default(),
+            upper_len: 0,
+            last_key: None,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn is_last_key(&self, key: Option<&K>) -> bool {
+        self.last_key.as_ref() == key
+    }
+
+    pub fn clear(&mut self) {
+        self.lower.clear();
+        self.lower_len = 0;
+        self.upper.clear();
+        self.upper_len = 0;
+        self.last_key = None;
+    }
+
+    pub fn push(&mut self, key: K, value: T) {
+        let value = OrderedFloat(value);
+
+        if self
+            .lower
+            .last_key_value()
+            .is_none_or(|(max, _)| value <= *max)
+        {
+            *self.lower.entry(value).or_default() += 1;
+            self.lower_len += 1;
+        } else {
+            *self.upper.entry(value).or_default() += 1;
+            self.upper_len += 1;
+        }
+
+        self.last_key = Some(key);
+
+        self.balance();
+    }
+
+    /// Removes a value that was pushed
+    pub fn remove(&mut self, value: T) {
+        let value = OrderedFloat(value);
+
+        if Self::take(&mut self.lower, value) {
+            self.lower_len -= 1;
+        } else if Self::take(&mut self.upper, value) {
+            self.upper_len -= 1;
+        }
+
+        self.balance();
+    }
+
+    /// Interpolated between the closest values, `None` until the window is full
+    pub fn get(&self) -> Option<T> {
+        if self.lower_len + self.upper_len < self.size {
+            return None;
+        }
+
+        let rank = self.percentile * (self.size - 1) as f32;
+        let fraction = T::from(rank - rank.floor()).unwrap();
+
+        let lower = self.lower.last_key_value()?.0 .0;
+
+        Some(match self.upper.first_key_value() {
+            Some((upper, _)) => lower + (upper.0 - lower) * fraction,
+            None => lower,
+        })
+    }
+
+    /// Moves values from one side to the other until `lower` ends at the rank
+    fn balance(&mut self) {
+        let len = self.lower_len + self.upper_len;
+
+        let rank = (self.percentile * (self.size - 1) as f32).floor() as usize;
+
+        let target = (rank + 1).min(len);
+
+        while self.lower_len > target {
+            let max = *self.lower.last_key_value().unwrap().0;
+            Self::take(&mut self.lower, max);
+            self.lower_len -= 1;
+            *self.upper.entry(max).or_default() += 1;
+            self.upper_len += 1;
+        }
+
+        while self.lower_len < target {
+            let min = *self.upper.first_key_value().unwrap().0;
+            Self::take(&mut self.upper, min);
+            self.upper_len -= 1;
+            *self.lower.entry(min).or_default() += 1;
+            self.lower_len += 1;
+        }
+    }
+
+    fn take(values: &mut BTreeMap<OrderedFloat<T>, usize>, value: OrderedFloat<T>) -> bool {
+        let Some(count) = values.get_mut(&value) else {
+            return false;
+        };
+
+        *count -= 1;
+
+        if *count == 0 {
+            values.remove(&value);
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn follows_the_percentile_of_the_window() {
+        let values = [5.0, 1.0, 4.0, 4.0, 2.0, 8.0, 3.0, 7.0];
+
+        let size = 4;
+
+        [0.0, 0.25, 0.5, 0.9, 1.0]
+            .into_iter()
+            .for_each(|percentile| {
+                let mut window = RollingPercentile::new(size, percentile);
+
+                values.iter().enumerate().for_each(|(index, value)| {
+                    window.push(index, *value);
+
+                    if let Some(previous) = index.checked_sub(size) {
+                        window.remove(values[previous]);
+                    }
+
+                    let expected = index.checked_sub(size - 1).map(|start| {
+                        let mut sorted = values[start..=index].to_vec();
+                        sorted.sort_by(f64::total_cmp);
+
+                        let rank = percentile as f64 * (size - 1) as f64;
+                        let lower = sorted[rank.floor() as usize];
+                        let upper = sorted[(rank.floor() as usize + 1).min(size - 1)];
+
+                        lower + (upper - lower) * rank.fract()
+                    });
+
+                    let close = window
+                        .get()
+                        .zip(expected)
+                        .is_some_and(|(value, expected)| (value - expected).abs() < 1e-6);
+
+                    assert!(close || window.get() == expected, "{percentile} at {index}");
+                });
+            });
+    }
+}